@@ -0,0 +1,17 @@
+// Whatever Midgard answers, parsing it gives a response or an error, never a panic.
+// Run from the repository root, seeded with the recorded responses:
+// `cargo +nightly fuzz run midgard_responses fuzz/corpus/midgard_responses tests/fixtures/midgard`
+#![no_main]
+
+use catalog_crypto_api::core::models::{
+    depth_history::DepthHistoryResponse, earnings_history::EarningsHistoryResponse,
+    runepool_units_history::RunepoolUnitsHistoryResponse, swap_history::SwapHistoryResponse,
+};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = serde_json::from_slice::<DepthHistoryResponse>(data);
+    let _ = serde_json::from_slice::<EarningsHistoryResponse>(data);
+    let _ = serde_json::from_slice::<SwapHistoryResponse>(data);
+    let _ = serde_json::from_slice::<RunepoolUnitsHistoryResponse>(data);
+});
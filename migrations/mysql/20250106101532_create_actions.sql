@@ -0,0 +1,20 @@
+-- Add migration script here
+CREATE TABLE `actions` (
+    id BIGINT AUTO_INCREMENT PRIMARY KEY,
+    tx_id VARCHAR(128) NOT NULL,
+    action_type VARCHAR(32) NOT NULL,
+    status VARCHAR(16) NOT NULL,
+    date TIMESTAMP(6) NOT NULL,
+    height BIGINT UNSIGNED NOT NULL,
+    pools JSON NOT NULL,
+    in_txs JSON NOT NULL,
+    out_txs JSON NOT NULL,
+    addresses JSON NOT NULL,
+    metadata JSON NOT NULL,
+    swap_slip BIGINT UNSIGNED NULL,
+    liquidity_fee BIGINT UNSIGNED NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE KEY uq_actions_tx (tx_id, action_type),
+    INDEX idx_actions_date (date),
+    INDEX idx_actions_type_date (action_type, date)
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4 COLLATE = utf8mb4_unicode_ci;
\ No newline at end of file
@@ -0,0 +1,11 @@
+-- Only the SHA-256 of a key is stored, create one with
+-- INSERT INTO `api_keys` (name, key_hash) VALUES ('some client', SHA2('<the key>', 256));
+CREATE TABLE `api_keys` (
+    id BIGINT AUTO_INCREMENT PRIMARY KEY,
+    name VARCHAR(128) NOT NULL,
+    key_hash CHAR(64) NOT NULL,
+    requests_per_minute INT UNSIGNED NOT NULL DEFAULT 60,
+    enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE KEY uq_api_keys_hash (key_hash)
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4 COLLATE = utf8mb4_unicode_ci;
\ No newline at end of file
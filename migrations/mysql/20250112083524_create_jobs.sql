@@ -0,0 +1,17 @@
+-- Backfills requested through the admin api, picked up by the job runner cron
+CREATE TABLE `jobs` (
+    id BIGINT AUTO_INCREMENT PRIMARY KEY,
+    dataset VARCHAR(16) NOT NULL,
+    pool VARCHAR(64) NULL,
+    from_time TIMESTAMP NOT NULL,
+    to_time TIMESTAMP NOT NULL,
+    status VARCHAR(16) NOT NULL DEFAULT 'queued',
+    cursor_time TIMESTAMP NULL,
+    intervals_fetched INT UNSIGNED NOT NULL DEFAULT 0,
+    error TEXT NULL,
+    requested_by VARCHAR(128) NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    started_at TIMESTAMP NULL,
+    finished_at TIMESTAMP NULL,
+    INDEX idx_jobs_status (status, id)
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4 COLLATE = utf8mb4_unicode_ci;
\ No newline at end of file
@@ -0,0 +1,42 @@
+-- Amounts in base units outgrow BIGINT UNSIGNED once summed up, counters stay BIGINT UNSIGNED
+
+ALTER TABLE depth_intervals
+    MODIFY asset_depth DECIMAL(38, 0) NOT NULL,
+    MODIFY liquidity_units DECIMAL(38, 0) NOT NULL,
+    MODIFY rune_depth DECIMAL(38, 0) NOT NULL,
+    MODIFY synth_supply DECIMAL(38, 0) NOT NULL,
+    MODIFY synth_units DECIMAL(38, 0) NOT NULL,
+    MODIFY units DECIMAL(38, 0) NOT NULL;
+
+ALTER TABLE earning_intervals
+    MODIFY block_rewards DECIMAL(38, 0) NOT NULL,
+    MODIFY bonding_earnings DECIMAL(38, 0) NOT NULL,
+    MODIFY earnings DECIMAL(38, 0) NOT NULL,
+    MODIFY liquidity_earnings DECIMAL(38, 0) NOT NULL,
+    MODIFY liquidity_fees DECIMAL(38, 0) NOT NULL;
+
+ALTER TABLE swap_intervals
+    MODIFY from_trade_fees DECIMAL(38, 0) NOT NULL,
+    MODIFY from_trade_volume DECIMAL(38, 0) NOT NULL,
+    MODIFY from_trade_volume_usd DECIMAL(38, 0) NOT NULL,
+    MODIFY synth_mint_fees DECIMAL(38, 0) NOT NULL,
+    MODIFY synth_mint_volume DECIMAL(38, 0) NOT NULL,
+    MODIFY synth_mint_volume_usd DECIMAL(38, 0) NOT NULL,
+    MODIFY synth_redeem_fees DECIMAL(38, 0) NOT NULL,
+    MODIFY synth_redeem_volume DECIMAL(38, 0) NOT NULL,
+    MODIFY synth_redeem_volume_usd DECIMAL(38, 0) NOT NULL,
+    MODIFY to_asset_fees DECIMAL(38, 0) NOT NULL,
+    MODIFY to_asset_volume DECIMAL(38, 0) NOT NULL,
+    MODIFY to_asset_volume_usd DECIMAL(38, 0) NOT NULL,
+    MODIFY to_rune_fees DECIMAL(38, 0) NOT NULL,
+    MODIFY to_rune_volume DECIMAL(38, 0) NOT NULL,
+    MODIFY to_rune_volume_usd DECIMAL(38, 0) NOT NULL,
+    MODIFY to_trade_fees DECIMAL(38, 0) NOT NULL,
+    MODIFY to_trade_volume DECIMAL(38, 0) NOT NULL,
+    MODIFY to_trade_volume_usd DECIMAL(38, 0) NOT NULL,
+    MODIFY total_fees DECIMAL(38, 0) NOT NULL,
+    MODIFY total_volume DECIMAL(38, 0) NOT NULL,
+    MODIFY total_volume_usd DECIMAL(38, 0) NOT NULL;
+
+ALTER TABLE runepool_unit_intervals
+    MODIFY units DECIMAL(38, 0) NOT NULL;
\ No newline at end of file
@@ -0,0 +1,11 @@
+-- Every in and out tx id of an action, a `txid` search matches any of them. Existing rows start with
+-- the id they were stored under and get the others when the actions cron refreshes them.
+-- One tx can hold several actions of a type, at different heights.
+ALTER TABLE actions ADD COLUMN tx_ids JSON NULL;
+
+UPDATE actions SET tx_ids = JSON_ARRAY(tx_id);
+
+ALTER TABLE actions
+    MODIFY tx_ids JSON NOT NULL,
+    DROP INDEX uq_actions_tx,
+    ADD UNIQUE KEY uq_actions_tx (tx_id, action_type, height);
@@ -0,0 +1,19 @@
+-- Create intervals table
+CREATE TABLE depth_intervals (
+    id BIGSERIAL PRIMARY KEY,
+    start_time TIMESTAMPTZ NOT NULL,
+    end_time TIMESTAMPTZ NOT NULL,
+    asset_depth BIGINT NOT NULL,
+    asset_price DOUBLE PRECISION NOT NULL,
+    asset_price_usd DOUBLE PRECISION NOT NULL,
+    liquidity_units BIGINT NOT NULL,
+    luvi DOUBLE PRECISION NOT NULL,
+    members_count INTEGER NOT NULL,
+    rune_depth BIGINT NOT NULL,
+    synth_supply BIGINT NOT NULL,
+    synth_units BIGINT NOT NULL,
+    units BIGINT NOT NULL,
+    created_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_time_range ON depth_intervals (start_time, end_time);
\ No newline at end of file
@@ -0,0 +1,17 @@
+-- Add migration script here
+CREATE TABLE earning_intervals (
+    id BIGSERIAL PRIMARY KEY,
+    start_time TIMESTAMPTZ NOT NULL,
+    end_time TIMESTAMPTZ NOT NULL,
+    avg_node_count DOUBLE PRECISION NOT NULL,
+    block_rewards BIGINT NOT NULL,
+    bonding_earnings BIGINT NOT NULL,
+    earnings BIGINT NOT NULL,
+    liquidity_earnings BIGINT NOT NULL,
+    liquidity_fees BIGINT NOT NULL,
+    rune_price_usd DOUBLE PRECISION NOT NULL,
+    pools JSONB NOT NULL,
+    created_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_earnings_time_range ON earning_intervals (start_time, end_time);
\ No newline at end of file
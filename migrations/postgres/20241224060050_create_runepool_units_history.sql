@@ -0,0 +1,11 @@
+-- Add migration script here
+CREATE TABLE runepool_unit_intervals (
+    id BIGSERIAL PRIMARY KEY,
+    start_time TIMESTAMPTZ NOT NULL,
+    end_time TIMESTAMPTZ NOT NULL,
+    count BIGINT NOT NULL,
+    units BIGINT NOT NULL,
+    created_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_runepool_units_time_range ON runepool_unit_intervals (start_time, end_time);
\ No newline at end of file
@@ -0,0 +1,24 @@
+-- Add migration script here
+CREATE TABLE actions (
+    id BIGSERIAL PRIMARY KEY,
+    tx_id VARCHAR(128) NOT NULL,
+    action_type VARCHAR(32) NOT NULL,
+    status VARCHAR(16) NOT NULL,
+    date TIMESTAMPTZ(6) NOT NULL,
+    height BIGINT NOT NULL,
+    pools JSONB NOT NULL,
+    in_txs JSONB NOT NULL,
+    out_txs JSONB NOT NULL,
+    addresses JSONB NOT NULL,
+    metadata JSONB NOT NULL,
+    swap_slip BIGINT NULL,
+    liquidity_fee BIGINT NULL,
+    created_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP,
+    CONSTRAINT uq_actions_tx UNIQUE (tx_id, action_type)
+);
+
+CREATE INDEX idx_actions_date ON actions (date);
+CREATE INDEX idx_actions_type_date ON actions (action_type, date);
+-- Address and pool searches use `@>`
+CREATE INDEX idx_actions_addresses ON actions USING GIN (addresses);
+CREATE INDEX idx_actions_pools ON actions USING GIN (pools);
\ No newline at end of file
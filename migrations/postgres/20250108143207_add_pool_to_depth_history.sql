@@ -0,0 +1,5 @@
+-- Depth history used to be ingested for ETH.ETH only, keep that as the default for existing rows
+ALTER TABLE depth_intervals
+    ADD COLUMN pool VARCHAR(64) NOT NULL DEFAULT 'ETH.ETH';
+
+CREATE INDEX idx_depth_pool_time_range ON depth_intervals (pool, start_time, end_time);
\ No newline at end of file
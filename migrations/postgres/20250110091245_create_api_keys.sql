@@ -0,0 +1,11 @@
+-- Only the SHA-256 of a key is stored, create one with
+-- INSERT INTO api_keys (name, key_hash) VALUES ('some client', encode(sha256('<the key>'), 'hex'));
+CREATE TABLE api_keys (
+    id BIGSERIAL PRIMARY KEY,
+    name VARCHAR(128) NOT NULL,
+    key_hash CHAR(64) NOT NULL,
+    requests_per_minute INTEGER NOT NULL DEFAULT 60,
+    enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP,
+    CONSTRAINT uq_api_keys_hash UNIQUE (key_hash)
+);
\ No newline at end of file
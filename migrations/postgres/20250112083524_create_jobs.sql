@@ -0,0 +1,18 @@
+-- Backfills requested through the admin api, picked up by the job runner cron
+CREATE TABLE jobs (
+    id BIGSERIAL PRIMARY KEY,
+    dataset VARCHAR(16) NOT NULL,
+    pool VARCHAR(64) NULL,
+    from_time TIMESTAMPTZ NOT NULL,
+    to_time TIMESTAMPTZ NOT NULL,
+    status VARCHAR(16) NOT NULL DEFAULT 'queued',
+    cursor_time TIMESTAMPTZ NULL,
+    intervals_fetched INTEGER NOT NULL DEFAULT 0,
+    error TEXT NULL,
+    requested_by VARCHAR(128) NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    started_at TIMESTAMPTZ NULL,
+    finished_at TIMESTAMPTZ NULL
+);
+
+CREATE INDEX idx_jobs_status ON jobs (status, id);
\ No newline at end of file
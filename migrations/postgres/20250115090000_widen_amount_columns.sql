@@ -0,0 +1,42 @@
+-- Amounts in base units outgrow BIGINT (cumulative volumes pass 9.2e18), counters stay BIGINT
+
+ALTER TABLE depth_intervals
+    ALTER COLUMN asset_depth TYPE NUMERIC(38, 0),
+    ALTER COLUMN liquidity_units TYPE NUMERIC(38, 0),
+    ALTER COLUMN rune_depth TYPE NUMERIC(38, 0),
+    ALTER COLUMN synth_supply TYPE NUMERIC(38, 0),
+    ALTER COLUMN synth_units TYPE NUMERIC(38, 0),
+    ALTER COLUMN units TYPE NUMERIC(38, 0);
+
+ALTER TABLE earning_intervals
+    ALTER COLUMN block_rewards TYPE NUMERIC(38, 0),
+    ALTER COLUMN bonding_earnings TYPE NUMERIC(38, 0),
+    ALTER COLUMN earnings TYPE NUMERIC(38, 0),
+    ALTER COLUMN liquidity_earnings TYPE NUMERIC(38, 0),
+    ALTER COLUMN liquidity_fees TYPE NUMERIC(38, 0);
+
+ALTER TABLE swap_intervals
+    ALTER COLUMN from_trade_fees TYPE NUMERIC(38, 0),
+    ALTER COLUMN from_trade_volume TYPE NUMERIC(38, 0),
+    ALTER COLUMN from_trade_volume_usd TYPE NUMERIC(38, 0),
+    ALTER COLUMN synth_mint_fees TYPE NUMERIC(38, 0),
+    ALTER COLUMN synth_mint_volume TYPE NUMERIC(38, 0),
+    ALTER COLUMN synth_mint_volume_usd TYPE NUMERIC(38, 0),
+    ALTER COLUMN synth_redeem_fees TYPE NUMERIC(38, 0),
+    ALTER COLUMN synth_redeem_volume TYPE NUMERIC(38, 0),
+    ALTER COLUMN synth_redeem_volume_usd TYPE NUMERIC(38, 0),
+    ALTER COLUMN to_asset_fees TYPE NUMERIC(38, 0),
+    ALTER COLUMN to_asset_volume TYPE NUMERIC(38, 0),
+    ALTER COLUMN to_asset_volume_usd TYPE NUMERIC(38, 0),
+    ALTER COLUMN to_rune_fees TYPE NUMERIC(38, 0),
+    ALTER COLUMN to_rune_volume TYPE NUMERIC(38, 0),
+    ALTER COLUMN to_rune_volume_usd TYPE NUMERIC(38, 0),
+    ALTER COLUMN to_trade_fees TYPE NUMERIC(38, 0),
+    ALTER COLUMN to_trade_volume TYPE NUMERIC(38, 0),
+    ALTER COLUMN to_trade_volume_usd TYPE NUMERIC(38, 0),
+    ALTER COLUMN total_fees TYPE NUMERIC(38, 0),
+    ALTER COLUMN total_volume TYPE NUMERIC(38, 0),
+    ALTER COLUMN total_volume_usd TYPE NUMERIC(38, 0);
+
+ALTER TABLE runepool_unit_intervals
+    ALTER COLUMN units TYPE NUMERIC(38, 0);
\ No newline at end of file
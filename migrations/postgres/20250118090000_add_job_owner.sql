@@ -0,0 +1,5 @@
+-- The runner working on a job and when it last got a page further. Only jobs whose heartbeat went
+-- stale are taken over, a job another instance is running is left alone.
+ALTER TABLE jobs
+    ADD COLUMN owner VARCHAR(128) NULL,
+    ADD COLUMN heartbeat_at TIMESTAMPTZ NULL;
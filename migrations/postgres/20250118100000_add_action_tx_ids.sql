@@ -0,0 +1,13 @@
+-- Every in and out tx id of an action, a `txid` search matches any of them. Existing rows start with
+-- the id they were stored under and get the others when the actions cron refreshes them.
+-- One tx can hold several actions of a type, at different heights.
+ALTER TABLE actions ADD COLUMN tx_ids JSONB NULL;
+
+UPDATE actions SET tx_ids = jsonb_build_array(tx_id);
+
+ALTER TABLE actions
+    ALTER COLUMN tx_ids SET NOT NULL,
+    DROP CONSTRAINT uq_actions_tx,
+    ADD CONSTRAINT uq_actions_tx UNIQUE (tx_id, action_type, height);
+
+CREATE INDEX idx_actions_tx_ids ON actions USING GIN (tx_ids);
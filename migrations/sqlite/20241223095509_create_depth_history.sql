@@ -0,0 +1,20 @@
+-- Create intervals table
+-- Times are stored as RFC 3339 text, which sorts and compares like the times themselves
+CREATE TABLE depth_intervals (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    start_time TEXT NOT NULL,
+    end_time TEXT NOT NULL,
+    asset_depth INTEGER NOT NULL,
+    asset_price REAL NOT NULL,
+    asset_price_usd REAL NOT NULL,
+    liquidity_units INTEGER NOT NULL,
+    luvi REAL NOT NULL,
+    members_count INTEGER NOT NULL,
+    rune_depth INTEGER NOT NULL,
+    synth_supply INTEGER NOT NULL,
+    synth_units INTEGER NOT NULL,
+    units INTEGER NOT NULL,
+    created_at TEXT DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_time_range ON depth_intervals (start_time, end_time);
\ No newline at end of file
@@ -0,0 +1,17 @@
+-- Add migration script here
+CREATE TABLE earning_intervals (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    start_time TEXT NOT NULL,
+    end_time TEXT NOT NULL,
+    avg_node_count REAL NOT NULL,
+    block_rewards INTEGER NOT NULL,
+    bonding_earnings INTEGER NOT NULL,
+    earnings INTEGER NOT NULL,
+    liquidity_earnings INTEGER NOT NULL,
+    liquidity_fees INTEGER NOT NULL,
+    rune_price_usd REAL NOT NULL,
+    pools TEXT NOT NULL,
+    created_at TEXT DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_earnings_time_range ON earning_intervals (start_time, end_time);
\ No newline at end of file
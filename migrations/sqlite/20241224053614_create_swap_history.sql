@@ -0,0 +1,45 @@
+-- Add migration script here
+CREATE TABLE swap_intervals (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    start_time TEXT NOT NULL,
+    end_time TEXT NOT NULL,
+    average_slip REAL NOT NULL,
+    from_trade_average_slip REAL NOT NULL,
+    from_trade_count INTEGER NOT NULL,
+    from_trade_fees INTEGER NOT NULL,
+    from_trade_volume INTEGER NOT NULL,
+    from_trade_volume_usd INTEGER NOT NULL,
+    rune_price_usd REAL NOT NULL,
+    synth_mint_average_slip REAL NOT NULL,
+    synth_mint_count INTEGER NOT NULL,
+    synth_mint_fees INTEGER NOT NULL,
+    synth_mint_volume INTEGER NOT NULL,
+    synth_mint_volume_usd INTEGER NOT NULL,
+    synth_redeem_average_slip REAL NOT NULL,
+    synth_redeem_count INTEGER NOT NULL,
+    synth_redeem_fees INTEGER NOT NULL,
+    synth_redeem_volume INTEGER NOT NULL,
+    synth_redeem_volume_usd INTEGER NOT NULL,
+    to_asset_average_slip REAL NOT NULL,
+    to_asset_count INTEGER NOT NULL,
+    to_asset_fees INTEGER NOT NULL,
+    to_asset_volume INTEGER NOT NULL,
+    to_asset_volume_usd INTEGER NOT NULL,
+    to_rune_average_slip REAL NOT NULL,
+    to_rune_count INTEGER NOT NULL,
+    to_rune_fees INTEGER NOT NULL,
+    to_rune_volume INTEGER NOT NULL,
+    to_rune_volume_usd INTEGER NOT NULL,
+    to_trade_average_slip REAL NOT NULL,
+    to_trade_count INTEGER NOT NULL,
+    to_trade_fees INTEGER NOT NULL,
+    to_trade_volume INTEGER NOT NULL,
+    to_trade_volume_usd INTEGER NOT NULL,
+    total_count INTEGER NOT NULL,
+    total_fees INTEGER NOT NULL,
+    total_volume INTEGER NOT NULL,
+    total_volume_usd INTEGER NOT NULL,
+    created_at TEXT DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_swap_time_range ON swap_intervals (start_time, end_time);
\ No newline at end of file
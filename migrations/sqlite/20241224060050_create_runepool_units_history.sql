@@ -0,0 +1,11 @@
+-- Add migration script here
+CREATE TABLE runepool_unit_intervals (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    start_time TEXT NOT NULL,
+    end_time TEXT NOT NULL,
+    count INTEGER NOT NULL,
+    units INTEGER NOT NULL,
+    created_at TEXT DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_runepool_units_time_range ON runepool_unit_intervals (start_time, end_time);
\ No newline at end of file
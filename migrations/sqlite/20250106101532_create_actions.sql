@@ -0,0 +1,22 @@
+-- Add migration script here
+-- The json columns are plain text, address and pool searches go through json_each
+CREATE TABLE actions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    tx_id TEXT NOT NULL,
+    action_type TEXT NOT NULL,
+    status TEXT NOT NULL,
+    date TEXT NOT NULL,
+    height INTEGER NOT NULL,
+    pools TEXT NOT NULL,
+    in_txs TEXT NOT NULL,
+    out_txs TEXT NOT NULL,
+    addresses TEXT NOT NULL,
+    metadata TEXT NOT NULL,
+    swap_slip INTEGER NULL,
+    liquidity_fee INTEGER NULL,
+    created_at TEXT DEFAULT CURRENT_TIMESTAMP,
+    CONSTRAINT uq_actions_tx UNIQUE (tx_id, action_type)
+);
+
+CREATE INDEX idx_actions_date ON actions (date);
+CREATE INDEX idx_actions_type_date ON actions (action_type, date);
\ No newline at end of file
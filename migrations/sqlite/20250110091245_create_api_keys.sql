@@ -0,0 +1,11 @@
+-- Only the SHA-256 of a key is stored, SQLite can't hash so compute it outside, e.g. with
+-- `printf '<the key>' | sha256sum`, and INSERT INTO api_keys (name, key_hash) VALUES ('some client', '<hash>');
+CREATE TABLE api_keys (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL,
+    key_hash TEXT NOT NULL,
+    requests_per_minute INTEGER NOT NULL DEFAULT 60,
+    enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TEXT DEFAULT CURRENT_TIMESTAMP,
+    CONSTRAINT uq_api_keys_hash UNIQUE (key_hash)
+);
\ No newline at end of file
@@ -0,0 +1,3 @@
+-- Admin keys can use the /admin routes (backfills and job management)
+ALTER TABLE api_keys
+    ADD COLUMN is_admin BOOLEAN NOT NULL DEFAULT FALSE;
\ No newline at end of file
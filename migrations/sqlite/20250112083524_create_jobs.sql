@@ -0,0 +1,18 @@
+-- Backfills requested through the admin api, picked up by the job runner cron
+CREATE TABLE jobs (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    dataset TEXT NOT NULL,
+    pool TEXT NULL,
+    from_time TEXT NOT NULL,
+    to_time TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'queued',
+    cursor_time TEXT NULL,
+    intervals_fetched INTEGER NOT NULL DEFAULT 0,
+    error TEXT NULL,
+    requested_by TEXT NULL,
+    created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    started_at TEXT NULL,
+    finished_at TEXT NULL
+);
+
+CREATE INDEX idx_jobs_status ON jobs (status, id);
\ No newline at end of file
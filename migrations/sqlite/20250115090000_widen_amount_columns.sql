@@ -0,0 +1,3 @@
+-- SQLite has no 128 bit integers or exact decimals, amount columns stay 64 bit INTEGERs and an
+-- amount past 9.2e18 is refused when stored. Kept so every backend has the same migrations.
+SELECT 1;
\ No newline at end of file
@@ -0,0 +1,4 @@
+-- The runner working on a job and when it last got a page further. Only jobs whose heartbeat went
+-- stale are taken over, a job another instance is running is left alone.
+ALTER TABLE jobs ADD COLUMN owner TEXT NULL;
+ALTER TABLE jobs ADD COLUMN heartbeat_at TEXT NULL;
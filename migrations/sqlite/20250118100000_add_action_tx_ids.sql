@@ -0,0 +1,37 @@
+-- Every in and out tx id of an action, a `txid` search matches any of them. Existing rows start with
+-- the id they were stored under and get the others when the actions cron refreshes them.
+-- One tx can hold several actions of a type, at different heights. SQLite can't change a
+-- constraint in place, so the table is rebuilt.
+CREATE TABLE actions_new (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    tx_id TEXT NOT NULL,
+    tx_ids TEXT NOT NULL,
+    action_type TEXT NOT NULL,
+    status TEXT NOT NULL,
+    date TEXT NOT NULL,
+    height INTEGER NOT NULL,
+    pools TEXT NOT NULL,
+    in_txs TEXT NOT NULL,
+    out_txs TEXT NOT NULL,
+    addresses TEXT NOT NULL,
+    metadata TEXT NOT NULL,
+    swap_slip INTEGER NULL,
+    liquidity_fee INTEGER NULL,
+    created_at TEXT DEFAULT CURRENT_TIMESTAMP,
+    CONSTRAINT uq_actions_tx UNIQUE (tx_id, action_type, height)
+);
+
+INSERT INTO actions_new (
+    id, tx_id, tx_ids, action_type, status, date, height, pools, in_txs, out_txs, addresses,
+    metadata, swap_slip, liquidity_fee, created_at
+)
+SELECT
+    id, tx_id, json_array(tx_id), action_type, status, date, height, pools, in_txs, out_txs,
+    addresses, metadata, swap_slip, liquidity_fee, created_at
+FROM actions;
+
+DROP TABLE actions;
+ALTER TABLE actions_new RENAME TO actions;
+
+CREATE INDEX idx_actions_date ON actions (date);
+CREATE INDEX idx_actions_type_date ON actions (action_type, date);
@@ -0,0 +1,100 @@
+use crate::api::error::error_response;
+use crate::api::rate_limit::RateLimiter;
+use crate::core::models::api_key::{hash_key, ApiKey};
+use crate::services::repository::api_keys::find_by_hash;
+use crate::services::storage::DbPool;
+use axum::{
+    extract::{Request, State},
+    http::StatusCode,
+    middleware::Next,
+    response::Response,
+};
+use chrono::Utc;
+use moka::future::Cache;
+use std::sync::Arc;
+use std::time::Duration;
+
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+// How long a looked up key is trusted before asking the database again, so disabling a key
+// takes effect within this delay
+const KEY_CACHE_TTL: Duration = Duration::from_secs(60);
+
+#[derive(Clone)]
+pub struct ApiKeyAuth {
+    pool: DbPool,
+    // Keyed by the key hash, `None` remembers unknown keys too
+    keys: Cache<String, Option<ApiKey>>,
+    limiter: Arc<RateLimiter>,
+}
+
+impl ApiKeyAuth {
+    pub fn new(pool: DbPool) -> Self {
+        Self {
+            pool,
+            keys: Cache::builder()
+                .max_capacity(10_000)
+                .time_to_live(KEY_CACHE_TTL)
+                .build(),
+            limiter: Arc::new(RateLimiter::default()),
+        }
+    }
+}
+
+// Rejects requests without a valid, enabled `x-api-key` and enforces the key's quota
+pub async fn require_api_key(
+    State(auth): State<ApiKeyAuth>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let Some(key) = request
+        .headers()
+        .get(API_KEY_HEADER)
+        .and_then(|value| value.to_str().ok())
+    else {
+        return error_response(StatusCode::UNAUTHORIZED, "Missing x-api-key header");
+    };
+
+    let key_hash = hash_key(key);
+    let api_key = auth
+        .keys
+        .try_get_with(key_hash.clone(), find_by_hash(&auth.pool, &key_hash))
+        .await;
+
+    let api_key = match api_key {
+        Ok(Some(api_key)) if api_key.enabled => api_key,
+        Ok(_) => return error_response(StatusCode::UNAUTHORIZED, "Invalid api key"),
+        Err(e) => {
+            tracing::error!("Database error when looking up api key: {}", e);
+            return error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Could not verify the api key",
+            );
+        }
+    };
+
+    let now = Utc::now().timestamp();
+    let rate_limit = auth
+        .limiter
+        .check(api_key.id, api_key.requests_per_minute, now);
+    if !rate_limit.allowed {
+        tracing::debug!("Api key {} is over its quota", api_key.name);
+        return rate_limit.too_many_requests(now);
+    }
+
+    request.extensions_mut().insert(api_key);
+    rate_limit.apply(next.run(request).await)
+}
+
+// Goes after `require_api_key`, which leaves the caller's key in the request extensions
+pub async fn require_admin(request: Request, next: Next) -> Response {
+    let is_admin = request
+        .extensions()
+        .get::<ApiKey>()
+        .is_some_and(|api_key| api_key.is_admin);
+    if !is_admin {
+        return error_response(StatusCode::FORBIDDEN, "Admin api key required");
+    }
+
+    next.run(request).await
+}
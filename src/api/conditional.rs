@@ -0,0 +1,172 @@
+use axum::{
+    http::{header, HeaderMap, HeaderValue, StatusCode, Uri},
+    response::{IntoResponse, Response},
+};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+const HTTP_DATE_FORMAT: &str = "%a, %d %b %Y %H:%M:%S GMT";
+
+// ETag and Last-Modified for a page of history intervals. The ETag is a SHA-256 of the query and
+// of the intervals as they are serialized for this request, so an interval ingested again with
+// other figures changes it, and so does the number mode. It stays the same across releases.
+pub struct Validators {
+    etag: String,
+    last_modified: DateTime<Utc>,
+}
+
+impl Validators {
+    pub fn new<T: Serialize>(
+        uri: &Uri,
+        intervals: &[T],
+        end_times: impl IntoIterator<Item = DateTime<Utc>>,
+    ) -> Self {
+        let last_modified = end_times
+            .into_iter()
+            .max()
+            .unwrap_or(DateTime::<Utc>::UNIX_EPOCH);
+
+        let mut hasher = Sha256::new();
+        hasher.update(uri.path());
+        hasher.update([0]);
+        hasher.update(uri.query().unwrap_or_default());
+        hasher.update([0]);
+        serde_json::to_writer(&mut hasher, intervals).expect("intervals serialize to JSON");
+
+        Self {
+            // Weak since the compression layer may re-encode the body
+            etag: format!("W/\"{:x}\"", hasher.finalize()),
+            last_modified,
+        }
+    }
+
+    fn headers(&self) -> [(header::HeaderName, HeaderValue); 3] {
+        let last_modified = self.last_modified.format(HTTP_DATE_FORMAT).to_string();
+        [
+            (
+                header::ETAG,
+                HeaderValue::from_str(&self.etag).expect("etag is ascii"),
+            ),
+            (
+                header::LAST_MODIFIED,
+                HeaderValue::from_str(&last_modified).expect("http date is ascii"),
+            ),
+            (header::CACHE_CONTROL, HeaderValue::from_static("no-cache")),
+        ]
+    }
+
+    // A 304 when the client already has this payload
+    pub fn check(&self, headers: &HeaderMap) -> Option<Response> {
+        is_fresh(headers, &self.etag, self.last_modified.timestamp())
+            .then(|| (StatusCode::NOT_MODIFIED, self.headers()).into_response())
+    }
+
+    pub fn apply(&self, mut response: Response) -> Response {
+        response.headers_mut().extend(self.headers());
+        response
+    }
+}
+
+fn is_fresh(headers: &HeaderMap, etag: &str, last_modified: i64) -> bool {
+    // If-None-Match takes precedence over If-Modified-Since (RFC 9110 13.2.2)
+    if let Some(if_none_match) = headers
+        .get(header::IF_NONE_MATCH)
+        .and_then(|value| value.to_str().ok())
+    {
+        return if_none_match.split(',').any(|candidate| {
+            let candidate = candidate.trim();
+            candidate == "*" || candidate.trim_start_matches("W/") == etag.trim_start_matches("W/")
+        });
+    }
+
+    headers
+        .get(header::IF_MODIFIED_SINCE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| DateTime::parse_from_rfc2822(value).ok())
+        .is_some_and(|since| last_modified <= since.timestamp())
+}
+
+// Same as `Validators::check` for a response built earlier, e.g. one served from the query cache
+pub fn check_response(headers: &HeaderMap, response_headers: &HeaderMap) -> Option<Response> {
+    let etag = response_headers.get(header::ETAG)?;
+    let last_modified = response_headers
+        .get(header::LAST_MODIFIED)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| DateTime::parse_from_rfc2822(value).ok())?;
+
+    is_fresh(headers, etag.to_str().ok()?, last_modified.timestamp()).then(|| {
+        let mut response = StatusCode::NOT_MODIFIED.into_response();
+        for name in [header::ETAG, header::LAST_MODIFIED, header::CACHE_CONTROL] {
+            if let Some(value) = response_headers.get(&name) {
+                response.headers_mut().insert(name, value.clone());
+            }
+        }
+        response
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn validators_of(query: &str, volumes: &[u64]) -> Validators {
+        let uri: Uri = format!("/swap_history?{}", query).parse().unwrap();
+        let end_times = (1..=volumes.len() as u32)
+            .map(|hour| Utc.with_ymd_and_hms(2024, 8, 1, hour, 0, 0).unwrap());
+        Validators::new(&uri, volumes, end_times)
+    }
+
+    fn validators(query: &str) -> Validators {
+        validators_of(query, &[100, 200])
+    }
+
+    fn headers(name: header::HeaderName, value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(name, HeaderValue::from_str(value).unwrap());
+        headers
+    }
+
+    #[test]
+    fn matching_etag_is_not_modified() {
+        let validators = validators("limit=2");
+        let response = validators
+            .check(&headers(header::IF_NONE_MATCH, &validators.etag))
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+        assert!(validators.check(&HeaderMap::new()).is_none());
+    }
+
+    #[test]
+    fn etag_depends_on_the_query() {
+        let first = validators("limit=2");
+        assert_ne!(first.etag, validators("limit=3").etag);
+        assert!(validators("limit=3")
+            .check(&headers(header::IF_NONE_MATCH, &first.etag))
+            .is_none());
+    }
+
+    #[test]
+    fn etag_depends_on_the_content() {
+        assert_eq!(validators("").etag, validators_of("", &[100, 200]).etag);
+        assert_ne!(validators("").etag, validators_of("", &[100, 201]).etag);
+    }
+
+    #[test]
+    fn last_modified_is_the_latest_end_time() {
+        let validators = validators("");
+        assert!(validators
+            .check(&headers(
+                header::IF_MODIFIED_SINCE,
+                "Thu, 01 Aug 2024 02:00:00 GMT"
+            ))
+            .is_some());
+        assert!(validators
+            .check(&headers(
+                header::IF_MODIFIED_SINCE,
+                "Thu, 01 Aug 2024 01:59:59 GMT"
+            ))
+            .is_none());
+    }
+}
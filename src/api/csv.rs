@@ -0,0 +1,244 @@
+use crate::core::models::dataset::Dataset;
+use crate::core::models::units::{Prices, Units};
+use crate::services::storage::{Column, DbPool, Kind, Query, Storage, Value};
+use axum::{
+    body::Body,
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+};
+use futures::TryStreamExt;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tracing::{error, info};
+
+// Rows are sent to the client in chunks of this many lines
+const CSV_CHUNK_ROWS: usize = 500;
+
+// `format=csv` wins over the Accept header, so `format=json` can force JSON from a browser
+pub fn wants_csv(format: Option<&str>, headers: &HeaderMap) -> bool {
+    if let Some(format) = format {
+        return format.eq_ignore_ascii_case("csv");
+    }
+
+    headers
+        .get(header::ACCEPT)
+        .and_then(|accept| accept.to_str().ok())
+        .is_some_and(|accept| {
+            accept
+                .split(',')
+                .any(|media_type| media_type.trim().starts_with("text/csv"))
+        })
+}
+
+// Quotes a field only when it needs it
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+// Timestamps, the pool for per pool tables, then every numeric column of the dataset.
+// Nested values (the earnings `pools` array) are left out.
+fn csv_columns(dataset: Dataset) -> Vec<Column> {
+    let mut columns = vec![("start_time", Kind::Time), ("end_time", Kind::Time)];
+    if dataset.has_pool() {
+        columns.push(("pool", Kind::Text));
+    }
+    columns.extend(
+        dataset
+            .numeric_columns()
+            .iter()
+            .map(|column| (column.name, column.kind.into())),
+    );
+    columns
+}
+
+fn csv_header(dataset: Dataset) -> String {
+    let names: Vec<&str> = csv_columns(dataset).iter().map(|(name, _)| *name).collect();
+    names.join(",") + "\n"
+}
+
+fn csv_record(values: &[Value]) -> String {
+    let fields: Vec<String> = values
+        .iter()
+        .map(|value| match value {
+            Value::Time(time) => time.timestamp().to_string(),
+            Value::Text(text) => csv_field(text),
+            Value::Json(document) => csv_field(&document.to_string()),
+            Value::Int(number) => number.to_string(),
+            Value::UInt(number) => number.to_string(),
+            Value::Amount(number) => number.to_string(),
+            Value::Float(number) => number.to_string(),
+            Value::Bool(flag) => flag.to_string(),
+            Value::Null => String::new(),
+        })
+        .collect();
+    fields.join(",") + "\n"
+}
+
+// Puts the amounts of a row in `units`, at the prices stored in the same row
+fn convert_record(dataset: Dataset, columns: &[Column], values: &mut [Value], units: Units) {
+    if units == Units::Base {
+        return;
+    }
+    let prices = Prices::of_row(dataset, |name| {
+        let index = columns.iter().position(|(column, _)| *column == name)?;
+        match values[index] {
+            Value::Float(price) => Some(price),
+            _ => None,
+        }
+    });
+    for ((name, _), value) in columns.iter().zip(values.iter_mut()) {
+        let (Some(denomination), Value::Amount(amount)) = (dataset.denomination(name), &value)
+        else {
+            continue;
+        };
+        *value = units
+            .convert(*amount as f64, denomination, &prices)
+            .map_or(Value::Null, Value::Float);
+    }
+}
+
+// Streams every row of `query` (which should select `*` from the dataset's table, without any
+// LIMIT) as CSV. The query runs in its own task so rows are never all held in memory.
+pub fn stream_csv(pool: DbPool, query: Query, dataset: Dataset, units: Units) -> Response {
+    let (tx, rx) = mpsc::channel::<Result<String, std::io::Error>>(4);
+
+    tokio::spawn(async move {
+        let columns = csv_columns(dataset);
+        let mut rows = pool.fetch_rows(&query, &columns);
+        let mut chunk = csv_header(dataset);
+        let mut chunk_rows = 0;
+        let mut total_rows = 0;
+
+        loop {
+            match rows.try_next().await {
+                Ok(Some(mut values)) => {
+                    convert_record(dataset, &columns, &mut values, units);
+                    chunk.push_str(&csv_record(&values));
+                }
+                Ok(None) => break,
+                Err(e) => {
+                    error!("Database error when exporting {} as CSV: {}", dataset, e);
+                    let _ = tx.send(Err(std::io::Error::other(e))).await;
+                    return;
+                }
+            }
+
+            chunk_rows += 1;
+            total_rows += 1;
+            if chunk_rows == CSV_CHUNK_ROWS {
+                if tx.send(Ok(std::mem::take(&mut chunk))).await.is_err() {
+                    info!("Client went away during the {} CSV export", dataset);
+                    return;
+                }
+                chunk_rows = 0;
+            }
+        }
+
+        if !chunk.is_empty() {
+            let _ = tx.send(Ok(chunk)).await;
+        }
+        info!("Exported {} {} rows as CSV", total_rows, dataset);
+    });
+
+    (
+        StatusCode::OK,
+        [
+            (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}_history.csv\"", dataset),
+            ),
+        ],
+        Body::from_stream(ReceiverStream::new(rx)),
+    )
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    #[test]
+    fn format_param_wins_over_accept_header() {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::ACCEPT, HeaderValue::from_static("text/csv"));
+
+        assert!(wants_csv(None, &headers));
+        assert!(wants_csv(Some("CSV"), &HeaderMap::new()));
+        assert!(!wants_csv(Some("json"), &headers));
+        assert!(!wants_csv(None, &HeaderMap::new()));
+    }
+
+    #[test]
+    fn accept_header_with_several_media_types() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::ACCEPT,
+            HeaderValue::from_static("application/json;q=0.9, text/csv;q=1.0"),
+        );
+        assert!(wants_csv(None, &headers));
+    }
+
+    #[test]
+    fn header_lists_pool_only_for_depth() {
+        assert_eq!(
+            csv_header(Dataset::Runepool),
+            "start_time,end_time,count,units\n"
+        );
+        assert!(csv_header(Dataset::Depth).starts_with("start_time,end_time,pool,asset_depth,"));
+    }
+
+    #[test]
+    fn records_follow_the_columns() {
+        let start = chrono::DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        let values = [
+            Value::Time(start),
+            Value::Text("BTC.BTC".into()),
+            Value::UInt(42),
+            Value::Float(0.5),
+        ];
+        assert_eq!(csv_record(&values), "1700000000,BTC.BTC,42,0.5\n");
+    }
+
+    #[test]
+    fn amounts_are_converted_at_the_row_price() {
+        let columns = csv_columns(Dataset::Swap);
+        let row = || -> Vec<Value> {
+            columns
+                .iter()
+                .map(|(name, kind)| match (*name, kind) {
+                    ("rune_price_usd", _) => Value::Float(2.0),
+                    (_, Kind::Amount) => Value::Amount(300_000_000),
+                    (_, Kind::UInt) => Value::UInt(3),
+                    (_, Kind::Float) => Value::Float(0.5),
+                    _ => Value::Time(chrono::DateTime::UNIX_EPOCH),
+                })
+                .collect()
+        };
+        let column = |values: &[Value], name: &str| {
+            let index = columns.iter().position(|(column, _)| *column == name);
+            values[index.unwrap()].clone()
+        };
+
+        let mut usd = row();
+        convert_record(Dataset::Swap, &columns, &mut usd, Units::Usd);
+        assert_eq!(column(&usd, "total_volume"), Value::Float(6.0));
+        assert_eq!(column(&usd, "total_volume_usd"), Value::Float(3.0));
+        assert_eq!(column(&usd, "total_count"), Value::UInt(3));
+
+        let mut base = row();
+        convert_record(Dataset::Swap, &columns, &mut base, Units::Base);
+        assert_eq!(base, row());
+    }
+
+    #[test]
+    fn fields_are_quoted_when_needed() {
+        assert_eq!(csv_field("ETH.ETH"), "ETH.ETH");
+        assert_eq!(csv_field("a,\"b\""), "\"a,\"\"b\"\"\"");
+    }
+}
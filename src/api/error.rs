@@ -0,0 +1,41 @@
+// JSON error bodies of the routes, `{"success": false, "error": ...}`, and the answer to a query
+// that matched nothing
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde_json::json;
+use tracing::error;
+
+pub fn error_response(status: StatusCode, message: &str) -> Response {
+    (
+        status,
+        Json(json!({
+            "success": false,
+            "error": message
+        })),
+    )
+        .into_response()
+}
+
+pub fn bad_request(message: &str) -> Response {
+    error_response(StatusCode::BAD_REQUEST, message)
+}
+
+pub fn no_data() -> Response {
+    Json(json!({
+        "success": true,
+        "data": "no data found in the database for the given params"
+    }))
+    .into_response()
+}
+
+// Logs `e` along with what the route was `doing` and answers with a 500
+pub fn database_error(doing: &str, e: sqlx::Error) -> Response {
+    error!("Database error when {}: {}", doing, e);
+    error_response(
+        StatusCode::INTERNAL_SERVER_ERROR,
+        &format!("Database error: {}", e),
+    )
+}
@@ -0,0 +1,494 @@
+use crate::core::models::dataset::Dataset;
+use crate::core::models::export::ExportFormat;
+use crate::services::storage::{Column, DbPool, Kind, Storage, Value};
+use arrow_array::{
+    ArrayRef, Decimal128Array, Float64Array, Int64Array, RecordBatch, StringArray,
+    TimestampSecondArray, UInt64Array,
+};
+use arrow_schema::{DataType, Field, Schema, SchemaRef, TimeUnit};
+use axum::{
+    body::Body,
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+};
+use chrono::{DateTime, SecondsFormat, Utc};
+use futures::TryStreamExt;
+use parquet::{arrow::ArrowWriter, basic::Compression, file::properties::WriterProperties};
+use serde_json::{Map, Value as JsonValue};
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tracing::{debug, error, info};
+
+// Rows read from the database per query, each chunk becomes one parquet row group
+const EXPORT_CHUNK_ROWS: i64 = 10_000;
+
+// Digits of the DECIMAL(38,0) amount columns
+const AMOUNT_PRECISION: u8 = 38;
+
+type Chunk = Result<Vec<u8>, std::io::Error>;
+
+// Timestamps, the pool for per pool tables, every numeric column and the JSON documents, then
+// the id, which is only read for the keyset cursor
+fn columns(dataset: Dataset) -> Vec<Column> {
+    let mut columns = vec![("start_time", Kind::Time), ("end_time", Kind::Time)];
+    if dataset.has_pool() {
+        columns.push(("pool", Kind::Text));
+    }
+    columns.extend(
+        dataset
+            .numeric_columns()
+            .iter()
+            .map(|column| (column.name, column.kind.into())),
+    );
+    columns.extend(
+        dataset
+            .json_columns()
+            .iter()
+            .map(|column| (*column, Kind::Json)),
+    );
+    columns.push(("id", Kind::Int));
+    columns
+}
+
+// Keyset pagination on (start_time, id) so every chunk is a cheap indexed range scan and
+// nothing is skipped or repeated while the export runs
+async fn fetch_chunk(
+    pool: &DbPool,
+    dataset: Dataset,
+    columns: &[Column],
+    pool_name: Option<&str>,
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+    cursor: Option<(DateTime<Utc>, i64)>,
+) -> Result<Vec<Vec<Value>>, sqlx::Error> {
+    let mut query = pool.query(format!("SELECT * FROM {} WHERE 1=1", dataset.table()));
+    if let Some(pool_name) = pool_name {
+        query.push(" AND pool = ").push_bind(pool_name);
+    }
+    if let Some(from) = from {
+        query.push(" AND start_time >= ").push_bind(from);
+    }
+    if let Some(to) = to {
+        query.push(" AND start_time < ").push_bind(to);
+    }
+    if let Some((start_time, id)) = cursor {
+        query
+            .push(" AND (start_time > ")
+            .push_bind(start_time)
+            .push(" OR (start_time = ")
+            .push_bind(start_time)
+            .push(" AND id > ")
+            .push_bind(id)
+            .push("))");
+    }
+    query
+        .push(" ORDER BY start_time ASC, id ASC LIMIT ")
+        .push_bind(EXPORT_CHUNK_ROWS);
+
+    pool.fetch_rows(&query, columns).try_collect().await
+}
+
+// Every column but the id, JSON documents are kept as their JSON text
+fn schema(dataset: Dataset) -> SchemaRef {
+    let fields: Vec<Field> = columns(dataset)
+        .into_iter()
+        .filter(|&(name, _)| name != "id")
+        .map(|(name, kind)| {
+            let data_type = match kind {
+                Kind::Time => DataType::Timestamp(TimeUnit::Second, Some("UTC".into())),
+                Kind::UInt => DataType::UInt64,
+                Kind::Amount => DataType::Decimal128(AMOUNT_PRECISION, 0),
+                Kind::Int => DataType::Int64,
+                Kind::Float => DataType::Float64,
+                Kind::Text | Kind::Json => DataType::Utf8,
+            };
+            Field::new(name, data_type, false)
+        })
+        .collect();
+    Arc::new(Schema::new(fields))
+}
+
+// `fetch_rows` decodes a column as its `Kind`, so `get` only fails on a bug
+fn column_values<T>(
+    rows: &[Vec<Value>],
+    index: usize,
+    get: impl Fn(&Value) -> Option<T>,
+) -> Result<Vec<T>, anyhow::Error> {
+    rows.iter()
+        .map(|row| {
+            get(&row[index]).ok_or_else(|| {
+                anyhow::anyhow!("unexpected value {:?} in column {}", row[index], index)
+            })
+        })
+        .collect()
+}
+
+fn record_batch(
+    dataset: Dataset,
+    schema: SchemaRef,
+    rows: &[Vec<Value>],
+) -> Result<RecordBatch, anyhow::Error> {
+    let mut arrays: Vec<ArrayRef> = Vec::new();
+    for (index, (_, kind)) in columns(dataset)
+        .into_iter()
+        .enumerate()
+        .take(schema.fields().len())
+    {
+        let array: ArrayRef = match kind {
+            Kind::Time => Arc::new(
+                TimestampSecondArray::from(column_values(rows, index, |value| match value {
+                    Value::Time(time) => Some(time.timestamp()),
+                    _ => None,
+                })?)
+                .with_timezone("UTC"),
+            ),
+            Kind::UInt => Arc::new(UInt64Array::from(column_values(
+                rows,
+                index,
+                |value| match value {
+                    Value::UInt(number) => Some(*number),
+                    _ => None,
+                },
+            )?)),
+            Kind::Amount => Arc::new(
+                Decimal128Array::from(column_values(rows, index, |value| match value {
+                    Value::Amount(number) => i128::try_from(*number).ok(),
+                    _ => None,
+                })?)
+                .with_precision_and_scale(AMOUNT_PRECISION, 0)?,
+            ),
+            Kind::Int => Arc::new(Int64Array::from(column_values(
+                rows,
+                index,
+                |value| match value {
+                    Value::Int(number) => Some(*number),
+                    _ => None,
+                },
+            )?)),
+            Kind::Float => {
+                Arc::new(Float64Array::from(column_values(
+                    rows,
+                    index,
+                    |value| match value {
+                        Value::Float(number) => Some(*number),
+                        _ => None,
+                    },
+                )?))
+            }
+            Kind::Text | Kind::Json => Arc::new(StringArray::from(column_values(
+                rows,
+                index,
+                |value| match value {
+                    Value::Text(text) => Some(text.clone()),
+                    Value::Json(document) => Some(document.to_string()),
+                    _ => None,
+                },
+            )?)),
+        };
+        arrays.push(array);
+    }
+
+    Ok(RecordBatch::try_new(schema, arrays)?)
+}
+
+// One JSON object per line, numbers as native JSON numbers and timestamps as RFC 3339
+fn ndjson_lines(dataset: Dataset, rows: &[Vec<Value>]) -> Result<Vec<u8>, anyhow::Error> {
+    let columns = columns(dataset);
+    let mut lines = Vec::new();
+    for row in rows {
+        let mut object = Map::new();
+        for ((name, _), value) in columns.iter().zip(row) {
+            if *name == "id" {
+                continue;
+            }
+            let value: JsonValue = match value {
+                Value::Time(time) => time.to_rfc3339_opts(SecondsFormat::Secs, true).into(),
+                Value::Text(text) => text.clone().into(),
+                Value::Int(number) => (*number).into(),
+                Value::UInt(number) => (*number).into(),
+                // JSON numbers past 64 bits are not portable, those amounts are written as strings
+                Value::Amount(number) => match u64::try_from(*number) {
+                    Ok(number) => number.into(),
+                    Err(_) => number.to_string().into(),
+                },
+                // NaN/inf are not valid JSON and become null
+                Value::Float(number) => (*number).into(),
+                Value::Bool(flag) => (*flag).into(),
+                Value::Json(document) => document.clone(),
+                Value::Null => JsonValue::Null,
+            };
+            object.insert(name.to_string(), value);
+        }
+
+        serde_json::to_writer(&mut lines, &object)?;
+        lines.push(b'\n');
+    }
+    Ok(lines)
+}
+
+// Start time (the first column) and id (the last one) of the last row
+fn cursor(rows: &[Vec<Value>]) -> Result<Option<(DateTime<Utc>, i64)>, anyhow::Error> {
+    let Some(row) = rows.last() else {
+        return Ok(None);
+    };
+    match row.as_slice() {
+        [Value::Time(start_time), .., Value::Int(id)] => Ok(Some((*start_time, *id))),
+        _ => Err(anyhow::anyhow!("export row without a start time and id")),
+    }
+}
+
+async fn write_export(
+    pool: DbPool,
+    dataset: Dataset,
+    format: ExportFormat,
+    pool_name: Option<String>,
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+    tx: mpsc::Sender<Chunk>,
+) -> Result<(), anyhow::Error> {
+    let schema = schema(dataset);
+    let columns = columns(dataset);
+    let mut parquet_writer = match format {
+        ExportFormat::Parquet => {
+            let properties = WriterProperties::builder()
+                .set_compression(Compression::SNAPPY)
+                .build();
+            Some(ArrowWriter::try_new(
+                Vec::new(),
+                schema.clone(),
+                Some(properties),
+            )?)
+        }
+        ExportFormat::Ndjson => None,
+    };
+
+    let mut cursor_position = None;
+    let mut total_rows = 0;
+    loop {
+        let rows = fetch_chunk(
+            &pool,
+            dataset,
+            &columns,
+            pool_name.as_deref(),
+            from,
+            to,
+            cursor_position,
+        )
+        .await?;
+        if rows.is_empty() {
+            break;
+        }
+        total_rows += rows.len();
+        debug!(
+            "Exporting {} {} rows ({} so far)",
+            rows.len(),
+            dataset,
+            total_rows
+        );
+
+        let bytes = match parquet_writer.as_mut() {
+            Some(writer) => {
+                writer.write(&record_batch(dataset, schema.clone(), &rows)?)?;
+                writer.flush()?;
+                // The writer keeps track of its own offsets, so the buffer can be drained
+                std::mem::take(writer.inner_mut())
+            }
+            None => ndjson_lines(dataset, &rows)?,
+        };
+        if tx.send(Ok(bytes)).await.is_err() {
+            info!("Client went away during the {} export", dataset);
+            return Ok(());
+        }
+
+        if (rows.len() as i64) < EXPORT_CHUNK_ROWS {
+            break;
+        }
+        cursor_position = cursor(&rows)?;
+    }
+
+    // Footer, also written for an empty range so the file is still valid parquet
+    if let Some(writer) = parquet_writer {
+        let _ = tx.send(Ok(writer.into_inner()?)).await;
+    }
+    info!(
+        "Exported {} {} rows as {}",
+        total_rows,
+        dataset,
+        format.extension()
+    );
+    Ok(())
+}
+
+// Streams `dataset` rows with `from <= start_time < to`, of `pool_name` for per pool tables, in
+// the requested format
+pub fn stream_export(
+    pool: DbPool,
+    dataset: Dataset,
+    format: ExportFormat,
+    pool_name: Option<String>,
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+) -> Response {
+    let (tx, rx) = mpsc::channel::<Chunk>(2);
+
+    tokio::spawn(async move {
+        if let Err(e) = write_export(pool, dataset, format, pool_name, from, to, tx.clone()).await {
+            error!("Failed to export {}: {}", dataset, e);
+            let _ = tx.send(Err(std::io::Error::other(e.to_string()))).await;
+        }
+    });
+
+    (
+        StatusCode::OK,
+        [
+            (header::CONTENT_TYPE, format.content_type().to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!(
+                    "attachment; filename=\"{}_history.{}\"",
+                    dataset,
+                    format.extension()
+                ),
+            ),
+        ],
+        Body::from_stream(ReceiverStream::new(rx)),
+    )
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::models::runepool_units_history::RunepoolUnitsInterval;
+    use crate::services::repository::{runepool, test_pool};
+    use arrow_array::Array;
+    use parquet::arrow::arrow_reader::{ParquetRecordBatchReader, ParquetRecordBatchReaderBuilder};
+
+    #[test]
+    fn schema_types_follow_the_dataset() {
+        let schema = schema(Dataset::Depth);
+
+        assert_eq!(
+            schema.field_with_name("start_time").unwrap().data_type(),
+            &DataType::Timestamp(TimeUnit::Second, Some("UTC".into()))
+        );
+        assert_eq!(
+            schema.field_with_name("pool").unwrap().data_type(),
+            &DataType::Utf8
+        );
+        assert_eq!(
+            schema.field_with_name("rune_depth").unwrap().data_type(),
+            &DataType::Decimal128(AMOUNT_PRECISION, 0)
+        );
+        assert_eq!(
+            schema.field_with_name("members_count").unwrap().data_type(),
+            &DataType::UInt64
+        );
+        assert_eq!(
+            schema
+                .field_with_name("asset_price_usd")
+                .unwrap()
+                .data_type(),
+            &DataType::Float64
+        );
+    }
+
+    #[test]
+    fn amounts_past_64_bits_are_exported_exactly() {
+        let units = u128::from(u64::MAX) * 1_000;
+        let row: Vec<Value> = columns(Dataset::Runepool)
+            .into_iter()
+            .map(|(name, kind)| match kind {
+                Kind::Time => Value::Time(DateTime::UNIX_EPOCH),
+                Kind::Amount => Value::Amount(units),
+                Kind::UInt => Value::UInt(7),
+                _ if name == "id" => Value::Int(1),
+                _ => Value::Null,
+            })
+            .collect();
+        let rows = vec![row];
+
+        let schema = schema(Dataset::Runepool);
+        let index = schema.index_of("units").unwrap();
+        let batch = record_batch(Dataset::Runepool, schema, &rows).unwrap();
+        let column = batch
+            .column(index)
+            .as_any()
+            .downcast_ref::<Decimal128Array>()
+            .unwrap();
+        assert_eq!(column.value(0), i128::try_from(units).unwrap());
+
+        let line: JsonValue =
+            serde_json::from_slice(&ndjson_lines(Dataset::Runepool, &rows).unwrap()).unwrap();
+        assert_eq!(line["units"], JsonValue::from(units.to_string()));
+        assert_eq!(line["count"], JsonValue::from(7));
+    }
+
+    #[test]
+    fn only_earnings_carry_json_columns() {
+        assert!(schema(Dataset::Earnings).field_with_name("pools").is_ok());
+        assert!(schema(Dataset::Swap).field_with_name("pools").is_err());
+        assert!(schema(Dataset::Swap).field_with_name("pool").is_err());
+    }
+
+    #[tokio::test]
+    async fn parquet_exports_span_several_chunks() {
+        let pool = test_pool().await;
+        let stored = EXPORT_CHUNK_ROWS as u64 + 50;
+        let intervals: Vec<RunepoolUnitsInterval> = (0..stored)
+            .map(|hour| RunepoolUnitsInterval {
+                count: hour,
+                start_time: DateTime::from_timestamp(hour as i64 * 3600, 0).unwrap(),
+                end_time: DateTime::from_timestamp((hour as i64 + 1) * 3600, 0).unwrap(),
+                units: u128::from(hour) * 1_000,
+            })
+            .collect();
+        runepool::store_intervals(&pool, &intervals).await.unwrap();
+
+        let response = stream_export(
+            pool,
+            Dataset::Runepool,
+            ExportFormat::Parquet,
+            None,
+            None,
+            None,
+        );
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let builder = ParquetRecordBatchReaderBuilder::try_new(body).unwrap();
+        // One row group per chunk read from the database
+        assert_eq!(builder.metadata().num_row_groups(), 2);
+        let reader: ParquetRecordBatchReader = builder.build().unwrap();
+
+        let mut read = Vec::new();
+        for batch in reader {
+            let batch = batch.unwrap();
+            let start_times = batch
+                .column_by_name("start_time")
+                .unwrap()
+                .as_any()
+                .downcast_ref::<TimestampSecondArray>()
+                .unwrap()
+                .clone();
+            let units = batch
+                .column_by_name("units")
+                .unwrap()
+                .as_any()
+                .downcast_ref::<Decimal128Array>()
+                .unwrap()
+                .clone();
+            read.extend((0..batch.num_rows()).map(|i| (start_times.value(i), units.value(i))));
+        }
+
+        assert_eq!(read.len() as u64, stored);
+        assert_eq!(read[0], (0, 0));
+        let chunk = EXPORT_CHUNK_ROWS as usize;
+        assert_eq!(read[chunk], (chunk as i64 * 3600, chunk as i128 * 1_000));
+        assert_eq!(
+            read.last(),
+            Some(&((stored as i64 - 1) * 3600, (stored as i128 - 1) * 1_000))
+        );
+    }
+}
@@ -0,0 +1,193 @@
+use crate::api::conditional::check_response;
+use crate::api::csv::wants_csv;
+use crate::api::error::bad_request;
+use crate::core::models::dataset::Dataset;
+use crate::core::models::serialization::{self, with_number_mode, NumberMode};
+use crate::services::cache::{query_cache, CachedResponse};
+use axum::{
+    body::{self, Body},
+    extract::{MatchedPath, Request, State},
+    http::{header, HeaderValue, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use std::time::Instant;
+
+pub const NUMBERS_HEADER: &str = "x-numbers";
+
+// Picks the number encoding for the whole request from `?numbers=` (or the `x-numbers` header),
+// so handlers and models never have to care about it
+pub async fn number_mode(request: Request, next: Next) -> Response {
+    let requested = request
+        .uri()
+        .query()
+        .and_then(|query| {
+            url::form_urlencoded::parse(query.as_bytes())
+                .find(|(key, _)| key == "numbers")
+                .map(|(_, value)| value.into_owned())
+        })
+        .or_else(|| {
+            request
+                .headers()
+                .get(NUMBERS_HEADER)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string)
+        });
+
+    let mode = match requested.map(NumberMode::try_from) {
+        None => NumberMode::default(),
+        Some(Ok(mode)) => mode,
+        Some(Err(e)) => return bad_request(&e),
+    };
+
+    with_number_mode(mode, next.run(request)).await
+}
+
+const CACHE_STATUS_HEADER: &str = "x-cache";
+
+// Same query, same response: parameters are sorted and empty ones dropped so `?a=1&b=` and
+// `?b=&a=1` share an entry
+fn cache_key(path: &str, query: Option<&str>, mode: NumberMode) -> String {
+    let mut params: Vec<(String, String)> =
+        url::form_urlencoded::parse(query.unwrap_or_default().as_bytes())
+            .filter(|(key, value)| !value.is_empty() && key != "numbers")
+            .map(|(key, value)| (key.into_owned(), value.into_owned()))
+            .collect();
+    params.sort();
+
+    let query = url::form_urlencoded::Serializer::new(String::new())
+        .extend_pairs(params)
+        .finish();
+    format!("{:?}:{}?{}", mode, path, query)
+}
+
+// Serves JSON responses of a history route out of the query cache, `dataset` is the table the
+// route reads so ingestion can invalidate its entries
+pub async fn cache_queries(
+    State(dataset): State<Dataset>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some(cache) = query_cache() else {
+        return next.run(request).await;
+    };
+
+    let key = cache_key(
+        request.uri().path(),
+        request.uri().query(),
+        serialization::number_mode(),
+    );
+    let format = url::form_urlencoded::parse(request.uri().query().unwrap_or_default().as_bytes())
+        .find(|(key, _)| key == "format")
+        .map(|(_, value)| value.into_owned());
+    // CSV exports are streamed and never cached
+    if wants_csv(format.as_deref(), request.headers()) {
+        return next.run(request).await;
+    }
+
+    if let Some(cached) = cache.get(dataset, &key).await {
+        if let Some(not_modified) = check_response(request.headers(), &cached.headers) {
+            return not_modified;
+        }
+
+        let mut response = Response::new(Body::from(cached.body.clone()));
+        *response.status_mut() = cached.status;
+        *response.headers_mut() = cached.headers.clone();
+        response
+            .headers_mut()
+            .insert(CACHE_STATUS_HEADER, HeaderValue::from_static("HIT"));
+        return response;
+    }
+
+    let generation = cache.generation(dataset);
+    let response = next.run(request).await;
+
+    let is_json = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .is_some_and(|content_type| content_type.as_bytes().starts_with(b"application/json"));
+    if response.status() != StatusCode::OK || !is_json {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let body = match body::to_bytes(body, usize::MAX).await {
+        Ok(body) => body,
+        Err(e) => {
+            tracing::error!("Failed to buffer response for the query cache: {}", e);
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+
+    cache
+        .insert(
+            dataset,
+            generation,
+            key,
+            CachedResponse {
+                status: parts.status,
+                headers: parts.headers.clone(),
+                body: body.clone(),
+            },
+        )
+        .await;
+
+    parts
+        .headers
+        .insert(CACHE_STATUS_HEADER, HeaderValue::from_static("MISS"));
+    Response::from_parts(parts, Body::from(body))
+}
+
+// Request count and latency by route template (`/export/:dataset`, not the actual path, to keep
+// the number of series bounded) and status
+pub async fn track_http(request: Request, next: Next) -> Response {
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+        .unwrap_or_else(|| "unmatched".to_string());
+    let method = request.method().to_string();
+    let started = Instant::now();
+
+    let response = next.run(request).await;
+
+    let labels = [
+        ("method", method),
+        ("route", route),
+        ("status", response.status().as_u16().to_string()),
+    ];
+    metrics::counter!("http_requests_total", &labels).increment(1);
+    metrics::histogram!("http_request_duration_seconds", &labels)
+        .record(started.elapsed().as_secs_f64());
+
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cache_key_ignores_parameter_order_and_empty_values() {
+        assert_eq!(
+            cache_key(
+                "/swap_history",
+                Some("limit=10&date_range=&order=desc"),
+                NumberMode::String
+            ),
+            cache_key(
+                "/swap_history",
+                Some("order=desc&limit=10"),
+                NumberMode::String
+            ),
+        );
+        assert_ne!(
+            cache_key("/swap_history", Some("limit=10"), NumberMode::String),
+            cache_key("/swap_history", Some("limit=10"), NumberMode::Native),
+        );
+        assert_ne!(
+            cache_key("/swap_history", Some("limit=10"), NumberMode::String),
+            cache_key("/depth_history", Some("limit=10"), NumberMode::String),
+        );
+    }
+}
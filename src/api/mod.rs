@@ -1,2 +1,12 @@
+pub mod auth;
+pub mod conditional;
+pub mod csv;
+pub mod error;
+pub mod export;
+pub mod middleware;
+pub mod rate_limit;
+pub mod router;
 pub mod routes;
 pub mod server;
+pub mod state;
+pub mod units;
@@ -0,0 +1,109 @@
+use crate::api::error::error_response;
+use axum::{
+    http::{header, HeaderValue, StatusCode},
+    response::Response,
+};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+const WINDOW_SECS: i64 = 60;
+
+// Outcome of counting one request against a key's quota
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimit {
+    pub limit: u32,
+    pub remaining: u32,
+    // Unix timestamp at which the current window ends
+    pub reset: i64,
+    pub allowed: bool,
+}
+
+impl RateLimit {
+    pub fn apply(&self, mut response: Response) -> Response {
+        let headers = response.headers_mut();
+        headers.insert("x-ratelimit-limit", HeaderValue::from(self.limit));
+        headers.insert("x-ratelimit-remaining", HeaderValue::from(self.remaining));
+        headers.insert("x-ratelimit-reset", HeaderValue::from(self.reset));
+        response
+    }
+
+    pub fn too_many_requests(&self, now: i64) -> Response {
+        let mut response = self.apply(error_response(
+            StatusCode::TOO_MANY_REQUESTS,
+            &format!("Rate limit of {} requests per minute exceeded", self.limit),
+        ));
+        response.headers_mut().insert(
+            header::RETRY_AFTER,
+            HeaderValue::from((self.reset - now).max(1)),
+        );
+        response
+    }
+}
+
+struct Window {
+    start: i64,
+    count: u32,
+}
+
+// Fixed one minute windows per api key. Counts live in memory, so with several instances every
+// instance enforces the quota on its own.
+#[derive(Default)]
+pub struct RateLimiter {
+    windows: Mutex<HashMap<i64, Window>>,
+}
+
+impl RateLimiter {
+    pub fn check(&self, key_id: i64, limit: u32, now: i64) -> RateLimit {
+        let start = now - now.rem_euclid(WINDOW_SECS);
+        let mut windows = self.windows.lock().expect("rate limiter lock poisoned");
+        let window = windows.entry(key_id).or_insert(Window { start, count: 0 });
+        if window.start != start {
+            *window = Window { start, count: 0 };
+        }
+
+        let allowed = window.count < limit;
+        if allowed {
+            window.count += 1;
+        }
+
+        RateLimit {
+            limit,
+            remaining: limit - window.count,
+            reset: start + WINDOW_SECS,
+            allowed,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn requests_over_the_limit_are_rejected_until_the_window_resets() {
+        let limiter = RateLimiter::default();
+        let now = 1_700_000_010;
+
+        assert_eq!(
+            limiter.check(1, 2, now),
+            RateLimit {
+                limit: 2,
+                remaining: 1,
+                reset: 1_700_000_040,
+                allowed: true
+            }
+        );
+        assert!(limiter.check(1, 2, now + 1).allowed);
+
+        let rejected = limiter.check(1, 2, now + 2);
+        assert!(!rejected.allowed);
+        assert_eq!(rejected.remaining, 0);
+
+        // Other keys have their own quota
+        assert!(limiter.check(2, 2, now + 2).allowed);
+
+        let next_window = limiter.check(1, 2, rejected.reset);
+        assert!(next_window.allowed);
+        assert_eq!(next_window.remaining, 1);
+    }
+}
@@ -0,0 +1,121 @@
+use crate::api::auth::{require_admin, require_api_key, ApiKeyAuth};
+use crate::api::middleware::{cache_queries, number_mode, track_http};
+use crate::api::routes::actions::get_actions;
+use crate::api::routes::admin::{cancel_job, get_jobs, post_backfill};
+use crate::api::routes::analytics::{
+    get_lp_performance, get_pool_yield, get_rolling_stats, get_timeseries,
+};
+use crate::api::routes::depth::get_depth_history;
+use crate::api::routes::earnings::get_earnings_history;
+use crate::api::routes::export::get_export;
+use crate::api::routes::health::{get_freshness, get_healthz, get_readyz, get_tasks};
+use crate::api::routes::metrics::get_metrics;
+use crate::api::routes::runepool::get_runepool_units_history;
+use crate::api::routes::swap::get_swap_history;
+use crate::api::state::AppState;
+use crate::core::models::dataset::Dataset;
+use crate::swagger::SwaggerApiDoc;
+use axum::{
+    middleware,
+    routing::{get, post},
+    Router,
+};
+use http::Method;
+use tower_http::{
+    compression::{
+        predicate::{DefaultPredicate, NotForContentType, Predicate},
+        CompressionLayer,
+    },
+    cors::{Any, CorsLayer},
+};
+use utoipa::OpenApi;
+use utoipa_swagger_ui::SwaggerUi;
+
+// Every route of the api with its middleware, tests serve it on a random port
+pub fn app(state: AppState) -> Router {
+    let api = Router::new()
+        .layer(CorsLayer::new().allow_origin(Any).allow_methods([
+            Method::GET,
+            Method::PUT,
+            Method::POST,
+            Method::DELETE,
+        ]))
+        .route(
+            "/depth_history",
+            get(get_depth_history).layer(middleware::from_fn_with_state(
+                Dataset::Depth,
+                cache_queries,
+            )),
+        )
+        .route(
+            "/earning_history",
+            get(get_earnings_history).layer(middleware::from_fn_with_state(
+                Dataset::Earnings,
+                cache_queries,
+            )),
+        )
+        .route(
+            "/swap_history",
+            get(get_swap_history)
+                .layer(middleware::from_fn_with_state(Dataset::Swap, cache_queries)),
+        )
+        .route(
+            "/runepool_units_history",
+            get(get_runepool_units_history).layer(middleware::from_fn_with_state(
+                Dataset::Runepool,
+                cache_queries,
+            )),
+        )
+        .route("/actions", get(get_actions))
+        .route("/analytics/pools/:pool/yield", get(get_pool_yield))
+        .route(
+            "/analytics/pools/:pool/lp_performance",
+            get(get_lp_performance),
+        )
+        .route("/analytics/:dataset/rolling", get(get_rolling_stats))
+        .route("/timeseries", get(get_timeseries))
+        .route("/export/:dataset", get(get_export))
+        .layer(middleware::from_fn(number_mode));
+
+    let auth = ApiKeyAuth::new(state.pool.clone());
+
+    // Every route but the docs needs a key, `server.require_api_key = false` turns that off for
+    // local development
+    let api = if !state.config.server.require_api_key {
+        tracing::warn!("Api key authentication is disabled");
+        api
+    } else {
+        api.layer(middleware::from_fn_with_state(
+            auth.clone(),
+            require_api_key,
+        ))
+    };
+
+    // Admin routes need an admin key whatever `server.require_api_key` says
+    let admin = Router::new()
+        .route("/admin/backfill/:dataset", post(post_backfill))
+        .route("/admin/jobs", get(get_jobs))
+        .route("/admin/jobs/:id/cancel", post(cancel_job))
+        .layer(middleware::from_fn(require_admin))
+        .layer(middleware::from_fn_with_state(auth, require_api_key));
+
+    // Probes for the load balancer and alerting, no key needed
+    let health = Router::new()
+        .route("/healthz", get(get_healthz))
+        .route("/readyz", get(get_readyz))
+        .route("/freshness", get(get_freshness))
+        .route("/tasks", get(get_tasks))
+        .route("/metrics", get(get_metrics));
+
+    api.merge(admin)
+        .merge(health)
+        .layer(middleware::from_fn(track_http))
+        // Parquet exports are already compressed
+        .layer(
+            CompressionLayer::new().compress_when(DefaultPredicate::new().and(
+                NotForContentType::const_new("application/vnd.apache.parquet"),
+            )),
+        )
+        .with_state(state)
+        .merge(SwaggerUi::new("/").url("/api-docs/openapi.json", SwaggerApiDoc::openapi()))
+}
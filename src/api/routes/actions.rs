@@ -0,0 +1,60 @@
+use crate::api::error::{database_error, no_data};
+use crate::core::models::actions::{ActionsQueryParams, ActionsSearchResponse};
+use crate::services::repository::actions::{self, ActionFilter};
+use crate::services::repository::{Order, Page};
+use crate::services::storage::DbPool;
+use axum::Json;
+use axum::{
+    extract::{Query, State},
+    response::IntoResponse,
+};
+use tracing::info;
+
+#[utoipa::path(
+    get,
+    path = "/actions",
+    operation_id = "get_actions",
+    tag = "actions",
+    params(
+        ("address" = Option<String>, Query, description = "Only actions where this address sent or received funds"),
+        ("txid" = Option<String>, Query, description = "Id of any inbound or outbound transaction of the action"),
+        ("pool" = Option<String>, Query, description = "Only actions touching the given pool, e.g. `BTC.BTC`"),
+        ("type" = Option<String>, Query, description = "Action type (swap/addLiquidity/withdraw/refund)"),
+        ("date_range" = Option<String>, Query, description = "Date range in format YYYY-MM-DD,YYYY-MM-DD"),
+        ("order" = Option<String>, Query, description = "Sort order by date (asc/desc). Default is `asc`"),
+        ("page" = Option<u32>, Query, description = "Page number. Default is `0`"),
+        ("limit" = Option<u32>, Query, description = "Items per page. Default is `30`")
+    ),
+    responses(
+        (status = 200, description = "List of stored actions matching the search", body = ActionsSearchResponse),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn get_actions(
+    State(pool): State<DbPool>,
+    Query(params): Query<ActionsQueryParams>,
+) -> impl IntoResponse {
+    info!("Received actions search request with params: {:#?}", params);
+
+    let filter = ActionFilter::from(&params);
+    let order = Order::from_param(params.order.as_deref());
+    let page = Page::new(params.page, params.limit);
+
+    match actions::query_actions(&pool, &filter, order, page).await {
+        Ok(actions) => {
+            info!("Successfully retrieved {} actions", actions.len());
+
+            if actions.is_empty() {
+                return no_data();
+            }
+
+            let response = ActionsSearchResponse {
+                count: actions.len(),
+                actions,
+            };
+
+            Json(response).into_response()
+        }
+        Err(e) => database_error("fetching actions", e),
+    }
+}
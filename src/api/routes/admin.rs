@@ -0,0 +1,163 @@
+use crate::api::error::{bad_request, database_error, error_response};
+use crate::core::models::api_key::ApiKey;
+use crate::core::models::dataset::Dataset;
+use crate::core::models::depth_history::DEPTH_POOL;
+use crate::core::models::job::{
+    BackfillQueryParams, Job, JobStatus, JobsQueryParams, JobsResponse,
+};
+use crate::services::repository::jobs;
+use crate::services::storage::DbPool;
+use axum::http::StatusCode;
+use axum::Json;
+use axum::{
+    extract::{Path, Query, State},
+    response::IntoResponse,
+    Extension,
+};
+use chrono::{DateTime, Utc};
+use tracing::info;
+
+const DEFAULT_JOBS_LIMIT: u32 = 50;
+const MAX_JOBS_LIMIT: u32 = 500;
+
+#[utoipa::path(
+    post,
+    path = "/admin/backfill/{dataset}",
+    operation_id = "post_backfill",
+    tag = "admin",
+    params(
+        ("dataset" = String, Path, description = "One of `depth`, `earnings`, `swap`, `runepool`"),
+        ("from" = i64, Query, description = "Unix timestamp to backfill from"),
+        ("to" = Option<i64>, Query, description = "Unix timestamp to backfill up to. Default is now"),
+        ("pool" = Option<String>, Query, description = "Pool to backfill, depth only. Default is `ETH.ETH`")
+    ),
+    responses(
+        (status = 202, description = "The queued backfill job", body = Job),
+        (status = 400, description = "Invalid dataset, pool or range"),
+        (status = 403, description = "The api key is not an admin key"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn post_backfill(
+    State(pool): State<DbPool>,
+    api_key: Option<Extension<ApiKey>>,
+    Path(dataset): Path<String>,
+    Query(params): Query<BackfillQueryParams>,
+) -> impl IntoResponse {
+    info!(
+        "Received backfill request for {} with params: {:#?}",
+        dataset, params
+    );
+
+    let dataset = match Dataset::try_from(dataset) {
+        Ok(dataset) => dataset,
+        Err(e) => return bad_request(&e),
+    };
+    if params.pool.is_some() && !dataset.has_pool() {
+        return bad_request(&format!("`pool` is not supported for {}", dataset));
+    }
+
+    let Some(from) = params
+        .from
+        .and_then(|from| DateTime::from_timestamp(from, 0))
+    else {
+        return bad_request("Missing or invalid `from` timestamp");
+    };
+    let to = match params.to.map(|to| DateTime::from_timestamp(to, 0)) {
+        None => Utc::now(),
+        Some(Some(to)) => to,
+        Some(None) => return bad_request("Invalid `to` timestamp"),
+    };
+    if from >= to {
+        return bad_request("`from` must be before `to`");
+    }
+
+    let pool_name = dataset
+        .has_pool()
+        .then(|| params.pool.as_deref().unwrap_or(DEPTH_POOL));
+    let requested_by = api_key
+        .as_ref()
+        .map(|Extension(api_key)| api_key.name.as_str());
+
+    let job = match jobs::create_backfill(&pool, dataset, pool_name, from, to, requested_by).await {
+        Ok(id) => jobs::find(&pool, id).await,
+        Err(e) => Err(e),
+    };
+
+    match job {
+        Ok(Some(job)) => (StatusCode::ACCEPTED, Json(job)).into_response(),
+        Ok(None) => error_response(StatusCode::INTERNAL_SERVER_ERROR, "Job vanished"),
+        Err(e) => database_error("queuing a backfill", e),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/admin/jobs",
+    operation_id = "get_jobs",
+    tag = "admin",
+    params(
+        ("status" = Option<String>, Query, description = "One of `queued`, `running`, `succeeded`, `failed`, `cancelled`"),
+        ("limit" = Option<u32>, Query, description = "Number of jobs, newest first. Default is `50`, max `500`")
+    ),
+    responses(
+        (status = 200, description = "Backfill jobs", body = JobsResponse),
+        (status = 400, description = "Invalid status"),
+        (status = 403, description = "The api key is not an admin key"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn get_jobs(
+    State(pool): State<DbPool>,
+    Query(params): Query<JobsQueryParams>,
+) -> impl IntoResponse {
+    let status = match params.status.map(JobStatus::try_from) {
+        None => None,
+        Some(Ok(status)) => Some(status),
+        Some(Err(e)) => return bad_request(&e),
+    };
+    let limit = params
+        .limit
+        .unwrap_or(DEFAULT_JOBS_LIMIT)
+        .min(MAX_JOBS_LIMIT);
+
+    match jobs::list(&pool, status, limit).await {
+        Ok(jobs) => Json(JobsResponse { jobs }).into_response(),
+        Err(e) => database_error("listing jobs", e),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/admin/jobs/{id}/cancel",
+    operation_id = "cancel_job",
+    tag = "admin",
+    params(
+        ("id" = i64, Path, description = "Job id")
+    ),
+    responses(
+        (status = 200, description = "The cancelled job", body = Job),
+        (status = 403, description = "The api key is not an admin key"),
+        (status = 404, description = "No job with this id"),
+        (status = 409, description = "The job already finished"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn cancel_job(State(pool): State<DbPool>, Path(id): Path<i64>) -> impl IntoResponse {
+    info!("Received cancel request for job {}", id);
+
+    let cancelled = match jobs::cancel(&pool, id).await {
+        Ok(cancelled) => cancelled,
+        Err(e) => return database_error("cancelling a job", e),
+    };
+
+    match jobs::find(&pool, id).await {
+        Ok(Some(job)) if cancelled => Json(job).into_response(),
+        Ok(Some(job)) => error_response(
+            StatusCode::CONFLICT,
+            &format!("Job {} already {}", id, job.status),
+        ),
+        Ok(None) => error_response(StatusCode::NOT_FOUND, &format!("No job with id {}", id)),
+        Err(e) => database_error("cancelling a job", e),
+    }
+}
@@ -0,0 +1,687 @@
+use crate::api::error::{bad_request, database_error, error_response, no_data};
+use crate::api::units::{convert_fields, encode, requested_units, to_json};
+use crate::core::analytics::lp_performance::{self, PoolState};
+use crate::core::analytics::pool_yield::{self, YieldSample};
+use crate::core::analytics::{rolling, timeseries};
+use crate::core::models::analytics::{
+    LpPerformanceQueryParams, LpPerformanceResponse, PoolYieldInterval, PoolYieldQueryParams,
+    PoolYieldResponse, PoolYieldSummary, RollingInterval, RollingQueryParams, RollingResponse,
+    TimeseriesBucket, TimeseriesQueryParams, TimeseriesResponse, TimeseriesSeries, YieldWindow,
+    DEFAULT_ROLLING_WINDOW, MAX_ROLLING_WINDOW, MAX_SERIES_ROWS, MAX_TIMESERIES_BUCKETS,
+    MAX_TIMESERIES_SERIES,
+};
+use crate::core::models::common::Interval;
+use crate::core::models::dataset::Dataset;
+use crate::core::models::depth_history::{DepthInterval, DEPTH_POOL};
+use crate::core::models::units::{Denomination, Prices, Units};
+use crate::services::repository::dataset::{fetch_column_values, start_time_bounds, ColumnValue};
+use crate::services::repository::depth::{self, DepthFilter};
+use crate::services::repository::earnings::{self, EarningsFilter};
+use crate::services::repository::{Order, Page, Sort};
+use crate::services::storage::DbPool;
+use axum::http::StatusCode;
+use axum::Json;
+use axum::{
+    extract::{Path, Query, State},
+    response::IntoResponse,
+};
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+use tracing::{debug, error, info};
+
+impl From<&DepthInterval> for PoolState {
+    fn from(interval: &DepthInterval) -> Self {
+        PoolState {
+            rune_depth: interval.rune_depth as f64,
+            asset_depth: interval.asset_depth as f64,
+            units: interval.units as f64,
+            luvi: interval.luvi,
+            asset_price: interval.asset_price,
+            asset_price_usd: interval.asset_price_usd,
+        }
+    }
+}
+
+// `response` as JSON with `convert` applied to it, unless the amounts stay in base units
+fn in_units(
+    response: &impl Serialize,
+    units: Units,
+    convert: impl FnOnce(&mut serde_json::Value),
+) -> axum::response::Response {
+    if units == Units::Base {
+        return Json(response).into_response();
+    }
+    match to_json(response) {
+        Ok(mut body) => {
+            convert(&mut body);
+            Json(body).into_response()
+        }
+        Err(e) => {
+            error!("Failed to serialize analytics in {:?}: {}", units, e);
+            error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                &format!("Serialization error: {}", e),
+            )
+        }
+    }
+}
+
+// Values of a numeric column in `units`, before any statistic is derived from them. Intervals
+// without a USD price are left out of USD series. A range of more than `MAX_SERIES_ROWS`
+// intervals is refused rather than loaded.
+async fn column_values(
+    pool: &DbPool,
+    dataset: Dataset,
+    column: &'static str,
+    pool_name: Option<&str>,
+    date_range: Option<(DateTime<Utc>, DateTime<Utc>)>,
+    units: Units,
+) -> Result<Vec<ColumnValue>, axum::response::Response> {
+    // One more than allowed tells a range that is too long from one that just fits
+    let limit = MAX_SERIES_ROWS + 1;
+    let rows = fetch_column_values(pool, dataset, column, pool_name, date_range, limit)
+        .await
+        .map_err(|e| database_error("computing analytics", e))?;
+    if rows.len() > MAX_SERIES_ROWS {
+        return Err(bad_request(&format!(
+            "The range covers more than {} {} intervals, use a shorter date_range",
+            MAX_SERIES_ROWS, dataset
+        )));
+    }
+
+    let Some(denomination) = dataset.denomination(column) else {
+        return Ok(rows);
+    };
+    if units == Units::Base {
+        return Ok(rows);
+    }
+
+    let mut price_columns = HashMap::new();
+    if units == Units::Usd {
+        for &price_column in Prices::columns(dataset) {
+            let prices =
+                fetch_column_values(pool, dataset, price_column, pool_name, date_range, limit)
+                    .await
+                    .map_err(|e| database_error("computing analytics", e))?
+                    .into_iter()
+                    .map(|row| (row.start_time, row.value))
+                    .collect::<HashMap<_, _>>();
+            price_columns.insert(price_column, prices);
+        }
+    }
+
+    Ok(rows
+        .into_iter()
+        .filter_map(|mut row| {
+            let prices = Prices::of_row(dataset, |name| {
+                price_columns.get(name)?.get(&row.start_time).copied()
+            });
+            row.value = units.convert(row.value, denomination, &prices)?;
+            Some(row)
+        })
+        .collect())
+}
+
+#[utoipa::path(
+    get,
+    path = "/analytics/pools/{pool}/yield",
+    operation_id = "get_pool_yield",
+    tag = "analytics",
+    params(
+        ("pool" = String, Path, description = "Pool name, e.g. `ETH.ETH`"),
+        ("window" = Option<String>, Query, description = "Window ending at the latest stored interval (7d/30d/90d). Default is `30d`"),
+        ("units" = Option<String>, Query, description = "Amounts in 1e8 base units (base), whole RUNE/asset (decimal) or USD at each interval's price (usd). Default is `base`")
+    ),
+    responses(
+        (status = 200, description = "Annualized APR/APY series and summary for the pool", body = PoolYieldResponse),
+        (status = 400, description = "Invalid window or units"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn get_pool_yield(
+    State(pool): State<DbPool>,
+    Path(pool_name): Path<String>,
+    Query(params): Query<PoolYieldQueryParams>,
+) -> impl IntoResponse {
+    info!(
+        "Received pool yield request for {} with params: {:#?}",
+        pool_name, params
+    );
+
+    let window = match params.window.map(YieldWindow::try_from) {
+        None => YieldWindow::Month,
+        Some(Ok(window)) => window,
+        Some(Err(e)) => return bad_request(&e),
+    };
+    let units = match requested_units(params.units) {
+        Ok(units) => units,
+        Err(e) => return bad_request(&e),
+    };
+
+    // The window ends at the latest stored depth for the pool so backfilled data works too
+    let latest_end_time = match depth::latest_end_time(&pool, &pool_name).await {
+        Ok(Some(latest_end_time)) => latest_end_time,
+        Ok(None) => return no_data(),
+        Err(e) => return database_error("computing analytics", e),
+    };
+    let window_start = latest_end_time - window.duration();
+    debug!(
+        "Yield window: start={}, end={}",
+        window_start, latest_end_time
+    );
+
+    let depth_filter = DepthFilter {
+        pool: Some(pool_name.clone()),
+        from: Some(window_start),
+        to: Some(latest_end_time),
+        ..DepthFilter::default()
+    };
+    let oldest_first = Sort::start_time(Order::Asc);
+    let depths = match depth::query_intervals(&pool, &depth_filter, oldest_first, None).await {
+        Ok(depths) => depths,
+        Err(e) => return database_error("computing analytics", e),
+    };
+
+    let earnings_filter = EarningsFilter {
+        from: Some(window_start),
+        to: Some(latest_end_time),
+        ..EarningsFilter::default()
+    };
+    let earnings =
+        match earnings::query_intervals(&pool, &earnings_filter, oldest_first, None).await {
+            Ok(earnings) => earnings,
+            Err(e) => return database_error("computing analytics", e),
+        };
+
+    // Per pool earnings keyed by interval start so they can be joined with the depths
+    let mut pool_earnings: HashMap<DateTime<Utc>, u128> = HashMap::new();
+    for interval in earnings {
+        if let Some(entry) = interval.pools.iter().find(|entry| entry.pool == pool_name) {
+            pool_earnings.insert(interval.start_time, entry.earnings);
+        }
+    }
+
+    let mut samples = Vec::new();
+    let mut intervals = Vec::new();
+    let mut prices = Vec::new();
+    for depth in &depths {
+        let Some(&earnings) = pool_earnings.get(&depth.start_time) else {
+            continue;
+        };
+
+        let period_seconds = (depth.end_time - depth.start_time).num_seconds() as f64;
+        let pool_value =
+            pool_yield::pool_value_in_rune(depth.rune_depth, depth.asset_depth, depth.asset_price);
+        let apr = pool_yield::annualize_apr(
+            pool_yield::period_return(earnings, pool_value),
+            period_seconds,
+        );
+
+        intervals.push(PoolYieldInterval {
+            start_time: depth.start_time,
+            end_time: depth.end_time,
+            earnings,
+            pool_value_rune: pool_value,
+            apr,
+            apy: pool_yield::apr_to_apy(apr, period_seconds),
+        });
+        samples.push(YieldSample {
+            period_seconds,
+            earnings,
+            pool_value,
+        });
+        prices.push(Prices::pool(depth.asset_price, depth.asset_price_usd));
+    }
+    info!(
+        "Joined {} of {} depth intervals with earnings for {}",
+        intervals.len(),
+        depths.len(),
+        pool_name
+    );
+
+    let Some(summary) = pool_yield::summarize(&samples) else {
+        return no_data();
+    };
+
+    let response = PoolYieldResponse {
+        pool: pool_name,
+        window,
+        start_time: window_start,
+        end_time: latest_end_time,
+        summary: PoolYieldSummary {
+            total_earnings: summary.total_earnings,
+            average_pool_value_rune: summary.average_pool_value,
+            period_return: summary.period_return,
+            apr: summary.apr,
+            apy: summary.apy,
+        },
+        intervals,
+    };
+
+    // Totals add up the converted intervals, so each one counts at its own price
+    in_units(&response, units, |body| {
+        let fields = [
+            ("earnings", Denomination::Rune),
+            ("poolValueRune", Denomination::Rune),
+        ];
+        let converted = |amount: fn(&YieldSample) -> f64| -> Option<f64> {
+            samples
+                .iter()
+                .zip(&prices)
+                .map(|(sample, prices)| units.convert(amount(sample), Denomination::Rune, prices))
+                .sum()
+        };
+        let total_earnings = converted(|sample| sample.earnings as f64);
+        let average_pool_value =
+            converted(|sample| sample.pool_value).map(|total| total / samples.len() as f64);
+
+        if let Some(intervals) = body["intervals"].as_array_mut() {
+            for (interval, prices) in intervals.iter_mut().zip(&prices) {
+                convert_fields(interval, &fields, units, prices);
+            }
+        }
+        body["summary"]["totalEarnings"] = encode(total_earnings);
+        body["summary"]["averagePoolValueRune"] = encode(average_pool_value);
+    })
+}
+
+#[utoipa::path(
+    get,
+    path = "/analytics/pools/{pool}/lp_performance",
+    operation_id = "get_lp_performance",
+    tag = "analytics",
+    params(
+        ("pool" = String, Path, description = "Pool name, e.g. `ETH.ETH`"),
+        ("from" = Option<i64>, Query, description = "Unix timestamp of the deposit. Default is 30 days before `to`"),
+        ("to" = Option<i64>, Query, description = "Unix timestamp to value the position at. Default is the latest stored interval"),
+        ("deposit_rune" = Option<u64>, Query, description = "RUNE deposited, in 1e8 base units. Default is `0`"),
+        ("deposit_asset" = Option<u64>, Query, description = "Asset deposited, in 1e8 base units. Default is `0`"),
+        ("units" = Option<String>, Query, description = "Amounts in 1e8 base units (base), whole RUNE/asset (decimal) or USD (usd), the deposit at the start prices and the position at the end ones. Default is `base`")
+    ),
+    responses(
+        (status = 200, description = "Position value, hold value, impermanent loss and fee gain over the window", body = LpPerformanceResponse),
+        (status = 400, description = "Invalid deposit, time range or units"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn get_lp_performance(
+    State(pool): State<DbPool>,
+    Path(pool_name): Path<String>,
+    Query(params): Query<LpPerformanceQueryParams>,
+) -> impl IntoResponse {
+    info!(
+        "Received LP performance request for {} with params: {:#?}",
+        pool_name, params
+    );
+
+    let deposit_rune = params.deposit_rune.unwrap_or(0);
+    let deposit_asset = params.deposit_asset.unwrap_or(0);
+    if deposit_rune == 0 && deposit_asset == 0 {
+        return bad_request("deposit_rune or deposit_asset must be greater than 0");
+    }
+    let units = match requested_units(params.units) {
+        Ok(units) => units,
+        Err(e) => return bad_request(&e),
+    };
+
+    let to = match params.to {
+        Some(to) => match DateTime::from_timestamp(to, 0) {
+            Some(to) => to,
+            None => return bad_request("Invalid `to` timestamp"),
+        },
+        None => match depth::latest_end_time(&pool, &pool_name).await {
+            Ok(Some(latest_end_time)) => latest_end_time,
+            Ok(None) => return no_data(),
+            Err(e) => return database_error("computing analytics", e),
+        },
+    };
+    let from = match params.from {
+        Some(from) => match DateTime::from_timestamp(from, 0) {
+            Some(from) => from,
+            None => return bad_request("Invalid `from` timestamp"),
+        },
+        None => to - Duration::days(30),
+    };
+    if from >= to {
+        return bad_request("`from` must be before `to`");
+    }
+    debug!("LP performance window: from={}, to={}", from, to);
+
+    // First interval starting in the window and last one ending in it
+    let start_filter = DepthFilter {
+        pool: Some(pool_name.clone()),
+        from: Some(from),
+        ..DepthFilter::default()
+    };
+    let end_filter = DepthFilter {
+        pool: Some(pool_name.clone()),
+        to: Some(to),
+        ..DepthFilter::default()
+    };
+    let first = Some(Page::first(1));
+    let start =
+        match depth::query_intervals(&pool, &start_filter, Sort::start_time(Order::Asc), first)
+            .await
+        {
+            Ok(start) => start.into_iter().next(),
+            Err(e) => return database_error("computing analytics", e),
+        };
+    let newest_end = Sort::by(Dataset::Depth, "end_time", Order::Desc);
+    let end = match depth::query_intervals(&pool, &end_filter, newest_end, first).await {
+        Ok(end) => end.into_iter().next(),
+        Err(e) => return database_error("computing analytics", e),
+    };
+
+    let (start, end) = match (start, end) {
+        (Some(start), Some(end)) if start.start_time < end.end_time => (start, end),
+        _ => return no_data(),
+    };
+
+    let performance = lp_performance::calculate(
+        &PoolState::from(&start),
+        &PoolState::from(&end),
+        deposit_rune as f64,
+        deposit_asset as f64,
+    );
+
+    let response = LpPerformanceResponse {
+        pool: pool_name,
+        start_time: start.start_time,
+        end_time: end.end_time,
+        deposit_rune,
+        deposit_asset,
+        pool_units: performance.pool_units,
+        rune_withdrawable: performance.rune_withdrawable,
+        asset_withdrawable: performance.asset_withdrawable,
+        deposit_value_rune: performance.deposit_value,
+        position_value_rune: performance.position_value,
+        hold_value_rune: performance.hold_value,
+        position_value_usd: performance.position_value_usd,
+        hold_value_usd: performance.hold_value_usd,
+        impermanent_loss: performance.impermanent_loss,
+        fee_gain_rune: performance.fee_gain,
+        luvi_growth: performance.luvi_growth,
+        lp_vs_hold: performance.lp_vs_hold,
+    };
+
+    in_units(&response, units, |body| {
+        let deposit = [
+            ("depositRune", Denomination::Rune),
+            ("depositAsset", Denomination::Asset),
+            ("depositValueRune", Denomination::Rune),
+        ];
+        let position = [
+            ("runeWithdrawable", Denomination::Rune),
+            ("assetWithdrawable", Denomination::Asset),
+            ("positionValueRune", Denomination::Rune),
+            ("holdValueRune", Denomination::Rune),
+            ("feeGainRune", Denomination::Rune),
+            ("positionValueUSD", Denomination::Usd),
+            ("holdValueUSD", Denomination::Usd),
+        ];
+        let start_prices = Prices::pool(start.asset_price, start.asset_price_usd);
+        let end_prices = Prices::pool(end.asset_price, end.asset_price_usd);
+        convert_fields(body, &deposit, units, &start_prices);
+        convert_fields(body, &position, units, &end_prices);
+    })
+}
+
+#[utoipa::path(
+    get,
+    path = "/analytics/{dataset}/rolling",
+    operation_id = "get_rolling_stats",
+    tag = "analytics",
+    params(
+        ("dataset" = String, Path, description = "One of `depth`, `earnings`, `swap`, `runepool`"),
+        ("column" = String, Query, description = "Numeric column of the dataset, e.g. `total_volume_usd`, `asset_price_usd`, `units`"),
+        ("window" = Option<usize>, Query, description = "Number of intervals in the rolling window. Default is `7`, max is `400`"),
+        ("date_range" = Option<String>, Query, description = "Date range in format YYYY-MM-DD,YYYY-MM-DD"),
+        ("pool" = Option<String>, Query, description = "Pool name, only for the `depth` dataset. Default is `ETH.ETH`"),
+        ("units" = Option<String>, Query, description = "Amount columns in 1e8 base units (base), whole RUNE/asset (decimal) or USD at each interval's price (usd), converted before the statistics are computed. Default is `base`")
+    ),
+    responses(
+        (status = 200, description = "SMA, EMA, rolling sum, rolling stddev and percent change for every interval", body = RollingResponse),
+        (status = 400, description = "Invalid dataset, column, window or units, or a range of more than 100000 intervals"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn get_rolling_stats(
+    State(pool): State<DbPool>,
+    Path(dataset): Path<String>,
+    Query(params): Query<RollingQueryParams>,
+) -> impl IntoResponse {
+    info!(
+        "Received rolling stats request for {} with params: {:#?}",
+        dataset, params
+    );
+
+    let dataset = match Dataset::try_from(dataset) {
+        Ok(dataset) => dataset,
+        Err(e) => return bad_request(&e),
+    };
+    let column = match params
+        .column
+        .as_deref()
+        .map(|column| dataset.numeric_column(column))
+    {
+        Some(Ok(column)) => column,
+        Some(Err(e)) => return bad_request(&e),
+        None => return bad_request("column is required"),
+    };
+    let window = params.window.unwrap_or(DEFAULT_ROLLING_WINDOW);
+    if window == 0 || window > MAX_ROLLING_WINDOW {
+        return bad_request(&format!(
+            "window must be between 1 and {}",
+            MAX_ROLLING_WINDOW
+        ));
+    }
+    let pool_name = match (&params.pool, dataset.has_pool()) {
+        (Some(pool_name), true) => Some(pool_name.clone()),
+        (None, true) => Some(DEPTH_POOL.to_string()),
+        (Some(_), false) => return bad_request(&format!("{} is not stored per pool", dataset)),
+        (None, false) => None,
+    };
+    let units = match requested_units(params.units.clone()) {
+        Ok(units) => units,
+        Err(e) => return bad_request(&e),
+    };
+
+    // The 400 row page cap does not apply to derived series, `MAX_SERIES_ROWS` does
+    let date_range = params.parse_date_range();
+    let rows = match column_values(
+        &pool,
+        dataset,
+        column,
+        pool_name.as_deref(),
+        date_range,
+        units,
+    )
+    .await
+    {
+        Ok(rows) => rows,
+        Err(response) => return response,
+    };
+    if rows.is_empty() {
+        return no_data();
+    }
+
+    let values: Vec<f64> = rows.iter().map(|row| row.value).collect();
+    let sma = rolling::sma(&values, window);
+    let ema = rolling::ema(&values, window);
+    let rolling_sum = rolling::rolling_sum(&values, window);
+    let rolling_stddev = rolling::rolling_stddev(&values, window);
+    let pct_change = rolling::pct_change(&values, window);
+
+    let intervals = rows
+        .iter()
+        .enumerate()
+        .map(|(i, row)| RollingInterval {
+            start_time: row.start_time,
+            end_time: row.end_time,
+            value: row.value,
+            sma: sma[i],
+            ema: ema[i],
+            rolling_sum: rolling_sum[i],
+            rolling_stddev: rolling_stddev[i],
+            pct_change: pct_change[i],
+        })
+        .collect();
+
+    let response = RollingResponse {
+        dataset: dataset.to_string(),
+        column: column.to_string(),
+        window,
+        pool: pool_name,
+        intervals,
+    };
+
+    Json(response).into_response()
+}
+
+#[utoipa::path(
+    get,
+    path = "/timeseries",
+    operation_id = "get_timeseries",
+    tag = "analytics",
+    params(
+        ("series" = String, Query, description = "Comma separated `dataset.column` list, e.g. `swap.total_volume_usd,depth.asset_price_usd,earnings.earnings,runepool.units`"),
+        ("interval" = Option<String>, Query, description = "Bucket size (5min/hour/day/week/month/quarter/year). Default is `day`"),
+        ("date_range" = Option<String>, Query, description = "Date range in format YYYY-MM-DD,YYYY-MM-DD. Default is the range covered by the data"),
+        ("pool" = Option<String>, Query, description = "Pool name for `depth` series. Default is `ETH.ETH`"),
+        ("units" = Option<String>, Query, description = "Amount columns in 1e8 base units (base), whole RUNE/asset (decimal) or USD at each interval's price (usd), converted before they are bucketed. Default is `base`")
+    ),
+    responses(
+        (status = 200, description = "One value per bucket for every requested series, aligned on the same buckets", body = TimeseriesResponse),
+        (status = 400, description = "Invalid series, interval or units, too many buckets or a range of more than 100000 intervals"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn get_timeseries(
+    State(pool): State<DbPool>,
+    Query(params): Query<TimeseriesQueryParams>,
+) -> impl IntoResponse {
+    info!("Received timeseries request with params: {:#?}", params);
+
+    let mut requested = Vec::new();
+    for name in params
+        .series
+        .as_deref()
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+    {
+        let Some((dataset, column)) = name.split_once('.') else {
+            return bad_request(&format!(
+                "Invalid series `{}`, expected `dataset.column`",
+                name
+            ));
+        };
+        let dataset = match Dataset::try_from(dataset.to_string()) {
+            Ok(dataset) => dataset,
+            Err(e) => return bad_request(&e),
+        };
+        let column = match dataset.numeric_column(column) {
+            Ok(column) => column,
+            Err(e) => return bad_request(&e),
+        };
+        requested.push((name.to_string(), dataset, column));
+    }
+    if requested.is_empty() {
+        return bad_request("series is required");
+    }
+    if requested.len() > MAX_TIMESERIES_SERIES {
+        return bad_request(&format!(
+            "At most {} series can be requested at once",
+            MAX_TIMESERIES_SERIES
+        ));
+    }
+
+    let interval = match params.interval.clone().map(Interval::try_from) {
+        None => Interval::Day,
+        Some(Ok(interval)) => interval,
+        Some(Err(e)) => return bad_request(&e),
+    };
+    let units = match requested_units(params.units.clone()) {
+        Ok(units) => units,
+        Err(e) => return bad_request(&e),
+    };
+    let date_range = params.parse_date_range();
+    let depth_pool = params.pool.clone().unwrap_or(DEPTH_POOL.to_string());
+    let pool_of = |dataset: Dataset| dataset.has_pool().then_some(depth_pool.as_str());
+
+    // The buckets are checked before any series is loaded, without a date_range the range is the
+    // one the requested datasets cover
+    let (from, to) = match date_range {
+        Some(date_range) => date_range,
+        None => {
+            let mut covered: Option<(DateTime<Utc>, DateTime<Utc>)> = None;
+            for (_, dataset, _) in &requested {
+                match start_time_bounds(&pool, *dataset, pool_of(*dataset)).await {
+                    Ok(Some((first, last))) => {
+                        covered = Some(match covered {
+                            Some((from, to)) => (from.min(first), to.max(last)),
+                            None => (first, last),
+                        })
+                    }
+                    Ok(None) => {}
+                    Err(e) => return database_error("computing analytics", e),
+                }
+            }
+            match covered {
+                Some(covered) => covered,
+                None => return no_data(),
+            }
+        }
+    };
+    let Some(buckets) = timeseries::bucket_range(from, to, &interval, MAX_TIMESERIES_BUCKETS)
+    else {
+        return bad_request(&format!(
+            "The range covers more than {} buckets. Use a shorter date_range or a larger interval",
+            MAX_TIMESERIES_BUCKETS
+        ));
+    };
+
+    let mut fetched = Vec::new();
+    for (name, dataset, column) in requested {
+        match column_values(&pool, dataset, column, pool_of(dataset), date_range, units).await {
+            Ok(rows) => fetched.push((name, dataset, column, rows)),
+            Err(response) => return response,
+        }
+    }
+    debug!(
+        "Aligning {} series on {} {} buckets",
+        fetched.len(),
+        buckets.len(),
+        interval
+    );
+
+    let includes_depth = fetched.iter().any(|(_, dataset, _, _)| dataset.has_pool());
+    let series = fetched
+        .into_iter()
+        .map(|(name, dataset, column, rows)| {
+            let points: Vec<_> = rows.iter().map(|row| (row.start_time, row.value)).collect();
+            let values = timeseries::aggregate(&points, &interval, dataset.aggregation(column));
+            TimeseriesSeries {
+                name,
+                values: timeseries::align(&buckets, &values),
+            }
+        })
+        .collect();
+
+    let response = TimeseriesResponse {
+        pool: includes_depth.then_some(depth_pool),
+        buckets: buckets
+            .iter()
+            .map(|start| TimeseriesBucket {
+                start_time: *start,
+                end_time: timeseries::next_bucket(*start, &interval),
+            })
+            .collect(),
+        interval,
+        series,
+    };
+
+    Json(response).into_response()
+}
@@ -1,15 +1,19 @@
-use crate::core::models::common::{DEFAULT_PAGE_SIZE, MAX_PAGE_SIZE};
-use crate::core::models::depth_history::{
-    DepthHistoryQueryParams, DepthHistoryResponse, DepthInterval, MetaStats,
-};
+use crate::api::conditional::Validators;
+use crate::api::csv::{stream_csv, wants_csv};
+use crate::api::error::{bad_request, database_error, no_data};
+use crate::api::units::{history_response, requested_units};
+use crate::core::models::dataset::Dataset;
+use crate::core::models::depth_history::{DepthHistoryQueryParams, DepthHistoryResponse};
+use crate::core::models::units::Prices;
+use crate::services::repository::depth::{self, DepthFilter};
+use crate::services::repository::{intervals_query, Order, Page, Sort};
+use crate::services::storage::DbPool;
 use axum::{
     extract::{Query, State},
+    http::{HeaderMap, Uri},
     response::IntoResponse,
-    Json,
 };
-use serde_json::json;
-use sqlx::MySqlPool;
-use tracing::{debug, error, info};
+use tracing::{debug, info};
 
 #[utoipa::path(
     get,
@@ -17,12 +21,15 @@ use tracing::{debug, error, info};
     operation_id = "get_depth_history",
     tag = "depth",
     params(
+        ("pool" = Option<String>, Query, description = "Pool name. Default is `ETH.ETH`"),
         ("date_range" = Option<String>, Query, description = "Date range in format YYYY-MM-DD,YYYY-MM-DD"),
         ("liquidity_gt" = Option<u64>, Query, description = "Filter by minimum liquidity. Default is `0`"),
         ("sort_by" = Option<String>, Query, description = "Field to sort by. Default is `start_time`"),
         ("order" = Option<String>, Query, description = "Sort order (asc/desc). Default is `desc`"),
         ("page" = Option<u32>, Query, description = "Page number. Default is `0`"),
-        ("limit" = Option<u32>, Query, description = "Items per page. Default is `100`")
+        ("limit" = Option<u32>, Query, description = "Items per page. Default is `100`"),
+        ("format" = Option<String>, Query, description = "Response format (json/csv). `csv` (or `Accept: text/csv`) streams every matching row, ignoring `page` and `limit`"),
+        ("units" = Option<String>, Query, description = "Amounts in 1e8 base units (base), whole RUNE/asset (decimal) or USD at each interval's price (usd). Default is `base`")
     ),
     responses(
         (status = 200, description = "List of depth history intervals", body = DepthHistoryResponse),
@@ -30,128 +37,61 @@ use tracing::{debug, error, info};
     )
 )]
 pub async fn get_depth_history(
-    State(pool): State<MySqlPool>,
+    State(pool): State<DbPool>,
+    headers: HeaderMap,
+    uri: Uri,
     Query(params): Query<DepthHistoryQueryParams>,
 ) -> impl IntoResponse {
     info!("Received depth history request with params: {:#?}", params);
 
-    let limit = params.limit.unwrap_or(DEFAULT_PAGE_SIZE).min(MAX_PAGE_SIZE);
-    let offset = params.page.unwrap_or(0) * limit;
-    debug!("Using limit: {}, offset: {}", limit, offset);
-
-    let mut query = sqlx::QueryBuilder::new("SELECT * FROM `depth_intervals` WHERE 1=1");
-
-    // Handle date range
-    if let Some((start, end)) = params.parse_date_range() {
-        debug!("Date range filter: start={}, end={}", start, end);
-        query.push(" AND start_time >= ");
-        query.push_bind(start.naive_utc());
-        query.push(" AND end_time <= ");
-        query.push_bind(end.naive_utc());
-    } else {
-        debug!("No date range provided or invalid format");
-    }
-
-    // Handle liquidity filter
-    if let Some(min_liquidity) = params.liquidity_gt {
-        debug!("Liquidity filter: > {}", min_liquidity);
-        query.push(" AND liquidity_units > ");
-        query.push_bind(min_liquidity);
-    }
-
-    // TODO // Handle interval next time if you can
-    // if let Some(interval) = &params.interval {
-    //     debug!("Interval filter: {}", interval);
-    //     query.push(" AND `interval` = ");
-    //     query.push_bind(interval.to_string());
-    // }
+    let filter = DepthFilter::from(&params);
+    let sort = Sort::by(
+        Dataset::Depth,
+        params.get_sort_field(),
+        Order::from_param(params.order.as_deref()),
+    );
 
-    // Handle sorting
-    let sort_field = params.get_sort_field();
-    let sort_order = if params.order.as_deref() == Some("desc") {
-        "DESC"
-    } else {
-        "ASC"
+    let units = match requested_units(params.units.clone()) {
+        Ok(units) => units,
+        Err(e) => return bad_request(&e),
     };
-    debug!("Sorting by {} {}", sort_field, sort_order);
-    query.push(" ORDER BY ");
-    query.push(sort_field);
-    query.push(if params.order.as_deref() == Some("desc") {
-        " DESC"
-    } else {
-        " ASC"
-    });
 
-    // Add pagination
-    query.push(" LIMIT ");
-    query.push_bind(limit);
-    query.push(" OFFSET ");
-    query.push_bind(offset);
-
-    let query_sql = query.sql();
-    debug!("Executing SQL query: {}", query_sql);
+    // Exports skip pagination and stream every matching row
+    if wants_csv(params.format.as_deref(), &headers) {
+        let query = intervals_query(&pool, Dataset::Depth, &filter, sort);
+        debug!("Streaming CSV export: {}", query.sql());
+        return stream_csv(pool, query, Dataset::Depth, units);
+    }
 
-    match query
-        .build_query_as::<DepthInterval>()
-        .fetch_all(&pool)
-        .await
-    {
+    let page = Page::new(params.page, params.limit);
+    match depth::query_intervals(&pool, &filter, sort, Some(page)).await {
         Ok(intervals) => {
             info!("Successfully retrieved {} depth intervals", intervals.len());
 
-            if intervals.is_empty() {
-                return Json(json!({
-                    "success": true,
-                    "data": "no data found in the database for the given params"
-                }))
-                .into_response();
-            }
+            let Some(meta_stats) = depth::meta(&intervals) else {
+                return no_data();
+            };
 
-            // Calculate meta statistics
-            let meta_stats =
-                if let (Some(first), Some(last)) = (intervals.first(), intervals.last()) {
-                    MetaStats {
-                        start_time: first.start_time,
-                        end_time: last.end_time,
-                        start_asset_depth: first.asset_depth,
-                        end_asset_depth: last.asset_depth,
-                        start_rune_depth: first.rune_depth,
-                        end_rune_depth: last.rune_depth,
-                        start_lp_units: first.liquidity_units,
-                        end_lp_units: last.liquidity_units,
-                        start_member_count: first.members_count,
-                        end_member_count: last.members_count,
-                        start_synth_units: first.synth_units,
-                        end_synth_units: last.synth_units,
-                        // TODO Calculate this
-                        luvi_increase: 0.0,
-                        price_shift_loss: 0.0,
-                    }
-                } else {
-                    return Json(json!({
-                        "success": true,
-                        "data": "no data found"
-                    }))
-                    .into_response();
-                };
+            let validators = Validators::new(
+                &uri,
+                &intervals,
+                intervals.iter().map(|interval| interval.end_time),
+            );
+            if let Some(not_modified) = validators.check(&headers) {
+                return not_modified;
+            }
 
+            let prices: Vec<Prices> = intervals
+                .iter()
+                .map(|interval| Prices::pool(interval.asset_price, interval.asset_price_usd))
+                .collect();
             let response = DepthHistoryResponse {
                 intervals,
                 meta_stats,
             };
 
-            Json(response).into_response()
-        }
-        Err(e) => {
-            error!("Database error when fetching depth intervals: {}", e);
-            (
-                axum::http::StatusCode::INTERNAL_SERVER_ERROR,
-                Json(json!({
-                    "success": false,
-                    "error": format!("Database error: {}", e)
-                })),
-            )
-                .into_response()
+            validators.apply(history_response(Dataset::Depth, units, &response, &prices))
         }
+        Err(e) => database_error("fetching depth intervals", e),
     }
 }
@@ -1,35 +1,20 @@
-use crate::core::models::common::{DEFAULT_PAGE_SIZE, MAX_PAGE_SIZE};
-use crate::core::models::earnings_history::{EarningsHistoryQueryParams, Pool};
-use crate::core::models::earnings_history::{EarningsHistoryResponse, IntervalData, MetaStats};
-use axum::http::StatusCode;
-use axum::Json;
+use crate::api::conditional::Validators;
+use crate::api::csv::{stream_csv, wants_csv};
+use crate::api::error::{bad_request, database_error, no_data};
+use crate::api::units::{history_response, requested_units};
+use crate::core::models::dataset::Dataset;
+use crate::core::models::earnings_history::EarningsHistoryQueryParams;
+use crate::core::models::earnings_history::EarningsHistoryResponse;
+use crate::core::models::units::Prices;
+use crate::services::repository::earnings::{self, EarningsFilter};
+use crate::services::repository::{intervals_query, Order, Page, Sort};
+use crate::services::storage::DbPool;
 use axum::{
     extract::{Query, State},
+    http::{HeaderMap, Uri},
     response::IntoResponse,
 };
-use chrono::{DateTime, Utc};
-use serde_json::json;
-use serde_json::Value as JsonValue;
-use sqlx::prelude::FromRow;
-use sqlx::MySqlPool;
-use tracing::{debug, error, info};
-use utoipa::ToSchema;
-
-// !Just cuz in the models, we have intervalData which contains Vec<Pool> and rust don't know how to deserialize it
-// !So we need to create a new struct to deserialize it (Only solution i found)
-#[derive(Debug, FromRow, ToSchema, Clone)]
-struct EarningIntervalDB {
-    pub avg_node_count: f64,
-    pub block_rewards: u64,
-    pub bonding_earnings: u64,
-    pub earnings: u64,
-    pub end_time: DateTime<Utc>,
-    pub liquidity_earnings: u64,
-    pub liquidity_fees: u64,
-    pub rune_price_usd: f64,
-    pub start_time: DateTime<Utc>,
-    pub pools: JsonValue,
-}
+use tracing::{debug, info};
 
 // #[derive(Debug, Serialize, ToSchema)]
 // struct IntervalResponse {
@@ -67,7 +52,9 @@ struct EarningIntervalDB {
         ("sort_by" = Option<String>, Query, description = "Field to sort by. Default is `start_time`"),
         ("order" = Option<String>, Query, description = "Sort order (asc/desc). Default is `desc`"),
         ("page" = Option<u32>, Query, description = "Page number. Default is `0`"),
-        ("limit" = Option<u32>, Query, description = "Items per page. Default is `100`")
+        ("limit" = Option<u32>, Query, description = "Items per page. Default is `100`"),
+        ("format" = Option<String>, Query, description = "Response format (json/csv). `csv` (or `Accept: text/csv`) streams every matching row, ignoring `page` and `limit`. The `pools` breakdown is not part of the CSV"),
+        ("units" = Option<String>, Query, description = "Amounts in 1e8 base units (base), whole RUNE/asset (decimal) or USD at each interval's price (usd). Default is `base`")
     ),
     responses(
         (status = 200, description = "List of earnings history intervals", body = EarningsHistoryResponse),
@@ -75,7 +62,9 @@ struct EarningIntervalDB {
     )
 )]
 pub async fn get_earnings_history(
-    State(pool): State<MySqlPool>,
+    State(pool): State<DbPool>,
+    headers: HeaderMap,
+    uri: Uri,
     Query(params): Query<EarningsHistoryQueryParams>,
 ) -> impl IntoResponse {
     info!(
@@ -83,149 +72,57 @@ pub async fn get_earnings_history(
         params
     );
 
-    let limit = params.limit.unwrap_or(DEFAULT_PAGE_SIZE).min(MAX_PAGE_SIZE);
-    let offset = params.page.unwrap_or(0) * limit;
-    debug!("Using limit: {}, offset: {}", limit, offset);
-
-    let mut query = sqlx::QueryBuilder::new("SELECT * FROM `earning_intervals` WHERE 1=1");
-
-    // Add filters
-    if let Some((start, end)) = params.parse_date_range() {
-        debug!("Date range filter: start={}, end={}", start, end);
-        query
-            .push(" AND start_time >= ")
-            .push_bind(start)
-            .push(" AND end_time <= ")
-            .push_bind(end);
-    }
-
-    if let Some(min_earnings) = params.earnings_gt {
-        debug!("Earnings filter: > {}", min_earnings);
-        query.push(" AND earnings > ").push_bind(min_earnings);
-    }
-
-    if let Some(min_rewards) = params.block_rewards_gt {
-        debug!("Block rewards filter: > {}", min_rewards);
-        query.push(" AND block_rewards > ").push_bind(min_rewards);
-    }
-
-    if let Some(min_nodes) = params.node_count_gt {
-        debug!("Node count filter: > {}", min_nodes);
-        query.push(" AND avg_node_count > ").push_bind(min_nodes);
-    }
-
-    if let Some(pool_name) = &params.pool {
-        debug!("Pool filter: {}", pool_name);
-        query.push(" AND JSON_CONTAINS(pools, JSON_ARRAY(JSON_OBJECT('pool', ");
-        query.push_bind(pool_name);
-        query.push(")))");
-    }
+    let filter = EarningsFilter::from(&params);
+    let sort = Sort::by(
+        Dataset::Earnings,
+        params.get_sort_field(),
+        Order::from_param(params.order.as_deref()),
+    );
 
-    // Add sorting
-    let sort_field = params.get_sort_field();
-    let sort_order = if params.order.as_deref() == Some("desc") {
-        "DESC"
-    } else {
-        "ASC"
+    let units = match requested_units(params.units.clone()) {
+        Ok(units) => units,
+        Err(e) => return bad_request(&e),
     };
-    debug!("Sorting by {} {}", sort_field, sort_order);
-    query
-        .push(" ORDER BY ")
-        .push(sort_field)
-        .push(" ")
-        .push(sort_order);
 
-    query.push(" LIMIT ").push_bind(limit as i64);
-    query.push(" OFFSET ").push_bind(offset as i64);
-
-    let query_string = query.sql();
-    debug!("Executing query: {}", query_string);
+    // Exports skip pagination and stream every matching row
+    if wants_csv(params.format.as_deref(), &headers) {
+        let query = intervals_query(&pool, Dataset::Earnings, &filter, sort);
+        debug!("Streaming CSV export: {}", query.sql());
+        return stream_csv(pool, query, Dataset::Earnings, units);
+    }
 
-    match query
-        .build_query_as::<EarningIntervalDB>()
-        .fetch_all(&pool)
-        .await
-    {
-        Ok(db_intervals) => {
-            if db_intervals.is_empty() {
-                return Json(json!({
-                    "success": true,
-                    "data": "no data found in the database for the given params"
-                }))
-                .into_response();
+    let page = Page::new(params.page, params.limit);
+    match earnings::query_intervals(&pool, &filter, sort, Some(page)).await {
+        Ok(intervals) => {
+            let Some(meta_stats) = earnings::meta(&intervals) else {
+                return no_data();
+            };
+
+            let validators = Validators::new(
+                &uri,
+                &intervals,
+                intervals.iter().map(|interval| interval.end_time),
+            );
+            if let Some(not_modified) = validators.check(&headers) {
+                return not_modified;
             }
 
-            let intervals: Result<Vec<IntervalData>, serde_json::Error> = db_intervals
+            let prices: Vec<Prices> = intervals
                 .iter()
-                .map(|db| {
-                    let pools: Vec<Pool> = serde_json::from_value(db.pools.clone())?;
-
-                    Ok(IntervalData {
-                        start_time: db.start_time,
-                        end_time: db.end_time,
-                        avg_node_count: db.avg_node_count,
-                        block_rewards: db.block_rewards,
-                        bonding_earnings: db.bonding_earnings,
-                        earnings: db.earnings,
-                        liquidity_earnings: db.liquidity_earnings,
-                        liquidity_fees: db.liquidity_fees,
-                        rune_price_usd: db.rune_price_usd,
-                        pools,
-                    })
-                })
+                .map(|interval| Prices::rune(interval.rune_price_usd))
                 .collect();
-
-            match intervals {
-                Ok(intervals) => {
-                    // Calculate meta statistics
-                    let meta_stats = if let (Some(_first), Some(last)) =
-                        (db_intervals.first(), db_intervals.last())
-                    {
-                        MetaStats {
-                            avg_node_count: last.avg_node_count,
-                            block_rewards: last.block_rewards,
-                            bonding_earnings: last.bonding_earnings,
-                            earnings: last.earnings,
-                            end_time: last.end_time,
-                            liquidity_earnings: last.liquidity_earnings,
-                            liquidity_fees: last.liquidity_fees,
-                            pools: serde_json::from_value(last.pools.clone()).unwrap_or_default(),
-                        }
-                    } else {
-                        return Json(json!({
-                            "success": true,
-                            "data": "no data found"
-                        }))
-                        .into_response();
-                    };
-
-                    let response = EarningsHistoryResponse {
-                        intervals,
-                        meta_stats,
-                    };
-
-                    Json(response).into_response()
-                }
-                Err(e) => {
-                    error!("Error parsing intervals: {}", e);
-                    (
-                        StatusCode::INTERNAL_SERVER_ERROR,
-                        Json(json!({
-                            "success": false,
-                            "error": format!("Error parsing JSON pools data: {}", e)
-                        })),
-                    )
-                        .into_response()
-                }
-            }
-        }
-        Err(e) => {
-            error!("Database error when fetching earnings intervals: {}", e);
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                format!("Database error: {}", e),
-            )
-                .into_response()
+            let response = EarningsHistoryResponse {
+                intervals,
+                meta_stats,
+            };
+
+            validators.apply(history_response(
+                Dataset::Earnings,
+                units,
+                &response,
+                &prices,
+            ))
         }
+        Err(e) => database_error("fetching earnings intervals", e),
     }
 }
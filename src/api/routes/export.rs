@@ -0,0 +1,75 @@
+use crate::api::error::bad_request;
+use crate::api::export::stream_export;
+use crate::core::models::dataset::Dataset;
+use crate::core::models::depth_history::DEPTH_POOL;
+use crate::core::models::export::{ExportFormat, ExportQueryParams};
+use crate::services::storage::DbPool;
+use axum::{
+    extract::{Path, Query, State},
+    response::IntoResponse,
+};
+use chrono::DateTime;
+use tracing::info;
+
+#[utoipa::path(
+    get,
+    path = "/export/{dataset}",
+    operation_id = "get_export",
+    tag = "export",
+    params(
+        ("dataset" = String, Path, description = "One of `depth`, `earnings`, `swap`, `runepool`"),
+        ("from" = Option<i64>, Query, description = "Unix timestamp, intervals starting at or after it. Default is the first stored interval"),
+        ("to" = Option<i64>, Query, description = "Unix timestamp, intervals starting before it. Default is the last stored interval"),
+        ("pool" = Option<String>, Query, description = "Pool name, only for the `depth` dataset. Default is `ETH.ETH`"),
+        ("format" = Option<String>, Query, description = "Output format (parquet/ndjson). Default is `ndjson`")
+    ),
+    responses(
+        (status = 200, description = "Every interval of the range, streamed as parquet or newline delimited JSON"),
+        (status = 400, description = "Invalid dataset, format, pool or range"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn get_export(
+    State(pool): State<DbPool>,
+    Path(dataset): Path<String>,
+    Query(params): Query<ExportQueryParams>,
+) -> impl IntoResponse {
+    info!(
+        "Received export request for {} with params: {:#?}",
+        dataset, params
+    );
+
+    let dataset = match Dataset::try_from(dataset) {
+        Ok(dataset) => dataset,
+        Err(e) => return bad_request(&e),
+    };
+    let pool_name = match (params.pool, dataset.has_pool()) {
+        (Some(pool_name), true) => Some(pool_name),
+        (None, true) => Some(DEPTH_POOL.to_string()),
+        (Some(_), false) => return bad_request(&format!("{} is not stored per pool", dataset)),
+        (None, false) => None,
+    };
+    let format = match params.format.map(ExportFormat::try_from) {
+        None => ExportFormat::Ndjson,
+        Some(Ok(format)) => format,
+        Some(Err(e)) => return bad_request(&e),
+    };
+
+    let from = match params.from.map(|from| DateTime::from_timestamp(from, 0)) {
+        None => None,
+        Some(Some(from)) => Some(from),
+        Some(None) => return bad_request("Invalid `from` timestamp"),
+    };
+    let to = match params.to.map(|to| DateTime::from_timestamp(to, 0)) {
+        None => None,
+        Some(Some(to)) => Some(to),
+        Some(None) => return bad_request("Invalid `to` timestamp"),
+    };
+    if let (Some(from), Some(to)) = (from, to) {
+        if from >= to {
+            return bad_request("`from` must be before `to`");
+        }
+    }
+
+    stream_export(pool, dataset, format, pool_name, from, to)
+}
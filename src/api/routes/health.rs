@@ -0,0 +1,153 @@
+use crate::api::error::database_error;
+use crate::config::app::AppConfig;
+use crate::config::connect::{check_schema, SchemaError};
+use crate::core::models::dataset::Dataset;
+use crate::core::models::health::{
+    DatasetFreshness, FreshnessQueryParams, FreshnessResponse, HealthResponse, ReadinessResponse,
+    TaskStatus, TasksResponse,
+};
+use crate::services::repository::dataset::latest_end_time;
+use crate::services::storage::{DbPool, Storage};
+use crate::services::supervisor::task_states;
+use axum::http::StatusCode;
+use axum::Json;
+use axum::{
+    extract::{Query, State},
+    response::IntoResponse,
+};
+use chrono::Utc;
+use std::sync::Arc;
+use tracing::error;
+
+#[utoipa::path(
+    get,
+    path = "/healthz",
+    operation_id = "get_healthz",
+    tag = "health",
+    security(()),
+    responses(
+        (status = 200, description = "The process is up", body = HealthResponse)
+    )
+)]
+pub async fn get_healthz() -> impl IntoResponse {
+    Json(HealthResponse { status: "ok" })
+}
+
+#[utoipa::path(
+    get,
+    path = "/readyz",
+    operation_id = "get_readyz",
+    tag = "health",
+    security(()),
+    responses(
+        (status = 200, description = "The database is reachable and fully migrated", body = ReadinessResponse),
+        (status = 503, description = "The database is unreachable, has pending migrations or a schema this build can't use", body = ReadinessResponse)
+    )
+)]
+pub async fn get_readyz(State(pool): State<DbPool>) -> impl IntoResponse {
+    let response = match check_schema(&pool).await {
+        Ok(pending) => ReadinessResponse {
+            ready: pending.is_empty(),
+            database: true,
+            pending_migrations: pending,
+            error: None,
+        },
+        Err(e) => {
+            error!("Readiness check failed: {}", e);
+            ReadinessResponse {
+                ready: false,
+                database: !matches!(e, SchemaError::Database(_)) || pool.ping().await.is_ok(),
+                pending_migrations: Vec::new(),
+                error: Some(e.to_string()),
+            }
+        }
+    };
+
+    let status = if response.ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (status, Json(response))
+}
+
+#[utoipa::path(
+    get,
+    path = "/freshness",
+    operation_id = "get_freshness",
+    tag = "health",
+    security(()),
+    params(
+        ("max_lag" = Option<i64>, Query, description = "Seconds a dataset may lag behind now before it counts as stale. Default is `freshness.max_lag_secs` from the config, `7200` unless changed")
+    ),
+    responses(
+        (status = 200, description = "Every dataset is fresh", body = FreshnessResponse),
+        (status = 503, description = "At least one dataset is stale or empty", body = FreshnessResponse),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn get_freshness(
+    State(pool): State<DbPool>,
+    State(config): State<Arc<AppConfig>>,
+    Query(params): Query<FreshnessQueryParams>,
+) -> impl IntoResponse {
+    let max_lag = params.max_lag.unwrap_or(config.freshness.max_lag_secs);
+    let now = Utc::now();
+
+    let mut datasets = Vec::with_capacity(Dataset::ALL.len());
+    for dataset in Dataset::ALL {
+        let latest = match latest_end_time(&pool, dataset).await {
+            Ok(latest) => latest,
+            Err(e) => {
+                return database_error(&format!("checking {} freshness", dataset), e);
+            }
+        };
+
+        // Midgard reports the current, still open interval so its end can be in the future
+        let lag = latest.map(|latest| (now - latest).num_seconds().max(0));
+        datasets.push(DatasetFreshness {
+            dataset: dataset.to_string(),
+            latest_end_time: latest.map(|latest| latest.timestamp()),
+            lag_seconds: lag,
+            stale: lag.is_none_or(|lag| lag > max_lag),
+        });
+    }
+
+    let fresh = datasets.iter().all(|dataset| !dataset.stale);
+    let status = if fresh {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (
+        status,
+        Json(FreshnessResponse {
+            fresh,
+            max_lag_seconds: max_lag,
+            datasets,
+        }),
+    )
+        .into_response()
+}
+
+#[utoipa::path(
+    get,
+    path = "/tasks",
+    operation_id = "get_tasks",
+    tag = "health",
+    security(()),
+    responses(
+        (status = 200, description = "Every background task is running", body = TasksResponse),
+        (status = 503, description = "A background task failed and is waiting to be restarted, or the process is shutting down", body = TasksResponse)
+    )
+)]
+pub async fn get_tasks() -> impl IntoResponse {
+    let tasks = task_states();
+    let healthy = tasks.iter().all(|task| task.status == TaskStatus::Running);
+    let status = if healthy {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (status, Json(TasksResponse { healthy, tasks }))
+}
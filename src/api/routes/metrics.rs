@@ -0,0 +1,33 @@
+use crate::services::storage::DbPool;
+use crate::services::telemetry;
+use axum::http::{header, StatusCode};
+use axum::{extract::State, response::IntoResponse};
+
+#[utoipa::path(
+    get,
+    path = "/metrics",
+    operation_id = "get_metrics",
+    tag = "health",
+    security(()),
+    responses(
+        (status = 200, description = "Prometheus text exposition of the HTTP, database and ingestion metrics", content_type = "text/plain"),
+        (status = 503, description = "Metrics are not being recorded")
+    )
+)]
+pub async fn get_metrics(State(pool): State<DbPool>) -> impl IntoResponse {
+    telemetry::record_pool(&pool);
+
+    match telemetry::render() {
+        Some(metrics) => (
+            StatusCode::OK,
+            [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+            metrics,
+        )
+            .into_response(),
+        None => (
+            StatusCode::SERVICE_UNAVAILABLE,
+            "metrics recorder not installed",
+        )
+            .into_response(),
+    }
+}
@@ -1,4 +1,10 @@
+pub mod actions;
+pub mod admin;
+pub mod analytics;
 pub mod depth;
 pub mod earnings;
+pub mod export;
+pub mod health;
+pub mod metrics;
 pub mod runepool;
 pub mod swap;
@@ -1,17 +1,21 @@
-use crate::core::models::common::{DEFAULT_PAGE_SIZE, MAX_PAGE_SIZE};
+use crate::api::conditional::Validators;
+use crate::api::csv::{stream_csv, wants_csv};
+use crate::api::error::{database_error, no_data};
+use crate::core::models::dataset::Dataset;
 use crate::core::models::runepool_units_history::{
-    MetaStats, RunepoolUnitsHistoryQueryParams, RunepoolUnitsHistoryResponse, RunepoolUnitsInterval,
+    RunepoolUnitsHistoryQueryParams, RunepoolUnitsHistoryResponse,
 };
-use axum::http::StatusCode;
-
+use crate::core::models::units::Units;
+use crate::services::repository::runepool::{self, RunepoolFilter};
+use crate::services::repository::{intervals_query, Order, Page, Sort};
+use crate::services::storage::DbPool;
 use axum::Json;
 use axum::{
     extract::{Query, State},
+    http::{HeaderMap, Uri},
     response::IntoResponse,
 };
-use serde_json::json;
-use sqlx::MySqlPool;
-use tracing::{debug, error, info};
+use tracing::{debug, info};
 
 #[utoipa::path(
     get,
@@ -24,7 +28,8 @@ use tracing::{debug, error, info};
         ("sort_by" = Option<String>, Query, description = "Field to sort by. Default is `start_time`"),
         ("order" = Option<String>, Query, description = "Sort order (asc/desc). Default is `desc`"),
         ("page" = Option<u32>, Query, description = "Page number. Default is `0`"),
-        ("limit" = Option<u32>, Query, description = "Items per page. Default is `100`")
+        ("limit" = Option<u32>, Query, description = "Items per page. Default is `100`"),
+        ("format" = Option<String>, Query, description = "Response format (json/csv). `csv` (or `Accept: text/csv`) streams every matching row, ignoring `page` and `limit`")
     ),
     responses(
         (status = 200, description = "List of runepool units history intervals", body = RunepoolUnitsHistoryResponse),
@@ -32,7 +37,9 @@ use tracing::{debug, error, info};
     )
 )]
 pub async fn get_runepool_units_history(
-    State(pool): State<MySqlPool>,
+    State(pool): State<DbPool>,
+    headers: HeaderMap,
+    uri: Uri,
     Query(params): Query<RunepoolUnitsHistoryQueryParams>,
 ) -> impl IntoResponse {
     info!(
@@ -40,99 +47,48 @@ pub async fn get_runepool_units_history(
         params
     );
 
-    let limit = params.limit.unwrap_or(DEFAULT_PAGE_SIZE).min(MAX_PAGE_SIZE);
-    let offset = params.page.unwrap_or(0) * limit;
-    debug!("Using limit: {}, offset: {}", limit, offset);
-
-    let mut query = sqlx::QueryBuilder::new("SELECT * FROM `runepool_unit_intervals` WHERE 1=1");
-
-    if let Some((start, end)) = params.parse_date_range() {
-        debug!("Date range filter: start={}, end={}", start, end);
-        query
-            .push(" AND start_time >= ")
-            .push_bind(start)
-            .push(" AND end_time <= ")
-            .push_bind(end);
-    }
+    let filter = RunepoolFilter::from(&params);
+    let sort = Sort::by(
+        Dataset::Runepool,
+        params.get_sort_field(),
+        Order::from_param(params.order.as_deref()),
+    );
 
-    if let Some(min_units) = params.units_gt {
-        debug!("Units filter: > {}", min_units);
-        query.push(" AND units > ").push_bind(min_units);
+    // Exports skip pagination and stream every matching row
+    if wants_csv(params.format.as_deref(), &headers) {
+        let query = intervals_query(&pool, Dataset::Runepool, &filter, sort);
+        debug!("Streaming CSV export: {}", query.sql());
+        return stream_csv(pool, query, Dataset::Runepool, Units::Base);
     }
 
-    let sort_field = params.get_sort_field();
-    let sort_order = if params.order.as_deref() == Some("desc") {
-        "DESC"
-    } else {
-        "ASC"
-    };
-
-    query
-        .push(" ORDER BY ")
-        .push(sort_field)
-        .push(" ")
-        .push(sort_order);
-
-    query.push(" LIMIT ").push_bind(limit as i64);
-    query.push(" OFFSET ").push_bind(offset as i64);
-
-    let query_string = query.sql();
-    debug!("Executing query: {}", query_string);
-
-    match query
-        .build_query_as::<RunepoolUnitsInterval>()
-        .fetch_all(&pool)
-        .await
-    {
+    let page = Page::new(params.page, params.limit);
+    match runepool::query_intervals(&pool, &filter, sort, Some(page)).await {
         Ok(intervals) => {
             info!(
                 "Successfully retrieved {} runepool unit intervals",
                 intervals.len()
             );
 
-            if intervals.is_empty() {
-                return Json(json!({
-                    "success": true,
-                    "data": "no data found in the database for the given params"
-                }))
-                .into_response();
-            }
+            let Some(meta_stats) = runepool::meta(&intervals) else {
+                return no_data();
+            };
 
-            let meta_stats =
-                if let (Some(first), Some(last)) = (intervals.first(), intervals.last()) {
-                    MetaStats {
-                        start_time: first.start_time,
-                        end_time: last.end_time,
-                        start_count: first.count,
-                        end_count: last.count,
-                        start_units: first.units,
-                        end_units: last.units,
-                    }
-                } else {
-                    return Json(json!({
-                        "success": true,
-                        "data": "no data found in the database for the given params"
-                    }))
-                    .into_response();
-                };
+            let validators = Validators::new(
+                &uri,
+                &intervals,
+                intervals.iter().map(|interval| interval.end_time),
+            );
+            if let Some(not_modified) = validators.check(&headers) {
+                return not_modified;
+            }
 
             let response = RunepoolUnitsHistoryResponse {
                 intervals,
                 meta_stats,
             };
 
-            Json(response).into_response()
-        }
-        Err(e) => {
-            error!("Database error: {}", e);
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(json!({
-                    "success": false,
-                    "error": format!("Database error: {}", e)
-                })),
-            )
-                .into_response()
+            validators.apply(Json(response).into_response())
         }
+        Err(e) => database_error("fetching runepool intervals", e),
     }
 }
@@ -1,16 +1,14 @@
-use crate::{
-    core::models::{
-        common::Interval,
-        depth_history::{DepthHistoryParams, DepthHistoryResponse},
-    },
-    services::client::get_midgard_api_url,
+use crate::core::models::{
+    common::Interval,
+    depth_history::{DepthHistoryParams, DepthHistoryResponse, DEPTH_POOL},
 };
 use chrono::Utc;
 use reqwest::Client;
 
-pub async fn fetch_initial_depth_history() -> Result<DepthHistoryResponse, reqwest::Error> {
+pub async fn fetch_initial_depth_history(
+    base_url: &str,
+) -> Result<DepthHistoryResponse, reqwest::Error> {
     let client = Client::new();
-    let base_url = get_midgard_api_url();
 
     let params = DepthHistoryParams {
         interval: Some(Interval::Hour),
@@ -19,7 +17,7 @@ pub async fn fetch_initial_depth_history() -> Result<DepthHistoryResponse, reqwe
         to: Some(Utc::now()),
     };
 
-    let mut url = reqwest::Url::parse(&format!("{}/history/depths/ETH.ETH", base_url))
+    let mut url = reqwest::Url::parse(&format!("{}/history/depths/{}", base_url, DEPTH_POOL))
         .expect("Failed to parse URL");
 
     if let Some(interval) = &params.interval {
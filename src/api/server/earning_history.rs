@@ -1,16 +1,14 @@
-use crate::{
-    core::models::{
-        common::Interval,
-        earnings_history::{EarningsHistoryParams, EarningsHistoryResponse},
-    },
-    services::client::get_midgard_api_url,
+use crate::core::models::{
+    common::Interval,
+    earnings_history::{EarningsHistoryParams, EarningsHistoryResponse},
 };
 use chrono::Utc;
 use reqwest::Client;
 
-pub async fn fetch_initial_earnings_history() -> Result<EarningsHistoryResponse, reqwest::Error> {
+pub async fn fetch_initial_earnings_history(
+    base_url: &str,
+) -> Result<EarningsHistoryResponse, reqwest::Error> {
     let client = Client::new();
-    let base_url = get_midgard_api_url();
 
     let params = EarningsHistoryParams {
         interval: Some(Interval::Hour),
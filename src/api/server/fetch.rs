@@ -3,14 +3,16 @@ use super::{
     runepool_units_history::fetch_initial_runepool_units_history,
     swap_history::fetch_initial_swap_history,
 };
+use crate::core::models::depth_history::DEPTH_POOL;
 use crate::services::repository::{depth, earnings, runepool, swap};
+use crate::services::storage::DbPool;
 
-pub async fn fetch_and_store_depth_history(pool: &sqlx::MySqlPool) {
+pub async fn fetch_and_store_depth_history(pool: &DbPool, base_url: &str) {
     tracing::info!("Fetching initial depth history...");
-    match fetch_initial_depth_history().await {
+    match fetch_initial_depth_history(base_url).await {
         Ok(initial_data) => {
             tracing::info!("Successfully fetched initial depth history");
-            match depth::store_intervals(pool, &initial_data.intervals).await {
+            match depth::store_intervals(pool, DEPTH_POOL, &initial_data.intervals).await {
                 Ok(_) => tracing::info!(
                     "Successfully stored {} intervals",
                     initial_data.intervals.len()
@@ -22,9 +24,9 @@ pub async fn fetch_and_store_depth_history(pool: &sqlx::MySqlPool) {
     }
 }
 
-pub async fn fetch_and_store_earnings_history(pool: &sqlx::MySqlPool) {
+pub async fn fetch_and_store_earnings_history(pool: &DbPool, base_url: &str) {
     tracing::info!("Fetching initial earnings history...");
-    match fetch_initial_earnings_history().await {
+    match fetch_initial_earnings_history(base_url).await {
         Ok(initial_data) => {
             tracing::info!("Successfully fetched initial earnings history");
             match earnings::store_intervals(pool, &initial_data.intervals).await {
@@ -39,9 +41,9 @@ pub async fn fetch_and_store_earnings_history(pool: &sqlx::MySqlPool) {
     }
 }
 
-pub async fn fetch_and_store_swap_history(pool: &sqlx::MySqlPool) {
+pub async fn fetch_and_store_swap_history(pool: &DbPool, base_url: &str) {
     tracing::info!("Fetching initial swap history...");
-    match fetch_initial_swap_history().await {
+    match fetch_initial_swap_history(base_url).await {
         Ok(initial_data) => {
             tracing::info!("Successfully fetched initial swap history");
             match swap::store_intervals(pool, &initial_data.intervals).await {
@@ -56,9 +58,9 @@ pub async fn fetch_and_store_swap_history(pool: &sqlx::MySqlPool) {
     }
 }
 
-pub async fn fetch_and_store_runepool_units_history(pool: &sqlx::MySqlPool) {
+pub async fn fetch_and_store_runepool_units_history(pool: &DbPool, base_url: &str) {
     tracing::info!("Fetching initial runepool units history...");
-    match fetch_initial_runepool_units_history().await {
+    match fetch_initial_runepool_units_history(base_url).await {
         Ok(initial_data) => {
             tracing::info!("Successfully fetched initial runepool units history");
             match runepool::store_intervals(pool, &initial_data.intervals).await {
@@ -1,17 +1,14 @@
-use crate::{
-    core::models::{
-        common::Interval,
-        runepool_units_history::{RunepoolUnitsHistoryParams, RunepoolUnitsHistoryResponse},
-    },
-    services::client::get_midgard_api_url,
+use crate::core::models::{
+    common::Interval,
+    runepool_units_history::{RunepoolUnitsHistoryParams, RunepoolUnitsHistoryResponse},
 };
 use chrono::Utc;
 use reqwest::Client;
 
 pub async fn fetch_initial_runepool_units_history(
+    base_url: &str,
 ) -> Result<RunepoolUnitsHistoryResponse, reqwest::Error> {
     let client = Client::new();
-    let base_url = get_midgard_api_url();
 
     let params = RunepoolUnitsHistoryParams {
         interval: Some(Interval::Hour),
@@ -1,16 +1,14 @@
-use crate::{
-    core::models::{
-        common::Interval,
-        swap_history::{SwapHistoryParams, SwapHistoryResponse},
-    },
-    services::client::get_midgard_api_url,
+use crate::core::models::{
+    common::Interval,
+    swap_history::{SwapHistoryParams, SwapHistoryResponse},
 };
 use chrono::Utc;
 use reqwest::Client;
 
-pub async fn fetch_initial_swap_history() -> Result<SwapHistoryResponse, reqwest::Error> {
+pub async fn fetch_initial_swap_history(
+    base_url: &str,
+) -> Result<SwapHistoryResponse, reqwest::Error> {
     let client = Client::new();
-    let base_url = get_midgard_api_url();
 
     let params = SwapHistoryParams {
         interval: Some(Interval::Hour),
@@ -0,0 +1,11 @@
+use crate::config::app::AppConfig;
+use crate::services::storage::DbPool;
+use axum::extract::FromRef;
+use std::sync::Arc;
+
+// Handlers pick what they need with `State<DbPool>` or `State<Arc<AppConfig>>`
+#[derive(Clone, FromRef)]
+pub struct AppState {
+    pub pool: DbPool,
+    pub config: Arc<AppConfig>,
+}
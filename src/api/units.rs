@@ -0,0 +1,257 @@
+use crate::api::error::error_response;
+use crate::core::models::dataset::Dataset;
+use crate::core::models::serialization::{number_mode, NumberMode};
+use crate::core::models::units::{Denomination, Prices, Units};
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+use tracing::error;
+
+// Amounts of an entry of the earnings `pools` array. Asset amounts have no USD price there.
+const POOL_EARNINGS_AMOUNTS: &[(&str, Denomination)] = &[
+    ("assetLiquidityFees", Denomination::Asset),
+    ("earnings", Denomination::Rune),
+    ("rewards", Denomination::Rune),
+    ("runeLiquidityFees", Denomination::Rune),
+    ("saverEarning", Denomination::Asset),
+    ("totalLiquidityFeesRune", Denomination::Rune),
+];
+
+// `asset_depth` is `assetDepth` in responses, `total_volume_usd` is `totalVolumeUSD`
+fn json_key(column: &str) -> String {
+    let mut key = String::new();
+    for (i, word) in column.split('_').enumerate() {
+        match word {
+            "usd" if i > 0 => key.push_str("USD"),
+            word if i > 0 => {
+                let mut chars = word.chars();
+                key.extend(chars.next().map(|first| first.to_ascii_uppercase()));
+                key.push_str(chars.as_str());
+            }
+            word => key.push_str(word),
+        }
+    }
+    key
+}
+
+// Amount fields of a history interval, keyed like the response
+fn history_amounts(dataset: Dataset) -> Vec<(String, Denomination)> {
+    dataset
+        .numeric_columns()
+        .iter()
+        .filter_map(|column| {
+            dataset
+                .denomination(column.name)
+                .map(|denomination| (json_key(column.name), denomination))
+        })
+        .collect()
+}
+
+// Amounts are strings or numbers depending on `numbers`, converted values follow the same mode
+fn amount(value: &JsonValue) -> Option<f64> {
+    match value {
+        JsonValue::String(text) => text.parse().ok(),
+        value => value.as_f64(),
+    }
+}
+
+pub fn encode(value: Option<f64>) -> JsonValue {
+    match (value, number_mode()) {
+        (Some(value), NumberMode::String) => value.to_string().into(),
+        (Some(value), NumberMode::Native) => value.into(),
+        (None, _) => JsonValue::Null,
+    }
+}
+
+// Converts the amounts of `object` in place, fields it does not have are skipped
+pub fn convert_fields<K: AsRef<str>>(
+    object: &mut JsonValue,
+    fields: &[(K, Denomination)],
+    units: Units,
+    prices: &Prices,
+) {
+    for (key, denomination) in fields {
+        if let Some(value) = object.get_mut(key.as_ref()) {
+            *value = encode(
+                amount(value).and_then(|amount| units.convert(amount, *denomination, prices)),
+            );
+        }
+    }
+}
+
+fn convert_interval(
+    object: &mut JsonValue,
+    fields: &[(String, Denomination)],
+    units: Units,
+    prices: &Prices,
+) {
+    convert_fields(object, fields, units, prices);
+    if let Some(pools) = object.get_mut("pools").and_then(JsonValue::as_array_mut) {
+        for entry in pools {
+            convert_fields(entry, POOL_EARNINGS_AMOUNTS, units, prices);
+        }
+    }
+}
+
+// Through bytes rather than `to_value`, which refuses native numbers past 64 bits
+pub fn to_json(response: &impl Serialize) -> Result<JsonValue, serde_json::Error> {
+    serde_json::from_slice(&serde_json::to_vec(response)?)
+}
+
+// A history response (`intervals` and `meta`) with its amounts in `units`. `prices` go with the
+// intervals one to one. The meta figures are those of the last interval, or of the first one for
+// the `start*` fields, and are converted at that interval's prices.
+pub fn convert_history(
+    dataset: Dataset,
+    units: Units,
+    response: &impl Serialize,
+    prices: &[Prices],
+) -> Result<JsonValue, serde_json::Error> {
+    let mut body = to_json(response)?;
+    if units == Units::Base {
+        return Ok(body);
+    }
+
+    let fields = history_amounts(dataset);
+    if let Some(intervals) = body.get_mut("intervals").and_then(JsonValue::as_array_mut) {
+        for (interval, prices) in intervals.iter_mut().zip(prices) {
+            convert_interval(interval, &fields, units, prices);
+        }
+    }
+
+    let first = prices.first().copied().unwrap_or_default();
+    let last = prices.last().copied().unwrap_or_default();
+    if let Some(meta) = body.get_mut("meta") {
+        convert_interval(meta, &fields, units, &last);
+        for (key, denomination) in &fields {
+            let capitalized = key[..1].to_uppercase() + &key[1..];
+            convert_fields(
+                meta,
+                &[(format!("start{}", capitalized), *denomination)],
+                units,
+                &first,
+            );
+            convert_fields(
+                meta,
+                &[(format!("end{}", capitalized), *denomination)],
+                units,
+                &last,
+            );
+        }
+    }
+    Ok(body)
+}
+
+// The `units` query param, or why it is invalid
+pub fn requested_units(units: Option<String>) -> Result<Units, String> {
+    units.map(Units::try_from).unwrap_or(Ok(Units::default()))
+}
+
+// JSON answer of a history route, untouched in base units
+pub fn history_response(
+    dataset: Dataset,
+    units: Units,
+    response: &impl Serialize,
+    prices: &[Prices],
+) -> Response {
+    if units == Units::Base {
+        return Json(response).into_response();
+    }
+    match convert_history(dataset, units, response, prices) {
+        Ok(body) => Json(body).into_response(),
+        Err(e) => {
+            error!(
+                "Failed to convert the {} history to {:?}: {}",
+                dataset, units, e
+            );
+            error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                &format!("Serialization error: {}", e),
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn response_keys_follow_the_columns() {
+        assert_eq!(json_key("asset_depth"), "assetDepth");
+        assert_eq!(json_key("total_volume_usd"), "totalVolumeUSD");
+        assert_eq!(json_key("earnings"), "earnings");
+        assert_eq!(
+            history_amounts(Dataset::Depth)
+                .into_iter()
+                .map(|(key, _)| key)
+                .collect::<Vec<_>>(),
+            ["assetDepth", "runeDepth", "synthSupply"]
+        );
+        assert!(history_amounts(Dataset::Runepool).is_empty());
+    }
+
+    #[test]
+    fn depth_history_is_converted_at_each_interval_price() {
+        let response = json!({
+            "intervals": [
+                { "assetDepth": "200000000", "runeDepth": "400000000", "units": "7" },
+                { "assetDepth": "300000000", "runeDepth": "500000000", "units": "7" },
+            ],
+            "meta": {
+                "startAssetDepth": "200000000",
+                "endAssetDepth": "300000000",
+                "endLPUnits": "7",
+            },
+        });
+        let prices = [Prices::pool(2.0, 10.0), Prices::pool(4.0, 20.0)];
+
+        let decimal = convert_history(Dataset::Depth, Units::Decimal, &response, &prices).unwrap();
+        assert_eq!(decimal["intervals"][0]["assetDepth"], "2");
+        assert_eq!(decimal["intervals"][1]["runeDepth"], "5");
+        assert_eq!(decimal["intervals"][1]["units"], "7");
+
+        let usd = convert_history(Dataset::Depth, Units::Usd, &response, &prices).unwrap();
+        assert_eq!(usd["intervals"][0]["assetDepth"], "20");
+        assert_eq!(usd["intervals"][0]["runeDepth"], "20");
+        assert_eq!(usd["intervals"][1]["runeDepth"], "25");
+        assert_eq!(usd["meta"]["startAssetDepth"], "20");
+        assert_eq!(usd["meta"]["endAssetDepth"], "60");
+        assert_eq!(usd["meta"]["endLPUnits"], "7");
+
+        let base = convert_history(Dataset::Depth, Units::Base, &response, &prices).unwrap();
+        assert_eq!(base, response);
+    }
+
+    #[test]
+    fn pool_earnings_without_a_price_are_null_in_usd() {
+        let response = json!({
+            "intervals": [{
+                "earnings": "100000000",
+                "pools": [{ "pool": "BTC.BTC", "earnings": "50000000", "saverEarning": "1000" }],
+            }],
+            "meta": { "earnings": "100000000", "pools": [] },
+        });
+
+        let usd = convert_history(
+            Dataset::Earnings,
+            Units::Usd,
+            &response,
+            &[Prices::rune(3.0)],
+        )
+        .unwrap();
+        assert_eq!(usd["intervals"][0]["earnings"], "3");
+        assert_eq!(usd["intervals"][0]["pools"][0]["earnings"], "1.5");
+        assert_eq!(
+            usd["intervals"][0]["pools"][0]["saverEarning"],
+            JsonValue::Null
+        );
+        assert_eq!(usd["intervals"][0]["pools"][0]["pool"], "BTC.BTC");
+        assert_eq!(usd["meta"]["earnings"], "3");
+    }
+}
@@ -0,0 +1,290 @@
+// Command line of the binary. `serve` (also what runs without a subcommand) is the api server
+// with its background jobs, the other commands do one thing against the configured database and
+// exit, non zero when that thing failed.
+use crate::services::storage::DbPool;
+use crate::{
+    config::{app::AppConfig, connect},
+    core::models::{
+        common::parse_date_range, dataset::Dataset, depth_history::DEPTH_POOL, job::JobStatus,
+    },
+    services::{
+        jobs::{
+            cron::{hourly_fetcher::HourlyFetcher, job_runner::JobRunner},
+            verify::check_intervals,
+        },
+        repository::{dataset, jobs},
+        supervisor::Shutdown,
+    },
+};
+use chrono::{DateTime, NaiveDate, Utc};
+use clap::{Parser, Subcommand};
+use std::process::ExitCode;
+use std::sync::Arc;
+
+#[derive(Debug, Parser)]
+#[command(version, about = "Thorchain history api backed by Midgard")]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Run the api server, the hourly fetcher and the job runner (the default)
+    Serve,
+    /// Fetch and store one range of a dataset, then exit
+    Backfill {
+        #[arg(long, value_parser = parse_dataset)]
+        dataset: Dataset,
+        /// Unix timestamp, YYYY-MM-DD or RFC 3339
+        #[arg(long, value_parser = parse_time)]
+        from: DateTime<Utc>,
+        /// Same formats as --from, defaults to now
+        #[arg(long, value_parser = parse_time)]
+        to: Option<DateTime<Utc>>,
+        /// Depth only, defaults to ETH.ETH
+        #[arg(long)]
+        pool: Option<String>,
+    },
+    /// Apply the migrations the database is missing
+    Migrate,
+    /// Fetch the latest hour of every dataset once
+    SyncOnce,
+    /// Look for gaps, overlaps and odd intervals in what is stored
+    Verify {
+        #[arg(long, value_parser = parse_dataset)]
+        dataset: Dataset,
+        /// Inclusive days, like the api's date_range: YYYY-MM-DD,YYYY-MM-DD
+        #[arg(long, value_parser = parse_range)]
+        range: (DateTime<Utc>, DateTime<Utc>),
+        /// Depth only, defaults to ETH.ETH
+        #[arg(long)]
+        pool: Option<String>,
+    },
+}
+
+fn parse_dataset(value: &str) -> Result<Dataset, String> {
+    Dataset::try_from(value.to_string())
+}
+
+fn parse_time(value: &str) -> Result<DateTime<Utc>, String> {
+    if let Ok(timestamp) = value.parse::<i64>() {
+        return DateTime::from_timestamp(timestamp, 0)
+            .ok_or_else(|| format!("{} is out of range", timestamp));
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        return Ok(date.and_time(Default::default()).and_utc());
+    }
+    DateTime::parse_from_rfc3339(value)
+        .map(|time| time.with_timezone(&Utc))
+        .map_err(|_| "expected a unix timestamp, YYYY-MM-DD or RFC 3339".to_string())
+}
+
+fn parse_range(value: &str) -> Result<(DateTime<Utc>, DateTime<Utc>), String> {
+    match parse_date_range(&Some(value.to_string())) {
+        Some((from, to)) if from < to => Ok((from, to)),
+        Some(_) => Err("the range ends before it starts".to_string()),
+        None => Err("expected YYYY-MM-DD,YYYY-MM-DD".to_string()),
+    }
+}
+
+// `pool` only means something for depth, which falls back to the pool the crons track
+fn pool_name(dataset: Dataset, pool: Option<String>) -> Result<Option<String>, String> {
+    match (dataset.has_pool(), pool) {
+        (true, pool) => Ok(Some(pool.unwrap_or_else(|| DEPTH_POOL.to_string()))),
+        (false, None) => Ok(None),
+        (false, Some(_)) => Err(format!("--pool is not supported for {}", dataset)),
+    }
+}
+
+// Runs every command but `serve`
+pub async fn run(command: Command, config: Arc<AppConfig>) -> ExitCode {
+    let pool = match connect::connect_database(&config.database).await {
+        Ok(pool) => pool,
+        Err(e) => {
+            eprintln!("Failed to connect to database: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    if !matches!(command, Command::Migrate) {
+        if let Err(e) = connect::prepare_schema(&pool, &config.database).await {
+            eprintln!("{}", e);
+            return ExitCode::FAILURE;
+        }
+    }
+
+    let result = match command {
+        Command::Serve => unreachable!("serve is handled by main"),
+        Command::Backfill {
+            dataset,
+            from,
+            to,
+            pool: pool_arg,
+        } => backfill(&pool, config, dataset, from, to, pool_arg).await,
+        Command::Migrate => migrate(&pool).await,
+        Command::SyncOnce => sync_once(pool, config).await,
+        Command::Verify {
+            dataset,
+            range,
+            pool: pool_arg,
+        } => verify(&pool, dataset, range, pool_arg).await,
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+// Goes through the jobs table like an admin api backfill so it shows up in `GET /admin/jobs`,
+// but runs in this process instead of waiting for the server's job runner
+async fn backfill(
+    pool: &DbPool,
+    config: Arc<AppConfig>,
+    dataset: Dataset,
+    from: DateTime<Utc>,
+    to: Option<DateTime<Utc>>,
+    pool_arg: Option<String>,
+) -> Result<(), anyhow::Error> {
+    let to = to.unwrap_or_else(Utc::now);
+    if from >= to {
+        anyhow::bail!("--from must be before --to");
+    }
+    let pool_name = pool_name(dataset, pool_arg).map_err(anyhow::Error::msg)?;
+
+    let id =
+        jobs::create_backfill(pool, dataset, pool_name.as_deref(), from, to, Some("cli")).await?;
+    let runner = JobRunner::new(pool.clone(), config);
+    let job = jobs::claim(pool, id, runner.owner())
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("Job {} was picked up by another runner", id))?;
+
+    runner.run(job, &Shutdown::never()).await;
+
+    let job = jobs::find(pool, id)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("Job {} disappeared", id))?;
+    println!(
+        "Job {} {}, {} intervals fetched",
+        job.id, job.status, job.intervals_fetched
+    );
+
+    match JobStatus::try_from(job.status) {
+        Ok(JobStatus::Succeeded) => Ok(()),
+        _ => Err(anyhow::anyhow!(job.error.unwrap_or_else(|| {
+            "The backfill did not finish".to_string()
+        }))),
+    }
+}
+
+async fn migrate(pool: &DbPool) -> Result<(), anyhow::Error> {
+    let applied = connect::migrate(pool).await?;
+    println!(
+        "Applied {} migrations, the database schema is up to date",
+        applied
+    );
+    Ok(())
+}
+
+async fn sync_once(pool: DbPool, config: Arc<AppConfig>) -> Result<(), anyhow::Error> {
+    let failed = HourlyFetcher::new(pool, config).fetch_cycle().await;
+    if failed > 0 {
+        anyhow::bail!("{} of 4 datasets failed to sync", failed);
+    }
+    println!("Stored the latest hour of every dataset");
+    Ok(())
+}
+
+async fn verify(
+    pool: &DbPool,
+    dataset: Dataset,
+    (from, to): (DateTime<Utc>, DateTime<Utc>),
+    pool_arg: Option<String>,
+) -> Result<(), anyhow::Error> {
+    let pool_name = pool_name(dataset, pool_arg).map_err(anyhow::Error::msg)?;
+    let intervals = dataset::interval_bounds(pool, dataset, pool_name.as_deref(), from, to).await?;
+    let problems = check_intervals(from, to.min(Utc::now()), &intervals);
+
+    for problem in &problems {
+        println!("{}", problem);
+    }
+    println!(
+        "Checked {} {} intervals between {} and {}, found {} problems",
+        intervals.len(),
+        dataset,
+        from,
+        to,
+        problems.len()
+    );
+
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(anyhow::anyhow!("{} is not consistent", dataset))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_subcommands() {
+        let cli = Cli::try_parse_from([
+            "catalog-crypto-api",
+            "backfill",
+            "--dataset",
+            "depth",
+            "--from",
+            "2024-01-01",
+            "--to",
+            "1704153600",
+            "--pool",
+            "ETH.ETH",
+        ])
+        .unwrap();
+        let Some(Command::Backfill {
+            dataset,
+            from,
+            to,
+            pool,
+        }) = cli.command
+        else {
+            panic!("expected backfill");
+        };
+        assert_eq!(dataset, Dataset::Depth);
+        assert_eq!(from.timestamp(), 1704067200);
+        assert_eq!(to.unwrap().timestamp(), 1704153600);
+        assert_eq!(pool.as_deref(), Some("ETH.ETH"));
+
+        assert!(Cli::try_parse_from(["catalog-crypto-api"])
+            .unwrap()
+            .command
+            .is_none());
+        assert!(
+            Cli::try_parse_from(["catalog-crypto-api", "verify", "--dataset", "swap"]).is_err()
+        );
+        assert!(Cli::try_parse_from([
+            "catalog-crypto-api",
+            "backfill",
+            "--dataset",
+            "x",
+            "--from",
+            "0"
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn pool_is_depth_only() {
+        assert_eq!(
+            pool_name(Dataset::Depth, None).unwrap().as_deref(),
+            Some(DEPTH_POOL)
+        );
+        assert!(pool_name(Dataset::Swap, Some("ETH.ETH".to_string())).is_err());
+    }
+}
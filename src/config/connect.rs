@@ -1,10 +1,171 @@
-use sqlx::{mysql::MySqlPoolOptions, MySqlPool};
-use std::time::Duration;
-
-pub async fn connect_database(url: &str) -> Result<MySqlPool, sqlx::Error> {
-    MySqlPoolOptions::new()
-        .max_connections(5)
-        .acquire_timeout(Duration::from_secs(3))
-        .connect(url)
-        .await
+use super::app::DatabaseConfig;
+use crate::services::storage::{self, DbPool, Storage};
+use sqlx::prelude::FromRow;
+use std::collections::HashMap;
+
+#[derive(Debug, thiserror::Error)]
+pub enum SchemaError {
+    #[error("migration {0} failed halfway, fix the schema by hand and delete its row from `_sqlx_migrations`")]
+    Dirty(i64),
+    #[error("the database has migrations {0:?} this build does not know about, it was migrated by a newer version")]
+    Newer(Vec<i64>),
+    #[error("migration {0} was changed after it was applied")]
+    Modified(i64),
+    #[error("failed to migrate the database: {0}")]
+    Migrate(#[from] sqlx::migrate::MigrateError),
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+}
+
+#[derive(Debug, Clone, FromRow)]
+pub struct AppliedMigration {
+    pub version: i64,
+    pub success: bool,
+    pub checksum: Vec<u8>,
+}
+
+// Connects to the backend `database.backend` (or the url scheme) selects
+pub async fn connect_database(config: &DatabaseConfig) -> Result<DbPool, sqlx::Error> {
+    storage::connect(config).await
+}
+
+async fn applied_migrations(pool: &DbPool) -> Result<Vec<AppliedMigration>, sqlx::Error> {
+    let query =
+        pool.query("SELECT version, success, checksum FROM _sqlx_migrations ORDER BY version");
+    let applied = pool.fetch_all::<AppliedMigration>(&query).await;
+
+    // A database that was never migrated has no migrations table
+    match applied {
+        Err(sqlx::Error::Database(e)) if pool.backend().is_missing_table(e.as_ref()) => {
+            Ok(Vec::new())
+        }
+        applied => applied,
+    }
+}
+
+// Compares what the database applied with the migrations of this build and returns the versions
+// still to apply, oldest first
+pub fn compare_migrations<'a>(
+    known: impl IntoIterator<Item = (i64, &'a [u8])>,
+    applied: &[AppliedMigration],
+) -> Result<Vec<i64>, SchemaError> {
+    if let Some(dirty) = applied.iter().find(|migration| !migration.success) {
+        return Err(SchemaError::Dirty(dirty.version));
+    }
+
+    let known: HashMap<i64, &[u8]> = known.into_iter().collect();
+    let unknown: Vec<i64> = applied
+        .iter()
+        .map(|migration| migration.version)
+        .filter(|version| !known.contains_key(version))
+        .collect();
+    if !unknown.is_empty() {
+        return Err(SchemaError::Newer(unknown));
+    }
+
+    if let Some(modified) = applied
+        .iter()
+        .find(|migration| known[&migration.version] != migration.checksum.as_slice())
+    {
+        return Err(SchemaError::Modified(modified.version));
+    }
+
+    let mut pending: Vec<i64> = known
+        .into_keys()
+        .filter(|version| {
+            !applied
+                .iter()
+                .any(|migration| migration.version == *version)
+        })
+        .collect();
+    pending.sort_unstable();
+    Ok(pending)
+}
+
+// Versions of the embedded migrations the database has not applied yet, or why the schema
+// can't be used by this build at all
+pub async fn check_schema(pool: &DbPool) -> Result<Vec<i64>, SchemaError> {
+    let applied = applied_migrations(pool).await?;
+    let known = pool
+        .migrator()
+        .iter()
+        .filter(|migration| !migration.migration_type.is_down_migration())
+        .map(|migration| (migration.version, migration.checksum.as_ref()));
+
+    compare_migrations(known, &applied)
+}
+
+// Applies the pending migrations, after making sure the schema is one this build understands
+pub async fn migrate(pool: &DbPool) -> Result<usize, SchemaError> {
+    let pending = check_schema(pool).await?;
+    if !pending.is_empty() {
+        pool.run_migrations().await?;
+    }
+    Ok(pending.len())
+}
+
+// Run before anything touches the database. With `run_migrations` off a schema that is merely
+// behind is left alone, `/readyz` reports it until someone runs `migrate`.
+pub async fn prepare_schema(pool: &DbPool, config: &DatabaseConfig) -> Result<(), SchemaError> {
+    if config.run_migrations {
+        let applied = migrate(pool).await?;
+        if applied > 0 {
+            tracing::info!("Applied {} migrations", applied);
+        }
+        return Ok(());
+    }
+
+    let pending = check_schema(pool).await?;
+    if !pending.is_empty() {
+        tracing::warn!(
+            "The database is missing migrations {:?}, run `migrate` to apply them",
+            pending
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn applied(version: i64, success: bool, checksum: &[u8]) -> AppliedMigration {
+        AppliedMigration {
+            version,
+            success,
+            checksum: checksum.to_vec(),
+        }
+    }
+
+    const KNOWN: [(i64, &[u8]); 3] = [(1, b"a"), (2, b"b"), (3, b"c")];
+
+    #[test]
+    fn pending_migrations_are_the_unapplied_ones() {
+        assert_eq!(compare_migrations(KNOWN, &[]).unwrap(), vec![1, 2, 3]);
+        assert_eq!(
+            compare_migrations(KNOWN, &[applied(1, true, b"a")]).unwrap(),
+            vec![2, 3]
+        );
+    }
+
+    #[test]
+    fn unusable_schemas_are_refused() {
+        let dirty = [applied(1, true, b"a"), applied(2, false, b"b")];
+        assert!(matches!(
+            compare_migrations(KNOWN, &dirty),
+            Err(SchemaError::Dirty(2))
+        ));
+
+        let newer = [applied(1, true, b"a"), applied(4, true, b"d")];
+        assert!(matches!(
+            compare_migrations(KNOWN, &newer),
+            Err(SchemaError::Newer(versions)) if versions == vec![4]
+        ));
+
+        let modified = [applied(1, true, b"x")];
+        assert!(matches!(
+            compare_migrations(KNOWN, &modified),
+            Err(SchemaError::Modified(1))
+        ));
+    }
 }
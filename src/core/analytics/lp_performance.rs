@@ -0,0 +1,180 @@
+// Performance of a hypothetical liquidity position between two stored depth intervals.
+// Amounts are RUNE/asset base units (1e8), values are in RUNE unless the name says USD.
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PoolState {
+    pub rune_depth: f64,
+    pub asset_depth: f64,
+    pub units: f64,
+    pub luvi: f64,
+    // Price of one asset unit in RUNE
+    pub asset_price: f64,
+    pub asset_price_usd: f64,
+}
+
+impl PoolState {
+    pub fn rune_price_usd(&self) -> f64 {
+        if self.asset_price <= 0.0 {
+            return 0.0;
+        }
+        self.asset_price_usd / self.asset_price
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LpPerformance {
+    pub pool_units: f64,
+    pub rune_withdrawable: f64,
+    pub asset_withdrawable: f64,
+    pub deposit_value: f64,
+    pub position_value: f64,
+    pub hold_value: f64,
+    pub position_value_usd: f64,
+    pub hold_value_usd: f64,
+    pub value_without_fees: f64,
+    pub fee_gain: f64,
+    // Growth of the liquidity unit value index over the window (fees and rewards)
+    pub luvi_growth: f64,
+    // Negative when the position is worth less than just holding, fees excluded
+    pub impermanent_loss: f64,
+    pub lp_vs_hold: f64,
+}
+
+// THORChain add liquidity formula:
+// units = P * (r*A + R*a) / (2*R*A) * (1 - |R*a - r*A| / ((2*r + R) * (a + A)))
+pub fn liquidity_units(pool: &PoolState, deposit_rune: f64, deposit_asset: f64) -> f64 {
+    let (r, a) = (deposit_rune, deposit_asset);
+    let (big_r, big_a, p) = (pool.rune_depth, pool.asset_depth, pool.units);
+
+    if big_r <= 0.0 || big_a <= 0.0 || p <= 0.0 {
+        return 0.0;
+    }
+
+    let slip_adjustment = 1.0 - (big_r * a - r * big_a).abs() / ((2.0 * r + big_r) * (a + big_a));
+    p * (r * big_a + big_r * a) / (2.0 * big_r * big_a) * slip_adjustment
+}
+
+pub fn calculate(
+    start: &PoolState,
+    end: &PoolState,
+    deposit_rune: f64,
+    deposit_asset: f64,
+) -> LpPerformance {
+    let pool_units = liquidity_units(start, deposit_rune, deposit_asset);
+
+    // The position is valued per pool unit, our own deposit is assumed not to move the pool
+    let (rune_withdrawable, asset_withdrawable) = if end.units > 0.0 {
+        (
+            pool_units * end.rune_depth / end.units,
+            pool_units * end.asset_depth / end.units,
+        )
+    } else {
+        (0.0, 0.0)
+    };
+
+    let deposit_value = deposit_rune + deposit_asset * start.asset_price;
+    let position_value = rune_withdrawable + asset_withdrawable * end.asset_price;
+    let hold_value = deposit_rune + deposit_asset * end.asset_price;
+
+    let luvi_growth = if start.luvi > 0.0 && end.luvi.is_finite() {
+        end.luvi / start.luvi - 1.0
+    } else {
+        0.0
+    };
+    let value_without_fees = position_value / (1.0 + luvi_growth);
+
+    let ratio_to_hold = |value: f64| {
+        if hold_value > 0.0 {
+            value / hold_value - 1.0
+        } else {
+            0.0
+        }
+    };
+
+    LpPerformance {
+        pool_units,
+        rune_withdrawable,
+        asset_withdrawable,
+        deposit_value,
+        position_value,
+        hold_value,
+        position_value_usd: position_value * end.rune_price_usd(),
+        hold_value_usd: hold_value * end.rune_price_usd(),
+        value_without_fees,
+        fee_gain: position_value - value_without_fees,
+        luvi_growth,
+        impermanent_loss: ratio_to_hold(value_without_fees),
+        lp_vs_hold: ratio_to_hold(position_value),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::analytics::testing::assert_close;
+
+    fn pool(rune_depth: f64, asset_depth: f64, units: f64) -> PoolState {
+        PoolState {
+            rune_depth,
+            asset_depth,
+            units,
+            luvi: (rune_depth * asset_depth).sqrt() / units,
+            asset_price: rune_depth / asset_depth,
+            asset_price_usd: rune_depth / asset_depth * 5.0,
+        }
+    }
+
+    #[test]
+    fn symmetric_deposit_gets_proportional_units() {
+        let start = pool(1_000_000.0, 1_000.0, 500_000.0);
+        // 1% of both sides
+        assert_close(liquidity_units(&start, 10_000.0, 10.0), 5_000.0);
+    }
+
+    #[test]
+    fn asymmetric_deposit_pays_slip() {
+        let start = pool(1_000_000.0, 1_000.0, 500_000.0);
+        let symmetric = liquidity_units(&start, 10_000.0, 10.0);
+        let rune_only = liquidity_units(&start, 20_000.0, 0.0);
+        assert!(rune_only < symmetric);
+    }
+
+    #[test]
+    fn unchanged_pool_has_no_loss() {
+        let start = pool(1_000_000.0, 1_000.0, 500_000.0);
+        let performance = calculate(&start, &start, 10_000.0, 10.0);
+
+        assert_close(performance.position_value, 20_000.0);
+        assert_close(performance.hold_value, 20_000.0);
+        assert_close(performance.impermanent_loss, 0.0);
+        assert_close(performance.fee_gain, 0.0);
+    }
+
+    #[test]
+    fn price_quadrupling_loses_twenty_percent() {
+        // Constant product pool where the asset price goes 4x, no fees so luvi stays the same
+        let start = pool(1_000_000.0, 1_000.0, 500_000.0);
+        let end = pool(2_000_000.0, 500.0, 500_000.0);
+        let performance = calculate(&start, &end, 10_000.0, 10.0);
+
+        assert_close(performance.luvi_growth, 0.0);
+        assert_close(performance.position_value, 40_000.0);
+        assert_close(performance.hold_value, 50_000.0);
+        assert_close(performance.impermanent_loss, -0.2);
+        assert_close(performance.position_value_usd, 40_000.0 * 5.0);
+    }
+
+    #[test]
+    fn fees_show_up_as_luvi_growth() {
+        // Same price, but 10% more depth for the same units
+        let start = pool(1_000_000.0, 1_000.0, 500_000.0);
+        let end = pool(1_100_000.0, 1_100.0, 500_000.0);
+        let performance = calculate(&start, &end, 10_000.0, 10.0);
+
+        assert_close(performance.luvi_growth, 0.1);
+        assert_close(performance.position_value, 22_000.0);
+        assert_close(performance.fee_gain, 2_000.0);
+        assert_close(performance.impermanent_loss, 0.0);
+        assert_close(performance.lp_vs_hold, 0.1);
+    }
+}
@@ -0,0 +1,15 @@
+pub mod lp_performance;
+pub mod pool_yield;
+pub mod rolling;
+pub mod timeseries;
+
+#[cfg(test)]
+mod testing {
+    // Equal up to float rounding, relative to the expected value once it is past 1
+    pub fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 1e-9 * expected.abs().max(1.0),
+            "expected {expected}, got {actual}"
+        );
+    }
+}
@@ -0,0 +1,145 @@
+// Yield maths for a single pool. Everything is expressed in RUNE base units (1e8) so depth and
+// earnings can be compared directly, the asset side is converted with the interval's `asset_price`.
+
+pub const SECONDS_PER_YEAR: f64 = 365.0 * 24.0 * 60.0 * 60.0;
+
+// Total pool value in RUNE: the rune side plus the asset side converted at the pool price
+pub fn pool_value_in_rune(rune_depth: u128, asset_depth: u128, asset_price: f64) -> f64 {
+    rune_depth as f64 + asset_depth as f64 * asset_price
+}
+
+// Return earned by liquidity providers over one period
+pub fn period_return(earnings: u128, pool_value: f64) -> f64 {
+    if pool_value <= 0.0 || !pool_value.is_finite() {
+        return 0.0;
+    }
+    earnings as f64 / pool_value
+}
+
+// Simple (non compounding) annualization of a return earned over `period_seconds`
+pub fn annualize_apr(period_return: f64, period_seconds: f64) -> f64 {
+    if period_seconds <= 0.0 {
+        return 0.0;
+    }
+    period_return * SECONDS_PER_YEAR / period_seconds
+}
+
+// APY when the APR is compounded once every `period_seconds`
+pub fn apr_to_apy(apr: f64, period_seconds: f64) -> f64 {
+    if period_seconds <= 0.0 {
+        return 0.0;
+    }
+    let periods_per_year = SECONDS_PER_YEAR / period_seconds;
+    (1.0 + apr / periods_per_year).powf(periods_per_year) - 1.0
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct YieldSample {
+    pub period_seconds: f64,
+    pub earnings: u128,
+    pub pool_value: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct YieldSummary {
+    pub total_earnings: u128,
+    pub average_pool_value: f64,
+    pub period_return: f64,
+    pub apr: f64,
+    pub apy: f64,
+}
+
+// Summary over a whole window: total earnings against the average pool value,
+// annualized over the covered duration and compounded at the sample frequency
+pub fn summarize(samples: &[YieldSample]) -> Option<YieldSummary> {
+    if samples.is_empty() {
+        return None;
+    }
+
+    let total_earnings: u128 = samples.iter().map(|sample| sample.earnings).sum();
+    let total_seconds: f64 = samples.iter().map(|sample| sample.period_seconds).sum();
+    let average_pool_value =
+        samples.iter().map(|sample| sample.pool_value).sum::<f64>() / samples.len() as f64;
+
+    let window_return = period_return(total_earnings, average_pool_value);
+    let apr = annualize_apr(window_return, total_seconds);
+    let apy = apr_to_apy(apr, total_seconds / samples.len() as f64);
+
+    Some(YieldSummary {
+        total_earnings,
+        average_pool_value,
+        period_return: window_return,
+        apr,
+        apy,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::analytics::testing::assert_close;
+
+    const RUNE: u128 = 100_000_000;
+    const HOUR: f64 = 3600.0;
+    const DAY: f64 = 24.0 * HOUR;
+
+    #[test]
+    fn pool_value_converts_asset_side_at_pool_price() {
+        // 1000 RUNE + 10 ETH at 100 RUNE/ETH
+        let value = pool_value_in_rune(1000 * RUNE, 10 * RUNE, 100.0);
+        assert_close(value, (2000 * RUNE) as f64);
+    }
+
+    #[test]
+    fn period_return_is_zero_for_empty_pool() {
+        assert_close(period_return(100, 0.0), 0.0);
+        assert_close(period_return(100, f64::NAN), 0.0);
+    }
+
+    #[test]
+    fn daily_return_annualizes_linearly() {
+        // 0.01% per day is 3.65% per year
+        let apr = annualize_apr(0.0001, DAY);
+        assert_close(apr, 0.0365);
+    }
+
+    #[test]
+    fn apy_compounds_at_period_frequency() {
+        let apy = apr_to_apy(0.0365, DAY);
+        assert_close(apy, 1.0001_f64.powf(365.0) - 1.0);
+        assert!(apy > 0.0365);
+    }
+
+    #[test]
+    fn apy_equals_apr_when_compounded_once_a_year() {
+        assert_close(apr_to_apy(0.1, SECONDS_PER_YEAR), 0.1);
+    }
+
+    #[test]
+    fn summary_uses_total_earnings_over_average_value() {
+        let samples = vec![
+            YieldSample {
+                period_seconds: HOUR,
+                earnings: 100,
+                pool_value: 1_000_000.0,
+            },
+            YieldSample {
+                period_seconds: HOUR,
+                earnings: 300,
+                pool_value: 3_000_000.0,
+            },
+        ];
+
+        let summary = summarize(&samples).unwrap();
+        assert_eq!(summary.total_earnings, 400);
+        assert_close(summary.average_pool_value, 2_000_000.0);
+        assert_close(summary.period_return, 0.0002);
+        assert_close(summary.apr, 0.0002 * SECONDS_PER_YEAR / (2.0 * HOUR));
+        assert_close(summary.apy, apr_to_apy(summary.apr, HOUR));
+    }
+
+    #[test]
+    fn summary_of_nothing_is_none() {
+        assert!(summarize(&[]).is_none());
+    }
+}
@@ -0,0 +1,139 @@
+// Rolling window statistics over an ordered series. Every function returns one value per input
+// point, `None` until the window has filled up (or when the statistic is undefined).
+
+fn rolling<F>(values: &[f64], window: usize, statistic: F) -> Vec<Option<f64>>
+where
+    F: Fn(&[f64]) -> f64,
+{
+    (0..values.len())
+        .map(|i| {
+            if window == 0 || i + 1 < window {
+                None
+            } else {
+                Some(statistic(&values[i + 1 - window..=i]))
+            }
+        })
+        .collect()
+}
+
+fn mean(values: &[f64]) -> f64 {
+    values.iter().sum::<f64>() / values.len() as f64
+}
+
+pub fn rolling_sum(values: &[f64], window: usize) -> Vec<Option<f64>> {
+    rolling(values, window, |values| values.iter().sum())
+}
+
+// Simple moving average
+pub fn sma(values: &[f64], window: usize) -> Vec<Option<f64>> {
+    rolling(values, window, mean)
+}
+
+// Population standard deviation of the window
+pub fn rolling_stddev(values: &[f64], window: usize) -> Vec<Option<f64>> {
+    rolling(values, window, |values| {
+        let mean = mean(values);
+        let variance = values
+            .iter()
+            .map(|value| (value - mean).powi(2))
+            .sum::<f64>()
+            / values.len() as f64;
+        variance.sqrt()
+    })
+}
+
+// Exponential moving average with `alpha = 2 / (window + 1)`, seeded with the SMA of the first window
+pub fn ema(values: &[f64], window: usize) -> Vec<Option<f64>> {
+    let mut result = vec![None; values.len()];
+    if window == 0 || values.len() < window {
+        return result;
+    }
+
+    let alpha = 2.0 / (window as f64 + 1.0);
+    let mut current = mean(&values[..window]);
+    result[window - 1] = Some(current);
+    for i in window..values.len() {
+        current = alpha * values[i] + (1.0 - alpha) * current;
+        result[i] = Some(current);
+    }
+    result
+}
+
+// Change relative to the value `periods` points earlier, as a fraction (0.1 is +10%)
+pub fn pct_change(values: &[f64], periods: usize) -> Vec<Option<f64>> {
+    (0..values.len())
+        .map(|i| {
+            if periods == 0 || i < periods {
+                return None;
+            }
+            let previous = values[i - periods];
+            if previous == 0.0 {
+                None
+            } else {
+                Some(values[i] / previous - 1.0)
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_series(actual: &[Option<f64>], expected: &[Option<f64>]) {
+        assert_eq!(actual.len(), expected.len());
+        for (actual, expected) in actual.iter().zip(expected) {
+            match (actual, expected) {
+                (Some(actual), Some(expected)) => assert!(
+                    (actual - expected).abs() < 1e-9,
+                    "expected {expected}, got {actual}"
+                ),
+                _ => assert_eq!(actual, expected),
+            }
+        }
+    }
+
+    #[test]
+    fn sum_and_sma_start_once_the_window_is_full() {
+        let values = [1.0, 2.0, 3.0, 4.0];
+        assert_series(
+            &rolling_sum(&values, 3),
+            &[None, None, Some(6.0), Some(9.0)],
+        );
+        assert_series(&sma(&values, 3), &[None, None, Some(2.0), Some(3.0)]);
+    }
+
+    #[test]
+    fn stddev_is_population_stddev() {
+        let values = [2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0];
+        assert_eq!(rolling_stddev(&values, 8)[7], Some(2.0));
+        assert_series(&rolling_stddev(&[3.0, 3.0], 2), &[None, Some(0.0)]);
+    }
+
+    #[test]
+    fn ema_is_seeded_with_sma() {
+        // alpha = 0.5 for a window of 3
+        let values = [1.0, 2.0, 3.0, 5.0, 7.0];
+        assert_series(
+            &ema(&values, 3),
+            &[None, None, Some(2.0), Some(3.5), Some(5.25)],
+        );
+    }
+
+    #[test]
+    fn pct_change_skips_zero_base() {
+        let values = [0.0, 10.0, 11.0, 5.5];
+        assert_series(
+            &pct_change(&values, 1),
+            &[None, None, Some(0.1), Some(-0.5)],
+        );
+    }
+
+    #[test]
+    fn window_larger_than_series_is_all_none() {
+        let values = [1.0, 2.0];
+        assert!(sma(&values, 5).iter().all(Option::is_none));
+        assert!(ema(&values, 5).iter().all(Option::is_none));
+        assert!(pct_change(&values, 0).iter().all(Option::is_none));
+    }
+}
@@ -0,0 +1,180 @@
+// Calendar bucketing so series from different tables can be lined up on the same timestamps
+use crate::core::models::{common::Interval, dataset::Aggregation};
+use chrono::{DateTime, Datelike, Days, Duration, Months, NaiveDate, Timelike, Utc};
+use std::collections::BTreeMap;
+
+// Start of the bucket `time` falls in. Weeks start on Monday, everything is UTC.
+pub fn bucket_start(time: DateTime<Utc>, interval: &Interval) -> DateTime<Utc> {
+    let date = time.date_naive();
+    let start =
+        match interval {
+            Interval::FiveMin => {
+                let timestamp = time.timestamp();
+                return DateTime::from_timestamp(timestamp - timestamp.rem_euclid(300), 0)
+                    .unwrap_or(time);
+            }
+            Interval::Hour => date.and_hms_opt(time.hour(), 0, 0),
+            Interval::Day => date.and_hms_opt(0, 0, 0),
+            Interval::Week => (date - Days::new(date.weekday().num_days_from_monday() as u64))
+                .and_hms_opt(0, 0, 0),
+            Interval::Month => NaiveDate::from_ymd_opt(date.year(), date.month(), 1)
+                .and_then(|date| date.and_hms_opt(0, 0, 0)),
+            Interval::Quarter => NaiveDate::from_ymd_opt(date.year(), date.month0() / 3 * 3 + 1, 1)
+                .and_then(|date| date.and_hms_opt(0, 0, 0)),
+            Interval::Year => NaiveDate::from_ymd_opt(date.year(), 1, 1)
+                .and_then(|date| date.and_hms_opt(0, 0, 0)),
+        };
+    start.map(|start| start.and_utc()).unwrap_or(time)
+}
+
+pub fn next_bucket(start: DateTime<Utc>, interval: &Interval) -> DateTime<Utc> {
+    let months = match interval {
+        Interval::FiveMin => return start + Duration::minutes(5),
+        Interval::Hour => return start + Duration::hours(1),
+        Interval::Day => return start + Duration::days(1),
+        Interval::Week => return start + Duration::weeks(1),
+        Interval::Month => 1,
+        Interval::Quarter => 3,
+        Interval::Year => 12,
+    };
+    start
+        .checked_add_months(Months::new(months))
+        .unwrap_or(DateTime::<Utc>::MAX_UTC)
+}
+
+// Every bucket start from the bucket containing `from` up to the one containing `to`
+// Start of every bucket of `[from, to]`, `None` as soon as there are more than `max` of them so
+// a huge range is refused without being laid out
+pub fn bucket_range(
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+    interval: &Interval,
+    max: usize,
+) -> Option<Vec<DateTime<Utc>>> {
+    let mut buckets = Vec::new();
+    let mut current = bucket_start(from, interval);
+    while current <= to {
+        if buckets.len() == max {
+            return None;
+        }
+        buckets.push(current);
+        current = next_bucket(current, interval);
+    }
+    Some(buckets)
+}
+
+// Folds points (oldest first) into their buckets
+pub fn aggregate(
+    points: &[(DateTime<Utc>, f64)],
+    interval: &Interval,
+    aggregation: Aggregation,
+) -> BTreeMap<DateTime<Utc>, f64> {
+    let mut buckets: BTreeMap<DateTime<Utc>, (f64, usize, f64)> = BTreeMap::new();
+    for (time, value) in points {
+        let entry = buckets
+            .entry(bucket_start(*time, interval))
+            .or_insert((0.0, 0, 0.0));
+        entry.0 += value;
+        entry.1 += 1;
+        entry.2 = *value;
+    }
+
+    buckets
+        .into_iter()
+        .map(|(start, (sum, count, last))| {
+            let value = match aggregation {
+                Aggregation::Sum => sum,
+                Aggregation::Average => sum / count as f64,
+                Aggregation::Last => last,
+            };
+            (start, value)
+        })
+        .collect()
+}
+
+// One value per bucket, `None` where the series has no data
+pub fn align(buckets: &[DateTime<Utc>], values: &BTreeMap<DateTime<Utc>, f64>) -> Vec<Option<f64>> {
+    buckets
+        .iter()
+        .map(|bucket| values.get(bucket).copied())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(year, month, day, hour, minute, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn buckets_start_on_calendar_boundaries() {
+        // Thursday
+        let time = at(2024, 8, 15, 13, 47);
+        assert_eq!(
+            bucket_start(time, &Interval::FiveMin),
+            at(2024, 8, 15, 13, 45)
+        );
+        assert_eq!(bucket_start(time, &Interval::Hour), at(2024, 8, 15, 13, 0));
+        assert_eq!(bucket_start(time, &Interval::Day), at(2024, 8, 15, 0, 0));
+        assert_eq!(bucket_start(time, &Interval::Week), at(2024, 8, 12, 0, 0));
+        assert_eq!(bucket_start(time, &Interval::Month), at(2024, 8, 1, 0, 0));
+        assert_eq!(bucket_start(time, &Interval::Quarter), at(2024, 7, 1, 0, 0));
+        assert_eq!(bucket_start(time, &Interval::Year), at(2024, 1, 1, 0, 0));
+    }
+
+    #[test]
+    fn month_buckets_follow_the_calendar() {
+        let (from, to) = (at(2024, 1, 20, 0, 0), at(2024, 3, 1, 0, 0));
+        assert_eq!(
+            bucket_range(from, to, &Interval::Month, 3),
+            Some(vec![
+                at(2024, 1, 1, 0, 0),
+                at(2024, 2, 1, 0, 0),
+                at(2024, 3, 1, 0, 0)
+            ])
+        );
+        assert_eq!(bucket_range(from, to, &Interval::Month, 2), None);
+    }
+
+    #[test]
+    fn aggregation_per_bucket() {
+        let points = vec![
+            (at(2024, 8, 15, 1, 0), 1.0),
+            (at(2024, 8, 15, 2, 0), 3.0),
+            (at(2024, 8, 17, 2, 0), 10.0),
+        ];
+        let day = at(2024, 8, 15, 0, 0);
+
+        assert_eq!(
+            aggregate(&points, &Interval::Day, Aggregation::Sum)[&day],
+            4.0
+        );
+        assert_eq!(
+            aggregate(&points, &Interval::Day, Aggregation::Average)[&day],
+            2.0
+        );
+        assert_eq!(
+            aggregate(&points, &Interval::Day, Aggregation::Last)[&day],
+            3.0
+        );
+    }
+
+    #[test]
+    fn missing_buckets_are_none() {
+        let points = vec![(at(2024, 8, 15, 1, 0), 1.0), (at(2024, 8, 17, 2, 0), 10.0)];
+        let values = aggregate(&points, &Interval::Day, Aggregation::Sum);
+        let buckets = bucket_range(
+            at(2024, 8, 15, 0, 0),
+            at(2024, 8, 17, 0, 0),
+            &Interval::Day,
+            10,
+        )
+        .unwrap();
+
+        assert_eq!(align(&buckets, &values), vec![Some(1.0), None, Some(10.0)]);
+    }
+}
@@ -0,0 +1,131 @@
+use super::serialization::{nanos_timestamp_serialization, u64_serialization};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct Coin {
+    #[serde(with = "u64_serialization")]
+    pub amount: u64,
+    pub asset: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct ActionTransaction {
+    pub address: String,
+    pub coins: Vec<Coin>,
+    #[serde(rename = "txID")]
+    pub tx_id: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct Action {
+    #[serde(with = "nanos_timestamp_serialization")]
+    pub date: DateTime<Utc>,
+    #[serde(with = "u64_serialization")]
+    pub height: u64,
+    #[serde(rename = "in")]
+    pub in_txs: Vec<ActionTransaction>,
+    #[serde(rename = "out")]
+    pub out_txs: Vec<ActionTransaction>,
+    pub pools: Vec<String>,
+    pub status: String,
+    #[serde(rename = "type")]
+    pub action_type: String,
+    // Shape depends on the action type (swap, addLiquidity, withdraw, refund...) so we keep it raw
+    #[serde(default)]
+    #[schema(value_type = Object)]
+    pub metadata: serde_json::Value,
+}
+
+impl Action {
+    // The inbound tx id identifies the action, outbound is only a fallback for actions without one
+    pub fn tx_id(&self) -> Option<&str> {
+        self.in_txs
+            .iter()
+            .chain(self.out_txs.iter())
+            .map(|tx| tx.tx_id.as_str())
+            .find(|tx_id| !tx_id.is_empty())
+    }
+
+    // Every in and out tx id, what a `txid` search matches
+    pub fn tx_ids(&self) -> Vec<&str> {
+        let mut tx_ids: Vec<&str> = Vec::new();
+        for tx in self.in_txs.iter().chain(self.out_txs.iter()) {
+            if !tx.tx_id.is_empty() && !tx_ids.contains(&tx.tx_id.as_str()) {
+                tx_ids.push(tx.tx_id.as_str());
+            }
+        }
+        tx_ids
+    }
+
+    pub fn addresses(&self) -> Vec<&str> {
+        let mut addresses: Vec<&str> = Vec::new();
+        for tx in self.in_txs.iter().chain(self.out_txs.iter()) {
+            if !tx.address.is_empty() && !addresses.contains(&tx.address.as_str()) {
+                addresses.push(tx.address.as_str());
+            }
+        }
+        addresses
+    }
+
+    // Swap slip in basis points, only present on swap actions
+    pub fn swap_slip(&self) -> Option<u64> {
+        self.swap_metadata_u64("swapSlip")
+    }
+
+    pub fn liquidity_fee(&self) -> Option<u64> {
+        self.swap_metadata_u64("liquidityFee")
+    }
+
+    fn swap_metadata_u64(&self, field: &str) -> Option<u64> {
+        self.metadata
+            .get("swap")?
+            .get(field)?
+            .as_str()?
+            .parse::<u64>()
+            .ok()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Default, ToSchema)]
+pub struct ActionsMeta {
+    #[serde(rename = "nextPageToken", default)]
+    pub next_page_token: Option<String>,
+    #[serde(rename = "prevPageToken", default)]
+    pub prev_page_token: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ActionsResponse {
+    pub actions: Vec<Action>,
+    #[serde(default)]
+    pub meta: ActionsMeta,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ActionsParams {
+    pub limit: Option<u32>,
+    pub action_type: Option<String>,
+    pub next_page_token: Option<String>,
+    pub from: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ActionsSearchResponse {
+    pub actions: Vec<Action>,
+    pub count: usize,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ActionsQueryParams {
+    pub address: Option<String>,
+    pub txid: Option<String>,
+    pub pool: Option<String>,
+    #[serde(rename = "type")]
+    pub action_type: Option<String>,
+    pub date_range: Option<String>,
+    pub order: Option<String>,
+    pub page: Option<u32>,
+    pub limit: Option<u32>,
+}
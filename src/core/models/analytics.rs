@@ -0,0 +1,229 @@
+use super::common::Interval;
+use super::serialization::{
+    float_serialization, option_float_serialization, option_float_vec_serialization,
+    timestamp_serialization, u128_serialization, u64_serialization,
+};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, ToSchema)]
+pub enum YieldWindow {
+    #[serde(rename = "7d")]
+    Week,
+    #[serde(rename = "30d")]
+    Month,
+    #[serde(rename = "90d")]
+    Quarter,
+}
+
+impl YieldWindow {
+    pub fn duration(&self) -> Duration {
+        match self {
+            YieldWindow::Week => Duration::days(7),
+            YieldWindow::Month => Duration::days(30),
+            YieldWindow::Quarter => Duration::days(90),
+        }
+    }
+}
+
+impl std::fmt::Display for YieldWindow {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let window_str = match self {
+            YieldWindow::Week => "7d",
+            YieldWindow::Month => "30d",
+            YieldWindow::Quarter => "90d",
+        };
+        write!(f, "{}", window_str)
+    }
+}
+
+impl TryFrom<String> for YieldWindow {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        match s.to_lowercase().as_str() {
+            "7d" => Ok(YieldWindow::Week),
+            "30d" => Ok(YieldWindow::Month),
+            "90d" => Ok(YieldWindow::Quarter),
+            _ => Err("Invalid window, expected one of 7d, 30d, 90d".to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct PoolYieldInterval {
+    #[serde(rename = "startTime", with = "timestamp_serialization")]
+    pub start_time: DateTime<Utc>,
+    #[serde(rename = "endTime", with = "timestamp_serialization")]
+    pub end_time: DateTime<Utc>,
+    #[serde(rename = "earnings", with = "u128_serialization")]
+    pub earnings: u128,
+    #[serde(rename = "poolValueRune", with = "float_serialization")]
+    pub pool_value_rune: f64,
+    #[serde(rename = "apr", with = "float_serialization")]
+    pub apr: f64,
+    #[serde(rename = "apy", with = "float_serialization")]
+    pub apy: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct PoolYieldSummary {
+    #[serde(rename = "totalEarnings", with = "u128_serialization")]
+    pub total_earnings: u128,
+    #[serde(rename = "averagePoolValueRune", with = "float_serialization")]
+    pub average_pool_value_rune: f64,
+    #[serde(rename = "periodReturn", with = "float_serialization")]
+    pub period_return: f64,
+    #[serde(rename = "apr", with = "float_serialization")]
+    pub apr: f64,
+    #[serde(rename = "apy", with = "float_serialization")]
+    pub apy: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct PoolYieldResponse {
+    pub pool: String,
+    pub window: YieldWindow,
+    #[serde(rename = "startTime", with = "timestamp_serialization")]
+    pub start_time: DateTime<Utc>,
+    #[serde(rename = "endTime", with = "timestamp_serialization")]
+    pub end_time: DateTime<Utc>,
+    pub summary: PoolYieldSummary,
+    pub intervals: Vec<PoolYieldInterval>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct PoolYieldQueryParams {
+    pub window: Option<String>,
+    pub units: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct LpPerformanceResponse {
+    pub pool: String,
+    #[serde(rename = "startTime", with = "timestamp_serialization")]
+    pub start_time: DateTime<Utc>,
+    #[serde(rename = "endTime", with = "timestamp_serialization")]
+    pub end_time: DateTime<Utc>,
+    #[serde(rename = "depositRune", with = "u64_serialization")]
+    pub deposit_rune: u64,
+    #[serde(rename = "depositAsset", with = "u64_serialization")]
+    pub deposit_asset: u64,
+    #[serde(rename = "poolUnits", with = "float_serialization")]
+    pub pool_units: f64,
+    #[serde(rename = "runeWithdrawable", with = "float_serialization")]
+    pub rune_withdrawable: f64,
+    #[serde(rename = "assetWithdrawable", with = "float_serialization")]
+    pub asset_withdrawable: f64,
+    #[serde(rename = "depositValueRune", with = "float_serialization")]
+    pub deposit_value_rune: f64,
+    #[serde(rename = "positionValueRune", with = "float_serialization")]
+    pub position_value_rune: f64,
+    #[serde(rename = "holdValueRune", with = "float_serialization")]
+    pub hold_value_rune: f64,
+    #[serde(rename = "positionValueUSD", with = "float_serialization")]
+    pub position_value_usd: f64,
+    #[serde(rename = "holdValueUSD", with = "float_serialization")]
+    pub hold_value_usd: f64,
+    #[serde(rename = "impermanentLoss", with = "float_serialization")]
+    pub impermanent_loss: f64,
+    #[serde(rename = "feeGainRune", with = "float_serialization")]
+    pub fee_gain_rune: f64,
+    #[serde(rename = "luviGrowth", with = "float_serialization")]
+    pub luvi_growth: f64,
+    #[serde(rename = "lpVsHold", with = "float_serialization")]
+    pub lp_vs_hold: f64,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct LpPerformanceQueryParams {
+    pub from: Option<i64>,
+    pub to: Option<i64>,
+    pub deposit_rune: Option<u64>,
+    pub deposit_asset: Option<u64>,
+    pub units: Option<String>,
+}
+
+pub const DEFAULT_ROLLING_WINDOW: usize = 7;
+pub const MAX_ROLLING_WINDOW: usize = 400;
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct RollingInterval {
+    #[serde(rename = "startTime", with = "timestamp_serialization")]
+    pub start_time: DateTime<Utc>,
+    #[serde(rename = "endTime", with = "timestamp_serialization")]
+    pub end_time: DateTime<Utc>,
+    #[serde(rename = "value", with = "float_serialization")]
+    pub value: f64,
+    #[serde(rename = "sma", with = "option_float_serialization")]
+    pub sma: Option<f64>,
+    #[serde(rename = "ema", with = "option_float_serialization")]
+    pub ema: Option<f64>,
+    #[serde(rename = "rollingSum", with = "option_float_serialization")]
+    pub rolling_sum: Option<f64>,
+    #[serde(rename = "rollingStddev", with = "option_float_serialization")]
+    pub rolling_stddev: Option<f64>,
+    #[serde(rename = "pctChange", with = "option_float_serialization")]
+    pub pct_change: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct RollingResponse {
+    pub dataset: String,
+    pub column: String,
+    pub window: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pool: Option<String>,
+    pub intervals: Vec<RollingInterval>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct RollingQueryParams {
+    pub column: Option<String>,
+    pub window: Option<usize>,
+    pub date_range: Option<String>,
+    pub pool: Option<String>,
+    pub units: Option<String>,
+}
+
+pub const MAX_TIMESERIES_SERIES: usize = 10;
+pub const MAX_TIMESERIES_BUCKETS: usize = 5000;
+
+// Intervals a rolling or timeseries request reads per column, a bit over eleven years of hourly data
+pub const MAX_SERIES_ROWS: usize = 100_000;
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct TimeseriesBucket {
+    #[serde(rename = "startTime", with = "timestamp_serialization")]
+    pub start_time: DateTime<Utc>,
+    #[serde(rename = "endTime", with = "timestamp_serialization")]
+    pub end_time: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct TimeseriesSeries {
+    // `dataset.column` as requested
+    pub name: String,
+    // One value per bucket, `null` where the dataset has no data
+    #[serde(with = "option_float_vec_serialization")]
+    pub values: Vec<Option<f64>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct TimeseriesResponse {
+    pub interval: Interval,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pool: Option<String>,
+    pub buckets: Vec<TimeseriesBucket>,
+    pub series: Vec<TimeseriesSeries>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct TimeseriesQueryParams {
+    pub series: Option<String>,
+    pub interval: Option<String>,
+    pub date_range: Option<String>,
+    pub pool: Option<String>,
+    pub units: Option<String>,
+}
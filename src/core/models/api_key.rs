@@ -0,0 +1,31 @@
+use crate::services::storage::Unsigned;
+use sha2::{Digest, Sha256};
+use sqlx::prelude::FromRow;
+
+#[derive(Debug, Clone, FromRow)]
+pub struct ApiKey {
+    pub id: i64,
+    pub name: String,
+    #[sqlx(try_from = "Unsigned")]
+    pub requests_per_minute: u32,
+    pub enabled: bool,
+    pub is_admin: bool,
+}
+
+// Hex SHA-256 of a key as sent by clients, matches MySQL's `SHA2(key, 256)`
+pub fn hash_key(key: &str) -> String {
+    format!("{:x}", Sha256::digest(key.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hash_matches_sha2_256_hex() {
+        assert_eq!(
+            hash_key("abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+}
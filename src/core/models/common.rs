@@ -3,8 +3,12 @@ use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 
 use super::{
-    depth_history::DepthHistoryQueryParams, earnings_history::EarningsHistoryQueryParams,
-    runepool_units_history::RunepoolUnitsHistoryQueryParams, swap_history::SwapHistoryQueryParams,
+    actions::ActionsQueryParams,
+    analytics::{RollingQueryParams, TimeseriesQueryParams},
+    depth_history::DepthHistoryQueryParams,
+    earnings_history::EarningsHistoryQueryParams,
+    runepool_units_history::RunepoolUnitsHistoryQueryParams,
+    swap_history::SwapHistoryQueryParams,
 };
 
 pub const DEFAULT_PAGE_SIZE: u32 = 30;
@@ -43,7 +47,7 @@ impl TryFrom<String> for Interval {
 
     fn try_from(s: String) -> Result<Self, Self::Error> {
         match s.to_lowercase().as_str() {
-            "five_min" => Ok(Interval::FiveMin),
+            "5min" | "five_min" => Ok(Interval::FiveMin),
             "hour" => Ok(Interval::Hour),
             "day" => Ok(Interval::Day),
             "week" => Ok(Interval::Week),
@@ -158,7 +162,25 @@ impl RunepoolUnitsHistoryQueryParams {
     }
 }
 
-fn parse_date_range(date_range: &Option<String>) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+impl ActionsQueryParams {
+    pub fn parse_date_range(&self) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+        parse_date_range(&self.date_range)
+    }
+}
+
+impl RollingQueryParams {
+    pub fn parse_date_range(&self) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+        parse_date_range(&self.date_range)
+    }
+}
+
+impl TimeseriesQueryParams {
+    pub fn parse_date_range(&self) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+        parse_date_range(&self.date_range)
+    }
+}
+
+pub fn parse_date_range(date_range: &Option<String>) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
     date_range.as_ref().and_then(|range| {
         let parts: Vec<&str> = range.split(',').collect();
         if parts.len() == 2 {
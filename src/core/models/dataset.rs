@@ -0,0 +1,230 @@
+use super::units::Denomination;
+
+// The four history tables, so handlers that work on "any dataset" can validate user supplied
+// dataset and column names before they end up in SQL
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Dataset {
+    Depth,
+    Earnings,
+    Swap,
+    Runepool,
+}
+
+// How several intervals of a column are combined into a larger bucket: flows (volumes, fees,
+// earnings) add up, levels (depths, prices, units) keep the latest value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Aggregation {
+    Sum,
+    Average,
+    Last,
+}
+
+// Stored type of a column: a BIGINT/INT UNSIGNED counter, an amount in base units (DECIMAL(38,0))
+// or a DOUBLE
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnKind {
+    UInt,
+    Amount,
+    Float,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Column {
+    pub name: &'static str,
+    pub kind: ColumnKind,
+}
+
+const fn column(name: &'static str, kind: ColumnKind) -> Column {
+    Column { name, kind }
+}
+
+const DEPTH_NUMERIC_COLUMNS: &[Column] = &[
+    column("asset_depth", ColumnKind::Amount),
+    column("asset_price", ColumnKind::Float),
+    column("asset_price_usd", ColumnKind::Float),
+    column("liquidity_units", ColumnKind::Amount),
+    column("luvi", ColumnKind::Float),
+    column("members_count", ColumnKind::UInt),
+    column("rune_depth", ColumnKind::Amount),
+    column("synth_supply", ColumnKind::Amount),
+    column("synth_units", ColumnKind::Amount),
+    column("units", ColumnKind::Amount),
+];
+
+const EARNINGS_NUMERIC_COLUMNS: &[Column] = &[
+    column("avg_node_count", ColumnKind::Float),
+    column("block_rewards", ColumnKind::Amount),
+    column("bonding_earnings", ColumnKind::Amount),
+    column("earnings", ColumnKind::Amount),
+    column("liquidity_earnings", ColumnKind::Amount),
+    column("liquidity_fees", ColumnKind::Amount),
+    column("rune_price_usd", ColumnKind::Float),
+];
+
+const SWAP_NUMERIC_COLUMNS: &[Column] = &[
+    column("average_slip", ColumnKind::Float),
+    column("from_trade_average_slip", ColumnKind::Float),
+    column("from_trade_count", ColumnKind::UInt),
+    column("from_trade_fees", ColumnKind::Amount),
+    column("from_trade_volume", ColumnKind::Amount),
+    column("from_trade_volume_usd", ColumnKind::Amount),
+    column("rune_price_usd", ColumnKind::Float),
+    column("synth_mint_average_slip", ColumnKind::Float),
+    column("synth_mint_count", ColumnKind::UInt),
+    column("synth_mint_fees", ColumnKind::Amount),
+    column("synth_mint_volume", ColumnKind::Amount),
+    column("synth_mint_volume_usd", ColumnKind::Amount),
+    column("synth_redeem_average_slip", ColumnKind::Float),
+    column("synth_redeem_count", ColumnKind::UInt),
+    column("synth_redeem_fees", ColumnKind::Amount),
+    column("synth_redeem_volume", ColumnKind::Amount),
+    column("synth_redeem_volume_usd", ColumnKind::Amount),
+    column("to_asset_average_slip", ColumnKind::Float),
+    column("to_asset_count", ColumnKind::UInt),
+    column("to_asset_fees", ColumnKind::Amount),
+    column("to_asset_volume", ColumnKind::Amount),
+    column("to_asset_volume_usd", ColumnKind::Amount),
+    column("to_rune_average_slip", ColumnKind::Float),
+    column("to_rune_count", ColumnKind::UInt),
+    column("to_rune_fees", ColumnKind::Amount),
+    column("to_rune_volume", ColumnKind::Amount),
+    column("to_rune_volume_usd", ColumnKind::Amount),
+    column("to_trade_average_slip", ColumnKind::Float),
+    column("to_trade_count", ColumnKind::UInt),
+    column("to_trade_fees", ColumnKind::Amount),
+    column("to_trade_volume", ColumnKind::Amount),
+    column("to_trade_volume_usd", ColumnKind::Amount),
+    column("total_count", ColumnKind::UInt),
+    column("total_fees", ColumnKind::Amount),
+    column("total_volume", ColumnKind::Amount),
+    column("total_volume_usd", ColumnKind::Amount),
+];
+
+const RUNEPOOL_NUMERIC_COLUMNS: &[Column] = &[
+    column("count", ColumnKind::UInt),
+    column("units", ColumnKind::Amount),
+];
+
+impl Dataset {
+    pub const ALL: [Dataset; 4] = [
+        Dataset::Depth,
+        Dataset::Earnings,
+        Dataset::Swap,
+        Dataset::Runepool,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Dataset::Depth => "depth",
+            Dataset::Earnings => "earnings",
+            Dataset::Swap => "swap",
+            Dataset::Runepool => "runepool",
+        }
+    }
+
+    pub fn table(&self) -> &'static str {
+        match self {
+            Dataset::Depth => "depth_intervals",
+            Dataset::Earnings => "earning_intervals",
+            Dataset::Swap => "swap_intervals",
+            Dataset::Runepool => "runepool_unit_intervals",
+        }
+    }
+
+    pub fn numeric_columns(&self) -> &'static [Column] {
+        match self {
+            Dataset::Depth => DEPTH_NUMERIC_COLUMNS,
+            Dataset::Earnings => EARNINGS_NUMERIC_COLUMNS,
+            Dataset::Swap => SWAP_NUMERIC_COLUMNS,
+            Dataset::Runepool => RUNEPOOL_NUMERIC_COLUMNS,
+        }
+    }
+
+    // Returns the column name as a `'static` str so it is safe to push into a query
+    pub fn numeric_column(&self, column: &str) -> Result<&'static str, String> {
+        self.numeric_columns()
+            .iter()
+            .find(|candidate| candidate.name == column)
+            .map(|candidate| candidate.name)
+            .ok_or_else(|| {
+                let names: Vec<&str> = self
+                    .numeric_columns()
+                    .iter()
+                    .map(|candidate| candidate.name)
+                    .collect();
+                format!(
+                    "Invalid column `{}` for {}, expected one of {}",
+                    column,
+                    self,
+                    names.join(", ")
+                )
+            })
+    }
+
+    pub fn aggregation(&self, column: &str) -> Aggregation {
+        match self {
+            Dataset::Depth | Dataset::Runepool => Aggregation::Last,
+            Dataset::Earnings | Dataset::Swap => match column {
+                "rune_price_usd" => Aggregation::Last,
+                "avg_node_count" => Aggregation::Average,
+                column if column.ends_with("average_slip") => Aggregation::Average,
+                _ => Aggregation::Sum,
+            },
+        }
+    }
+
+    // What an amount column is counted in. Pool and LP units are shares rather than amounts and
+    // have none, like counts and prices.
+    pub fn denomination(&self, column: &str) -> Option<Denomination> {
+        match (self, column) {
+            (Dataset::Depth, "asset_depth" | "synth_supply") => Some(Denomination::Asset),
+            (Dataset::Depth, "rune_depth") => Some(Denomination::Rune),
+            (
+                Dataset::Earnings,
+                "block_rewards" | "bonding_earnings" | "earnings" | "liquidity_earnings"
+                | "liquidity_fees",
+            ) => Some(Denomination::Rune),
+            (Dataset::Swap, column) if column.ends_with("_volume_usd") => Some(Denomination::Usd),
+            (Dataset::Swap, column) if column.ends_with("_volume") || column.ends_with("_fees") => {
+                Some(Denomination::Rune)
+            }
+            _ => None,
+        }
+    }
+
+    // Columns stored as JSON documents
+    pub fn json_columns(&self) -> &'static [&'static str] {
+        match self {
+            Dataset::Earnings => &["pools"],
+            _ => &[],
+        }
+    }
+
+    // Only depth is stored per pool, the other tables are network wide
+    pub fn has_pool(&self) -> bool {
+        matches!(self, Dataset::Depth)
+    }
+}
+
+impl std::fmt::Display for Dataset {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl TryFrom<String> for Dataset {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        match s.to_lowercase().as_str() {
+            "depth" => Ok(Dataset::Depth),
+            "earnings" => Ok(Dataset::Earnings),
+            "swap" => Ok(Dataset::Swap),
+            "runepool" => Ok(Dataset::Runepool),
+            _ => {
+                Err("Invalid dataset, expected one of depth, earnings, swap, runepool".to_string())
+            }
+        }
+    }
+}
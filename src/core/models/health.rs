@@ -0,0 +1,68 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct HealthResponse {
+    pub status: &'static str,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ReadinessResponse {
+    pub ready: bool,
+    pub database: bool,
+    #[serde(rename = "pendingMigrations")]
+    pub pending_migrations: Vec<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct DatasetFreshness {
+    pub dataset: String,
+    // Unix timestamp, `None` when nothing was stored yet
+    #[serde(rename = "latestEndTime")]
+    pub latest_end_time: Option<i64>,
+    #[serde(rename = "lagSeconds")]
+    pub lag_seconds: Option<i64>,
+    pub stale: bool,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct FreshnessResponse {
+    pub fresh: bool,
+    #[serde(rename = "maxLagSeconds")]
+    pub max_lag_seconds: i64,
+    pub datasets: Vec<DatasetFreshness>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct FreshnessQueryParams {
+    pub max_lag: Option<i64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum TaskStatus {
+    Running,
+    // Failed and waiting out its backoff
+    Restarting,
+    // Stopped by a shutdown
+    Stopped,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct TaskState {
+    pub name: String,
+    pub status: TaskStatus,
+    pub restarts: u32,
+    #[serde(rename = "lastError", skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+    // Unix timestamp of the last status change
+    pub since: i64,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TasksResponse {
+    pub healthy: bool,
+    pub tasks: Vec<TaskState>,
+}
@@ -0,0 +1,86 @@
+use crate::services::storage::Unsigned;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::prelude::FromRow;
+use utoipa::ToSchema;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Succeeded,
+    Failed,
+    Cancelled,
+}
+
+impl JobStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JobStatus::Queued => "queued",
+            JobStatus::Running => "running",
+            JobStatus::Succeeded => "succeeded",
+            JobStatus::Failed => "failed",
+            JobStatus::Cancelled => "cancelled",
+        }
+    }
+}
+
+impl TryFrom<String> for JobStatus {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        match s.to_lowercase().as_str() {
+            "queued" => Ok(JobStatus::Queued),
+            "running" => Ok(JobStatus::Running),
+            "succeeded" => Ok(JobStatus::Succeeded),
+            "failed" => Ok(JobStatus::Failed),
+            "cancelled" => Ok(JobStatus::Cancelled),
+            _ => Err(
+                "Invalid status, expected one of queued, running, succeeded, failed, cancelled"
+                    .to_string(),
+            ),
+        }
+    }
+}
+
+// A backfill of `[from_time, to_time)` for one dataset, `cursor_time` is how far it got
+#[derive(Debug, Clone, Serialize, FromRow, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Job {
+    pub id: i64,
+    pub dataset: String,
+    pub pool: Option<String>,
+    pub from_time: DateTime<Utc>,
+    pub to_time: DateTime<Utc>,
+    pub status: String,
+    pub cursor_time: Option<DateTime<Utc>>,
+    #[sqlx(try_from = "Unsigned")]
+    pub intervals_fetched: u32,
+    pub error: Option<String>,
+    pub requested_by: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub finished_at: Option<DateTime<Utc>>,
+    // Runner working on the job, and when it last reported progress
+    pub owner: Option<String>,
+    pub heartbeat_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct JobsResponse {
+    pub jobs: Vec<Job>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct BackfillQueryParams {
+    // Unix timestamps, `to` defaults to now
+    pub from: Option<i64>,
+    pub to: Option<i64>,
+    pub pool: Option<String>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct JobsQueryParams {
+    pub status: Option<String>,
+    pub limit: Option<u32>,
+}
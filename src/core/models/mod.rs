@@ -1,5 +1,14 @@
+pub mod actions;
+pub mod analytics;
+pub mod api_key;
 pub mod common;
+pub mod dataset;
 pub mod depth_history;
 pub mod earnings_history;
+pub mod export;
+pub mod health;
+pub mod job;
 pub mod runepool_units_history;
+pub mod serialization;
 pub mod swap_history;
+pub mod units;
@@ -1,68 +1,26 @@
-use chrono::{DateTime, TimeZone, Utc};
+use crate::services::storage::{Amount, Unsigned};
+use chrono::{DateTime, Utc};
 use prkorm::Table;
 use serde::{Deserialize, Serialize};
 use sqlx::prelude::FromRow;
 use utoipa::ToSchema;
 
 use super::common::Interval;
-
-mod timestamp_serialization {
-    use super::*;
-    use serde::{Deserializer, Serializer};
-
-    pub fn serialize<S>(date: &DateTime<Utc>, serializer: S) -> Result<S::Ok, S::Error>
-    where
-        S: Serializer,
-    {
-        serializer.serialize_str(&date.timestamp().to_string())
-    }
-
-    pub fn deserialize<'de, D>(deserializer: D) -> Result<DateTime<Utc>, D::Error>
-    where
-        D: Deserializer<'de>,
-    {
-        let timestamp_str = String::deserialize(deserializer)?;
-        let timestamp = timestamp_str
-            .parse::<i64>()
-            .map_err(serde::de::Error::custom)?;
-        Ok(Utc.timestamp_opt(timestamp, 0).unwrap())
-    }
-}
-
-mod u64_serialization {
-    use serde::{de, Deserialize, Deserializer, Serializer};
-
-    pub fn serialize<S>(value: &u64, serializer: S) -> Result<S::Ok, S::Error>
-    where
-        S: Serializer,
-    {
-        serializer.serialize_str(&value.to_string())
-    }
-
-    pub fn deserialize<'de, D>(deserializer: D) -> Result<u64, D::Error>
-    where
-        D: Deserializer<'de>,
-    {
-        let value_str = String::deserialize(deserializer)?;
-        value_str
-            .trim()
-            .replace(",", "")
-            .parse::<u64>()
-            .map_err(de::Error::custom)
-    }
-}
+use super::serialization::{timestamp_serialization, u128_serialization, u64_serialization};
 
 #[derive(Table, Debug, Serialize, Deserialize, FromRow, Clone, ToSchema)]
 #[table_name("`runepool_unit_intervals`")]
 pub struct RunepoolUnitsInterval {
     #[serde(rename = "count", with = "u64_serialization")]
+    #[sqlx(try_from = "Unsigned")]
     pub count: u64,
     #[serde(rename = "endTime", with = "timestamp_serialization")]
     pub end_time: DateTime<Utc>,
     #[serde(rename = "startTime", with = "timestamp_serialization")]
     pub start_time: DateTime<Utc>,
-    #[serde(rename = "units", with = "u64_serialization")]
-    pub units: u64,
+    #[serde(rename = "units", with = "u128_serialization")]
+    #[sqlx(try_from = "Amount")]
+    pub units: u128,
 }
 
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
@@ -71,14 +29,14 @@ pub struct MetaStats {
     pub end_count: u64,
     #[serde(rename = "endTime", with = "timestamp_serialization")]
     pub end_time: DateTime<Utc>,
-    #[serde(rename = "endUnits", with = "u64_serialization")]
-    pub end_units: u64,
+    #[serde(rename = "endUnits", with = "u128_serialization")]
+    pub end_units: u128,
     #[serde(rename = "startCount", with = "u64_serialization")]
     pub start_count: u64,
     #[serde(rename = "startTime", with = "timestamp_serialization")]
     pub start_time: DateTime<Utc>,
-    #[serde(rename = "startUnits", with = "u64_serialization")]
-    pub start_units: u64,
+    #[serde(rename = "startUnits", with = "u128_serialization")]
+    pub start_units: u128,
 }
 
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
@@ -104,4 +62,5 @@ pub struct RunepoolUnitsHistoryQueryParams {
     pub sort_by: Option<String>,
     pub order: Option<String>,
     pub units_gt: Option<u64>,
+    pub format: Option<String>,
 }
@@ -0,0 +1,518 @@
+// Serde helpers shared by every model. Midgard sends (and we answer with) numbers as strings,
+// `numbers=native` switches the responses to plain JSON numbers for the current request.
+use serde::{Deserialize, Deserializer};
+use std::future::Future;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NumberMode {
+    // Midgard compatible, `"123"`
+    #[default]
+    String,
+    // JSON numbers, `123`
+    Native,
+}
+
+impl TryFrom<String> for NumberMode {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        match s.to_lowercase().as_str() {
+            "string" => Ok(NumberMode::String),
+            "native" => Ok(NumberMode::Native),
+            _ => Err("Invalid numbers, expected one of string, native".to_string()),
+        }
+    }
+}
+
+// `Number.MAX_SAFE_INTEGER`, integers past it are rounded by JavaScript clients and stay strings
+// in native mode
+const MAX_SAFE_INTEGER: u128 = (1 << 53) - 1;
+
+tokio::task_local! {
+    static NUMBER_MODE: NumberMode;
+}
+
+// Runs `future` with `mode` applied to everything it serializes
+pub async fn with_number_mode<F: Future>(mode: NumberMode, future: F) -> F::Output {
+    NUMBER_MODE.scope(mode, future).await
+}
+
+// Mode of the request being handled, strings outside of a request (crons, tests)
+pub fn number_mode() -> NumberMode {
+    NUMBER_MODE.try_with(|mode| *mode).unwrap_or_default()
+}
+
+// Accepts `"123"` as well as `123` so we can read back our own native responses
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StringOrNumber {
+    String(String),
+    Unsigned(u64),
+    Signed(i64),
+    Float(f64),
+}
+
+fn deserialize_number_str<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(match StringOrNumber::deserialize(deserializer)? {
+        StringOrNumber::String(value) => value,
+        StringOrNumber::Unsigned(value) => value.to_string(),
+        StringOrNumber::Signed(value) => value.to_string(),
+        StringOrNumber::Float(value) => value.to_string(),
+    })
+}
+
+// Drops `,` thousands separators, but only where they group digits by three. Anything else is
+// returned as is so the parse that follows rejects it.
+fn strip_thousands(value: &str) -> String {
+    let (integer, fraction) = match value.split_once('.') {
+        Some((integer, fraction)) => (integer, Some(fraction)),
+        None => (value, None),
+    };
+    let digits = integer.strip_prefix('-').unwrap_or(integer);
+    let mut groups = digits.split(',');
+    let grouped = digits.contains(',')
+        && groups
+            .next()
+            .is_some_and(|first| (1..=3).contains(&first.len()))
+        && groups.all(|group| group.len() == 3);
+    if !grouped {
+        return value.to_string();
+    }
+
+    let mut stripped = integer.replace(',', "");
+    if let Some(fraction) = fraction {
+        stripped.push('.');
+        stripped.push_str(fraction);
+    }
+    stripped
+}
+
+// Amounts are whole base units. Midgard sometimes writes them with a fractional part, which is
+// accepted when it is zero and an error otherwise rather than truncated.
+fn parse_unsigned(value: &str) -> Result<u128, String> {
+    let value = strip_thousands(value.trim());
+    let integer = match value.split_once('.') {
+        Some((integer, fraction)) if fraction.bytes().all(|digit| digit == b'0') => integer,
+        Some(_) => return Err(format!("{} is not a whole number", value)),
+        None => &value,
+    };
+    integer
+        .parse::<u128>()
+        .map_err(|e| format!("invalid unsigned integer {:?}: {}", value, e))
+}
+
+fn parse_float(value: &str) -> Result<f64, String> {
+    let value = value.trim();
+    // Midgard's answer for ratios over an empty pool
+    if value == "NaN" {
+        return Ok(f64::NAN);
+    }
+    match strip_thousands(value).parse::<f64>() {
+        Ok(number) if number.is_finite() => Ok(number),
+        Ok(_) => Err(format!("{} is out of range", value)),
+        Err(e) => Err(format!("invalid number {:?}: {}", value, e)),
+    }
+}
+
+pub mod float_serialization {
+    use super::{deserialize_number_str, number_mode, parse_float, NumberMode};
+    use serde::{de::Deserializer, ser::Serializer};
+
+    pub fn serialize<S>(value: &f64, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match number_mode() {
+            NumberMode::String => serializer.serialize_str(&value.to_string()),
+            NumberMode::Native => serializer.serialize_f64(*value),
+        }
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<f64, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value_str = deserialize_number_str(deserializer)?;
+        parse_float(&value_str).map_err(serde::de::Error::custom)
+    }
+}
+
+pub mod option_float_serialization {
+    use super::{deserialize_number_str, number_mode, parse_float, NumberMode};
+    use serde::{de::Deserializer, ser::Serializer, Deserialize};
+
+    pub fn serialize<S>(value: &Option<f64>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match (value, number_mode()) {
+            (Some(value), NumberMode::String) => serializer.serialize_str(&value.to_string()),
+            (Some(value), NumberMode::Native) => serializer.serialize_f64(*value),
+            (None, _) => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<f64>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        struct Value(#[serde(deserialize_with = "deserialize_number_str")] String);
+
+        match Option::<Value>::deserialize(deserializer)? {
+            Some(Value(value_str)) => parse_float(&value_str)
+                .map(Some)
+                .map_err(serde::de::Error::custom),
+            None => Ok(None),
+        }
+    }
+}
+
+pub mod option_float_vec_serialization {
+    use super::option_float_serialization;
+    use serde::{de::Deserializer, ser::Serializer, Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize)]
+    struct Value(#[serde(with = "option_float_serialization")] Option<f64>);
+
+    pub fn serialize<S>(values: &[Option<f64>], serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_seq(values.iter().map(|value| Value(*value)))
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Vec<Option<f64>>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Ok(Vec::<Value>::deserialize(deserializer)?
+            .into_iter()
+            .map(|Value(value)| value)
+            .collect())
+    }
+}
+
+pub mod u64_serialization {
+    use super::{
+        deserialize_number_str, number_mode, parse_unsigned, NumberMode, MAX_SAFE_INTEGER,
+    };
+    use serde::{de, Deserializer, Serializer};
+
+    pub fn serialize<S>(value: &u64, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match number_mode() {
+            NumberMode::Native if u128::from(*value) <= MAX_SAFE_INTEGER => {
+                serializer.serialize_u64(*value)
+            }
+            _ => serializer.serialize_str(&value.to_string()),
+        }
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<u64, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value_str = deserialize_number_str(deserializer)?;
+        let value = parse_unsigned(&value_str).map_err(de::Error::custom)?;
+        u64::try_from(value).map_err(de::Error::custom)
+    }
+}
+
+// Amounts in base units, which do not always fit 64 bits
+pub mod u128_serialization {
+    use super::{
+        deserialize_number_str, number_mode, parse_unsigned, NumberMode, MAX_SAFE_INTEGER,
+    };
+    use serde::{de, Deserializer, Serializer};
+
+    pub fn serialize<S>(value: &u128, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match number_mode() {
+            NumberMode::Native if *value <= MAX_SAFE_INTEGER => serializer.serialize_u128(*value),
+            _ => serializer.serialize_str(&value.to_string()),
+        }
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<u128, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value_str = deserialize_number_str(deserializer)?;
+        parse_unsigned(&value_str).map_err(de::Error::custom)
+    }
+}
+
+pub mod u32_serialization {
+    use super::{deserialize_number_str, number_mode, parse_unsigned, NumberMode};
+    use serde::{de, Deserializer, Serializer};
+
+    pub fn serialize<S>(value: &u32, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match number_mode() {
+            NumberMode::String => serializer.serialize_str(&value.to_string()),
+            NumberMode::Native => serializer.serialize_u32(*value),
+        }
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<u32, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value_str = deserialize_number_str(deserializer)?;
+        let value = parse_unsigned(&value_str).map_err(de::Error::custom)?;
+        u32::try_from(value).map_err(de::Error::custom)
+    }
+}
+
+// Unix seconds
+pub mod timestamp_serialization {
+    use super::{deserialize_number_str, number_mode, NumberMode};
+    use chrono::{DateTime, TimeZone, Utc};
+    use serde::{Deserializer, Serializer};
+
+    pub fn serialize<S>(date: &DateTime<Utc>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match number_mode() {
+            NumberMode::String => serializer.serialize_str(&date.timestamp().to_string()),
+            NumberMode::Native => serializer.serialize_i64(date.timestamp()),
+        }
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<DateTime<Utc>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let timestamp_str = deserialize_number_str(deserializer)?;
+        let timestamp = timestamp_str
+            .trim()
+            .parse::<i64>()
+            .map_err(serde::de::Error::custom)?;
+        Utc.timestamp_opt(timestamp, 0)
+            .single()
+            .ok_or_else(|| serde::de::Error::custom("timestamp out of range"))
+    }
+}
+
+// Nanoseconds since epoch, used by Midgard for action dates. Always a string, it does not fit
+// in the 53 bits JavaScript numbers can represent exactly.
+pub mod nanos_timestamp_serialization {
+    use super::deserialize_number_str;
+    use chrono::{DateTime, TimeZone, Utc};
+    use serde::{Deserializer, Serializer};
+
+    pub fn serialize<S>(date: &DateTime<Utc>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let nanos =
+            date.timestamp() as i128 * 1_000_000_000 + date.timestamp_subsec_nanos() as i128;
+        serializer.serialize_str(&nanos.to_string())
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<DateTime<Utc>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let nanos_str = deserialize_number_str(deserializer)?;
+        let nanos = nanos_str
+            .trim()
+            .parse::<i64>()
+            .map_err(serde::de::Error::custom)?;
+        Ok(Utc.timestamp_nanos(nanos))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    use serde::Serialize;
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    struct Sample {
+        #[serde(with = "u64_serialization")]
+        amount: u64,
+        #[serde(with = "float_serialization")]
+        price: f64,
+        #[serde(with = "option_float_serialization")]
+        change: Option<f64>,
+    }
+
+    fn sample() -> Sample {
+        Sample {
+            amount: 12_345,
+            price: 1.5,
+            change: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn strings_by_default() {
+        let json = serde_json::to_string(&sample()).unwrap();
+        assert_eq!(json, r#"{"amount":"12345","price":"1.5","change":null}"#);
+    }
+
+    #[tokio::test]
+    async fn native_numbers_inside_the_scope_only() {
+        let json = with_number_mode(NumberMode::Native, async {
+            serde_json::to_string(&sample()).unwrap()
+        })
+        .await;
+        assert_eq!(json, r#"{"amount":12345,"price":1.5,"change":null}"#);
+
+        let json = serde_json::to_string(&sample()).unwrap();
+        assert_eq!(json, r#"{"amount":"12345","price":"1.5","change":null}"#);
+    }
+
+    #[tokio::test]
+    async fn native_integers_past_53_bits_stay_strings() {
+        #[derive(Serialize)]
+        struct Amounts(
+            #[serde(with = "u64_serialization")] u64,
+            #[serde(with = "u64_serialization")] u64,
+            #[serde(with = "u128_serialization")] u128,
+            #[serde(with = "u128_serialization")] u128,
+        );
+
+        let safe = (1 << 53) - 1;
+        let json = with_number_mode(NumberMode::Native, async {
+            serde_json::to_string(&Amounts(safe, safe + 1, safe.into(), (safe + 1).into())).unwrap()
+        })
+        .await;
+        assert_eq!(
+            json,
+            r#"[9007199254740991,"9007199254740992",9007199254740991,"9007199254740992"]"#
+        );
+    }
+
+    #[test]
+    fn both_encodings_deserialize() {
+        let from_strings: Sample =
+            serde_json::from_str(r#"{"amount":"12,345","price":"1.5","change":"0.1"}"#).unwrap();
+        let from_numbers: Sample =
+            serde_json::from_str(r#"{"amount":12345,"price":1.5,"change":0.1}"#).unwrap();
+
+        assert_eq!(from_strings, from_numbers);
+        assert_eq!(from_numbers.change, Some(0.1));
+    }
+
+    #[test]
+    fn invalid_numbers_mode_is_rejected() {
+        assert_eq!(
+            NumberMode::try_from("NATIVE".to_string()),
+            Ok(NumberMode::Native)
+        );
+        assert!(NumberMode::try_from("hex".to_string()).is_err());
+    }
+
+    #[test]
+    fn amounts_are_never_truncated() {
+        assert_eq!(parse_unsigned(" 1,234,567 "), Ok(1_234_567));
+        assert_eq!(parse_unsigned("42.000"), Ok(42));
+        assert!(parse_unsigned("42.5").is_err());
+        assert!(parse_unsigned("-1").is_err());
+        assert!(parse_unsigned("1,2,3").is_err());
+        assert!(parse_unsigned("1e30").is_err());
+        assert_eq!(
+            parse_unsigned("18446744073709551616"),
+            Ok(u128::from(u64::MAX) + 1)
+        );
+        assert!(parse_unsigned("340282366920938463463374607431768211456").is_err());
+        assert!(serde_json::from_str::<Sample>(
+            r#"{"amount":"18446744073709551616","price":"1","change":null}"#
+        )
+        .is_err());
+
+        #[derive(Debug, Deserialize)]
+        struct Members(#[serde(with = "u32_serialization")] u32);
+        assert_eq!(serde_json::from_str::<Members>(r#""42""#).unwrap().0, 42);
+        assert!(serde_json::from_str::<Members>(r#""4294967296""#).is_err());
+        assert!(serde_json::from_str::<Members>("12.5").is_err());
+    }
+
+    #[test]
+    fn floats_are_finite_or_midgard_nan() {
+        assert!(parse_float("NaN").unwrap().is_nan());
+        assert_eq!(parse_float("1,234.5"), Ok(1234.5));
+        assert!(parse_float("inf").is_err());
+        assert!(parse_float("1e400").is_err());
+        assert!(parse_float("1,23.5").is_err());
+    }
+
+    #[test]
+    fn timestamps_out_of_range_are_errors() {
+        #[derive(Debug, Deserialize)]
+        struct Time(#[serde(with = "timestamp_serialization")] chrono::DateTime<chrono::Utc>);
+        assert_eq!(
+            serde_json::from_str::<Time>(r#""1704067200""#).unwrap().0,
+            "2024-01-01T00:00:00Z"
+                .parse::<chrono::DateTime<chrono::Utc>>()
+                .unwrap()
+        );
+        assert!(serde_json::from_str::<Time>(r#""9223372036854775807""#).is_err());
+        assert!(serde_json::from_str::<Time>(r#""-9223372036854775808""#).is_err());
+    }
+
+    fn with_thousands(value: u64) -> String {
+        let digits = value.to_string();
+        let mut grouped = String::new();
+        for (i, digit) in digits.chars().enumerate() {
+            if i > 0 && (digits.len() - i).is_multiple_of(3) {
+                grouped.push(',');
+            }
+            grouped.push(digit);
+        }
+        grouped
+    }
+
+    proptest! {
+        #[test]
+        fn every_u64_encoding_round_trips(value: u64) {
+            let wide = u128::from(value);
+            prop_assert_eq!(parse_unsigned(&value.to_string()), Ok(wide));
+            prop_assert_eq!(parse_unsigned(&with_thousands(value)), Ok(wide));
+            prop_assert_eq!(parse_unsigned(&format!("{}.00", value)), Ok(wide));
+
+            let sample = Sample { amount: value, price: 1.0, change: None };
+            let json = serde_json::to_string(&sample).unwrap();
+            prop_assert_eq!(serde_json::from_str::<Sample>(&json).unwrap(), sample);
+        }
+
+        #[test]
+        fn every_u128_amount_round_trips(value: u128) {
+            #[derive(Debug, Serialize, Deserialize, PartialEq)]
+            struct Total(#[serde(with = "u128_serialization")] u128);
+
+            prop_assert_eq!(parse_unsigned(&value.to_string()), Ok(value));
+            let json = serde_json::to_string(&Total(value)).unwrap();
+            prop_assert_eq!(json.clone(), format!("\"{}\"", value));
+            prop_assert_eq!(serde_json::from_str::<Total>(&json).unwrap(), Total(value));
+        }
+
+        #[test]
+        fn fractional_amounts_are_rejected(value in 0..u64::MAX / 10, fraction in 1..1000u32) {
+            let amount = format!("{}.{:03}", value, fraction);
+            prop_assert!(parse_unsigned(&amount).is_err());
+        }
+
+        #[test]
+        fn any_string_parses_or_errors(value in "\\PC*") {
+            let _ = parse_unsigned(&value);
+            if let Ok(number) = parse_float(&value) {
+                prop_assert!(number.is_finite() || value.trim() == "NaN");
+            }
+        }
+    }
+}
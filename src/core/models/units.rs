@@ -0,0 +1,173 @@
+// Amounts as THORChain counts them, integers of 1e8 base units for RUNE and for every asset,
+// and what `units=decimal|usd` turns them into
+use super::dataset::Dataset;
+
+pub const BASE_UNITS_PER_COIN: f64 = 1e8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Units {
+    // 1e8 base units, as Midgard sends them
+    #[default]
+    Base,
+    // Whole RUNE or asset, `1.5` for `150000000`
+    Decimal,
+    // USD at the price of the interval the amount belongs to
+    Usd,
+}
+
+impl TryFrom<String> for Units {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        match s.to_lowercase().as_str() {
+            "base" => Ok(Units::Base),
+            "decimal" => Ok(Units::Decimal),
+            "usd" => Ok(Units::Usd),
+            _ => Err("Invalid units, expected one of base, decimal, usd".to_string()),
+        }
+    }
+}
+
+// What an amount is counted in. Midgard's `*VolumeUSD` figures are USD, in base units as well.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Denomination {
+    Rune,
+    Asset,
+    Usd,
+}
+
+// USD price of one RUNE and of one asset, `None` where the interval does not tell
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Prices {
+    pub rune_usd: Option<f64>,
+    pub asset_usd: Option<f64>,
+}
+
+// Empty pools report NaN or 0 prices, nothing can be valued with those
+fn usable(price: f64) -> Option<f64> {
+    (price.is_finite() && price > 0.0).then_some(price)
+}
+
+impl Prices {
+    // Earnings and swap intervals, which are network wide
+    pub fn rune(rune_price_usd: f64) -> Self {
+        Self {
+            rune_usd: usable(rune_price_usd),
+            asset_usd: None,
+        }
+    }
+
+    // Depth intervals price the asset in RUNE and in USD, the RUNE price follows from the two
+    pub fn pool(asset_price: f64, asset_price_usd: f64) -> Self {
+        Self {
+            rune_usd: usable(asset_price)
+                .zip(usable(asset_price_usd))
+                .map(|(asset_price, asset_price_usd)| asset_price_usd / asset_price),
+            asset_usd: usable(asset_price_usd),
+        }
+    }
+
+    // Numeric columns of `dataset` the prices are read from
+    pub fn columns(dataset: Dataset) -> &'static [&'static str] {
+        match dataset {
+            Dataset::Depth => &["asset_price", "asset_price_usd"],
+            Dataset::Earnings | Dataset::Swap => &["rune_price_usd"],
+            Dataset::Runepool => &[],
+        }
+    }
+
+    // Prices of one stored row, `get` looks a column up by name
+    pub fn of_row(dataset: Dataset, get: impl Fn(&str) -> Option<f64>) -> Self {
+        match dataset {
+            Dataset::Depth => match (get("asset_price"), get("asset_price_usd")) {
+                (Some(asset_price), Some(asset_price_usd)) => {
+                    Prices::pool(asset_price, asset_price_usd)
+                }
+                _ => Prices::default(),
+            },
+            Dataset::Earnings | Dataset::Swap => {
+                get("rune_price_usd").map(Prices::rune).unwrap_or_default()
+            }
+            Dataset::Runepool => Prices::default(),
+        }
+    }
+}
+
+impl Units {
+    // `None` when a USD value is asked for without a price to compute it with
+    pub fn convert(&self, amount: f64, denomination: Denomination, prices: &Prices) -> Option<f64> {
+        let coins = amount / BASE_UNITS_PER_COIN;
+        match (self, denomination) {
+            (Units::Base, _) => Some(amount),
+            (Units::Decimal, _) | (Units::Usd, Denomination::Usd) => Some(coins),
+            (Units::Usd, Denomination::Rune) => prices.rune_usd.map(|price| coins * price),
+            (Units::Usd, Denomination::Asset) => prices.asset_usd.map(|price| coins * price),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn amounts_convert_to_coins_and_usd() {
+        let prices = Prices::pool(20.0, 50.0);
+        assert_eq!(prices.rune_usd, Some(2.5));
+
+        assert_eq!(
+            Units::Base.convert(150_000_000.0, Denomination::Rune, &prices),
+            Some(150_000_000.0)
+        );
+        assert_eq!(
+            Units::Decimal.convert(150_000_000.0, Denomination::Asset, &prices),
+            Some(1.5)
+        );
+        assert_eq!(
+            Units::Usd.convert(150_000_000.0, Denomination::Rune, &prices),
+            Some(3.75)
+        );
+        assert_eq!(
+            Units::Usd.convert(150_000_000.0, Denomination::Asset, &prices),
+            Some(75.0)
+        );
+        assert_eq!(
+            Units::Usd.convert(150_000_000.0, Denomination::Usd, &Prices::default()),
+            Some(1.5)
+        );
+    }
+
+    #[test]
+    fn missing_prices_leave_usd_values_empty() {
+        let network = Prices::rune(2.0);
+        assert_eq!(Units::Usd.convert(1e8, Denomination::Asset, &network), None);
+
+        let empty_pool = Prices::pool(f64::NAN, 0.0);
+        assert_eq!(empty_pool, Prices::default());
+        assert_eq!(
+            Units::Usd.convert(1e8, Denomination::Rune, &empty_pool),
+            None
+        );
+        assert_eq!(
+            Units::Decimal.convert(1e8, Denomination::Rune, &empty_pool),
+            Some(1.0)
+        );
+    }
+
+    #[test]
+    fn prices_come_from_the_dataset_columns() {
+        let swap = Prices::of_row(Dataset::Swap, |column| {
+            (column == "rune_price_usd").then_some(4.0)
+        });
+        assert_eq!(swap, Prices::rune(4.0));
+
+        let depth = Prices::of_row(Dataset::Depth, |column| match column {
+            "asset_price" => Some(10.0),
+            "asset_price_usd" => Some(40.0),
+            _ => None,
+        });
+        assert_eq!(depth, Prices::pool(10.0, 40.0));
+        assert_eq!(Units::try_from("USD".to_string()), Ok(Units::Usd));
+        assert!(Units::try_from("sats".to_string()).is_err());
+    }
+}
@@ -0,0 +1,9 @@
+// Everything but the command line, so the fuzz targets can link against the models
+pub mod api;
+pub mod config;
+pub mod core;
+pub mod services;
+pub mod swagger;
+
+#[cfg(test)]
+mod tests;
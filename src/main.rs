@@ -1,74 +1,139 @@
-use api::routes::depth::get_depth_history;
-use api::routes::earnings::get_earnings_history;
-use api::routes::runepool::get_runepool_units_history;
-use api::routes::swap::get_swap_history;
-use api::server::fetch::{
-    fetch_and_store_depth_history, fetch_and_store_earnings_history,
-    fetch_and_store_runepool_units_history, fetch_and_store_swap_history,
-};
-use axum::{routing::get, Router};
+use api::router::app;
+use api::state::AppState;
+use catalog_crypto_api::{api, config, core, services};
 use chrono::Utc;
-use config::connect;
+use clap::Parser;
+use cli::{Cli, Command};
+use config::{app::AppConfig, connect};
 use dotenv::dotenv;
-use http::Method;
 use services::{
-    client::get_midgard_api_url, jobs::cron::hourly_fetcher::HourlyFetcher, spawn::spawn_cron_jobs,
+    cache,
+    jobs::cron::{actions_cron::ActionsCron, hourly_fetcher::HourlyFetcher, job_runner::JobRunner},
+    supervisor::{Shutdown, Supervisor},
+    telemetry,
 };
-use std::env;
 use std::net::SocketAddr;
-use swagger::SwaggerApiDoc;
+use std::process::ExitCode;
+use std::sync::Arc;
 use tokio::net::TcpListener;
-use tower_http::cors::{Any, CorsLayer};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
-use utoipa::OpenApi;
-use utoipa_swagger_ui::SwaggerUi;
-
-mod api;
-mod config;
-mod core;
-mod services;
 
-mod swagger;
+mod cli;
 
 /* ************************************************************ */
 /* ************************************************************ */
 /* !NOTE: PLEASE FETCH THINGS ONE BY ONE BECAUSE OF RATE LIMITS */
 /* ************************************************************ */
 /* ************************************************************ */
-#[tokio::main(flavor = "multi_thread", worker_threads = 10)]
-async fn main() {
+fn main() -> ExitCode {
     dotenv().ok();
+    let cli = Cli::parse();
 
-    let database_url = std::env::var("DATABASE_URL").expect("Database url issue");
+    let config = match AppConfig::load() {
+        Ok(config) => Arc::new(config),
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::FAILURE;
+        }
+    };
 
-    tracing::info!(
-        "Env variables are \n{}\n{}",
-        get_midgard_api_url(),
-        database_url
-    );
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(config.server.worker_threads)
+        .enable_all()
+        .build()
+        .expect("Failed to build the tokio runtime");
 
-    let pool = connect::connect_database(&database_url)
-        .await
-        .expect("Failed to connect to database");
+    runtime.block_on(async move {
+        setup_tracing();
 
-    setup_tracing();
+        match cli.command.unwrap_or(Command::Serve) {
+            Command::Serve => serve(config).await,
+            command => cli::run(command, config).await,
+        }
+    })
+}
+
+async fn serve(config: Arc<AppConfig>) -> ExitCode {
+    let pool = match connect::connect_database(&config.database).await {
+        Ok(pool) => pool,
+        Err(e) => {
+            tracing::error!("Failed to connect to the database: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    // Refuses a schema migrated by a newer version or left dirty by a failed migration, the
+    // repositories would fail in confusing ways on it
+    if let Err(e) = connect::prepare_schema(&pool, &config.database).await {
+        tracing::error!("{}", e);
+        return ExitCode::FAILURE;
+    }
+
+    tracing::info!("Using midgard at {}", config.midgard.url);
+
+    if let Err(e) = telemetry::install_recorder() {
+        tracing::error!("Failed to install the metrics recorder: {}", e);
+    }
+    cache::configure(&config.cache);
 
     tracing::info!("Connected to database...");
     println!("Current Utc TimeStamp: {:?}", Utc::now().timestamp());
 
-    // !NOTE: Uncomment this if you want to fetch initial data and read the comment above the main
-    // spawn_cron_jobs(pool.clone());
-    // fetch_initial_data(pool.clone()).await;
-
-    let hourly_pool = pool.clone();
-    tokio::spawn(async move {
-        let mut hourly_fetcher = HourlyFetcher::new(hourly_pool);
-        if let Err(e) = hourly_fetcher.start().await {
-            tracing::error!("Hourly fetcher failed: {}", e);
+    // Bound before the crons start, a port that is taken fails the start right away
+    let addr = (config.server.host.as_str(), config.server.port);
+    let listener = match TcpListener::bind(addr).await {
+        Ok(listener) => listener,
+        Err(e) => {
+            tracing::error!("Failed to listen on {}:{}: {}", addr.0, addr.1, e);
+            return ExitCode::FAILURE;
         }
+    };
+
+    let mut supervisor = Supervisor::new(&config.supervisor);
+
+    let (hourly_pool, hourly_config) = (pool.clone(), config.clone());
+    supervisor.spawn("hourly_fetcher", move |shutdown| {
+        let mut hourly_fetcher = HourlyFetcher::new(hourly_pool.clone(), hourly_config.clone());
+        async move { hourly_fetcher.start(shutdown).await }
     });
 
-    start_server(pool).await;
+    let (jobs_pool, jobs_config) = (pool.clone(), config.clone());
+    supervisor.spawn("job_runner", move |shutdown| {
+        let mut job_runner = JobRunner::new(jobs_pool.clone(), jobs_config.clone());
+        async move { job_runner.start(shutdown).await }
+    });
+
+    if config.ingestion.actions {
+        let (actions_pool, actions_config) = (pool.clone(), config.clone());
+        supervisor.spawn("actions_cron", move |shutdown| {
+            let mut actions_cron = ActionsCron::new(actions_pool.clone(), actions_config.clone());
+            async move { actions_cron.start(shutdown).await }
+        });
+    }
+
+    let server = start_server(listener, AppState { pool, config }, supervisor.handle());
+    tokio::pin!(server);
+    let served = tokio::select! {
+        served = &mut server => served,
+        _ = shutdown_signal() => {
+            tracing::info!("Shutting down, finishing in-flight requests and background work");
+            supervisor.stop();
+            server.await
+        }
+    };
+
+    let stopped = supervisor.shutdown().await;
+    match served {
+        Ok(()) if stopped => {
+            tracing::info!("Shut down cleanly");
+            ExitCode::SUCCESS
+        }
+        Ok(()) => ExitCode::FAILURE,
+        Err(e) => {
+            tracing::error!("Server failed: {}", e);
+            ExitCode::FAILURE
+        }
+    }
 }
 
 fn setup_tracing() {
@@ -81,39 +146,48 @@ fn setup_tracing() {
         .init();
 }
 
-async fn fetch_initial_data(pool: sqlx::MySqlPool) {
-    tracing::info!("Starting initial data fetch...");
-
-    fetch_and_store_depth_history(&pool).await;
-    fetch_and_store_earnings_history(&pool).await;
-    fetch_and_store_swap_history(&pool).await;
-    fetch_and_store_runepool_units_history(&pool).await;
-}
-
-async fn start_server(pool: sqlx::MySqlPool) {
-    let app = Router::new()
-        .layer(CorsLayer::new().allow_origin(Any).allow_methods([
-            Method::GET,
-            Method::PUT,
-            Method::POST,
-            Method::DELETE,
-        ]))
-        .route("/depth_history", get(get_depth_history))
-        .route("/earning_history", get(get_earnings_history))
-        .route("/swap_history", get(get_swap_history))
-        .route("/runepool_units_history", get(get_runepool_units_history))
-        .with_state(pool)
-        .merge(SwaggerUi::new("/").url("/api-docs/openapi.json", SwaggerApiDoc::openapi()));
-
-    let addr = SocketAddr::from(([0, 0, 0, 0], 3000));
-    let listener = TcpListener::bind(addr).await.unwrap();
-
-    tracing::debug!("listening on {}", listener.local_addr().unwrap());
+// Stops accepting connections once `shutdown` is requested and returns when the requests in
+// flight are answered
+async fn start_server(
+    listener: TcpListener,
+    state: AppState,
+    shutdown: Shutdown,
+) -> std::io::Result<()> {
+    tracing::debug!("listening on {}", listener.local_addr()?);
 
     axum::serve(
         listener,
-        app.into_make_service_with_connect_info::<SocketAddr>(),
+        app(state).into_make_service_with_connect_info::<SocketAddr>(),
     )
+    .with_graceful_shutdown(async move { shutdown.requested().await })
     .await
-    .unwrap();
+}
+
+// SIGTERM from the orchestrator, or ctrl-c in a terminal
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    let terminate = async {
+        use tokio::signal::unix::{signal, SignalKind};
+        match signal(SignalKind::terminate()) {
+            Ok(mut sigterm) => {
+                sigterm.recv().await;
+            }
+            Err(e) => {
+                tracing::error!("Failed to listen for SIGTERM: {}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        result = tokio::signal::ctrl_c() => {
+            if let Err(e) = result {
+                tracing::error!("Failed to listen for ctrl-c: {}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+        _ = terminate => {}
+    }
 }
@@ -0,0 +1,169 @@
+// In-process cache for API responses. Entries are grouped by the dataset they were read from so
+// the ingestion side can drop everything that might be stale as soon as it stores new rows.
+use crate::config::app::CacheConfig;
+use crate::core::models::dataset::Dataset;
+use axum::{body::Bytes, http::HeaderMap, http::StatusCode};
+use moka::future::Cache;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+
+#[derive(Debug, Clone)]
+pub struct CachedResponse {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub body: Bytes,
+}
+
+struct DatasetCache {
+    entries: Cache<String, Arc<CachedResponse>>,
+    // Bumped on every invalidation, lets a request that started before the invalidation know
+    // that what it read from the database may already be outdated
+    generation: AtomicU64,
+}
+
+pub struct QueryCache {
+    datasets: HashMap<Dataset, DatasetCache>,
+}
+
+impl QueryCache {
+    pub fn new(ttl: Duration, max_entries: u64) -> Self {
+        let datasets = Dataset::ALL
+            .into_iter()
+            .map(|dataset| {
+                let entries = Cache::builder()
+                    .max_capacity(max_entries)
+                    .time_to_live(ttl)
+                    .build();
+                (
+                    dataset,
+                    DatasetCache {
+                        entries,
+                        generation: AtomicU64::new(0),
+                    },
+                )
+            })
+            .collect();
+
+        Self { datasets }
+    }
+
+    fn dataset(&self, dataset: Dataset) -> &DatasetCache {
+        &self.datasets[&dataset]
+    }
+
+    pub async fn get(&self, dataset: Dataset, key: &str) -> Option<Arc<CachedResponse>> {
+        let cached = self.dataset(dataset).entries.get(key).await;
+        let outcome = if cached.is_some() { "hit" } else { "miss" };
+        metrics::counter!("query_cache_requests_total", "dataset" => dataset.to_string(), "outcome" => outcome)
+            .increment(1);
+        cached
+    }
+
+    pub fn generation(&self, dataset: Dataset) -> u64 {
+        self.dataset(dataset).generation.load(Ordering::Acquire)
+    }
+
+    // Stores `response` unless the dataset was invalidated since `generation` was read
+    pub async fn insert(
+        &self,
+        dataset: Dataset,
+        generation: u64,
+        key: String,
+        response: CachedResponse,
+    ) {
+        let cache = self.dataset(dataset);
+        if cache.generation.load(Ordering::Acquire) != generation {
+            return;
+        }
+
+        cache.entries.insert(key.clone(), Arc::new(response)).await;
+
+        // An invalidation that ran between the check above and the insert did not see the entry
+        if cache.generation.load(Ordering::Acquire) != generation {
+            cache.entries.invalidate(&key).await;
+        }
+    }
+
+    pub fn invalidate(&self, dataset: Dataset) {
+        let cache = self.dataset(dataset);
+        cache.generation.fetch_add(1, Ordering::AcqRel);
+        cache.entries.invalidate_all();
+        metrics::counter!("query_cache_invalidations_total", "dataset" => dataset.to_string())
+            .increment(1);
+    }
+}
+
+static QUERY_CACHE: OnceLock<QueryCache> = OnceLock::new();
+
+// Turns the cache on, until then (and with a TTL of 0) every request goes to the database
+pub fn configure(config: &CacheConfig) {
+    if config.ttl_secs > 0 {
+        let cache = QueryCache::new(Duration::from_secs(config.ttl_secs), config.max_entries);
+        if QUERY_CACHE.set(cache).is_err() {
+            tracing::warn!("The query cache was already configured");
+        }
+    }
+}
+
+pub fn query_cache() -> Option<&'static QueryCache> {
+    QUERY_CACHE.get()
+}
+
+// Called by the repositories once new rows of `dataset` are in the database
+pub fn invalidate(dataset: Dataset) {
+    if let Some(cache) = query_cache() {
+        tracing::debug!("Invalidating cached {} responses", dataset);
+        cache.invalidate(dataset);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(body: &'static str) -> CachedResponse {
+        CachedResponse {
+            status: StatusCode::OK,
+            headers: HeaderMap::new(),
+            body: Bytes::from_static(body.as_bytes()),
+        }
+    }
+
+    fn cache() -> QueryCache {
+        QueryCache::new(Duration::from_secs(60), 100)
+    }
+
+    #[tokio::test]
+    async fn invalidation_is_per_dataset() {
+        let cache = cache();
+        let generation = cache.generation(Dataset::Swap);
+        cache
+            .insert(Dataset::Swap, generation, "a".into(), response("swap"))
+            .await;
+        let generation = cache.generation(Dataset::Depth);
+        cache
+            .insert(Dataset::Depth, generation, "a".into(), response("depth"))
+            .await;
+
+        cache.invalidate(Dataset::Swap);
+
+        assert!(cache.get(Dataset::Swap, "a").await.is_none());
+        let depth = cache.get(Dataset::Depth, "a").await.unwrap();
+        assert_eq!(depth.body, Bytes::from_static(b"depth"));
+    }
+
+    #[tokio::test]
+    async fn results_read_before_an_invalidation_are_not_stored() {
+        let cache = cache();
+        let generation = cache.generation(Dataset::Earnings);
+
+        cache.invalidate(Dataset::Earnings);
+        cache
+            .insert(Dataset::Earnings, generation, "a".into(), response("stale"))
+            .await;
+
+        assert!(cache.get(Dataset::Earnings, "a").await.is_none());
+    }
+}
@@ -0,0 +1,140 @@
+use crate::services::storage::DbPool;
+use crate::{
+    config::app::MidgardConfig,
+    core::models::{
+        dataset::Dataset, depth_history::DepthHistoryResponse,
+        earnings_history::EarningsHistoryResponse,
+        runepool_units_history::RunepoolUnitsHistoryResponse, swap_history::SwapHistoryResponse,
+    },
+    services::{
+        repository::{depth, earnings, runepool, swap},
+        telemetry,
+    },
+};
+use chrono::{DateTime, Utc};
+use tokio::time;
+
+const MAX_SLOW_DOWN_RETRIES: u32 = 10;
+
+pub struct Page {
+    pub fetched: u32,
+    pub last_end_time: Option<DateTime<Utc>>,
+}
+
+fn page<T>(intervals: &[T], end_time: impl Fn(&T) -> DateTime<Utc>) -> Page {
+    Page {
+        fetched: intervals.len() as u32,
+        last_end_time: intervals.last().map(end_time),
+    }
+}
+
+async fn fetch_text(
+    client: &reqwest::Client,
+    config: &MidgardConfig,
+    dataset: Dataset,
+    url: reqwest::Url,
+) -> Result<String, anyhow::Error> {
+    for _ in 0..MAX_SLOW_DOWN_RETRIES {
+        let response_text = client.get(url.clone()).send().await?.text().await?;
+        if !response_text.contains("slow down") {
+            return Ok(response_text);
+        }
+
+        telemetry::rate_limited(dataset.as_str());
+        tracing::warn!(
+            "Rate limited, waiting for {} seconds before retry...",
+            config.rate_limit_backoff_secs
+        );
+        time::sleep(config.rate_limit_backoff()).await;
+    }
+
+    Err(anyhow::anyhow!(
+        "Still rate limited after {} retries",
+        MAX_SLOW_DOWN_RETRIES
+    ))
+}
+
+// Fetches and stores up to one page of hourly intervals starting at `from`, dropping the ones
+// that start at or after `to`
+pub async fn fetch_page(
+    client: &reqwest::Client,
+    pool: &DbPool,
+    config: &MidgardConfig,
+    dataset: Dataset,
+    pool_name: &str,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<Page, anyhow::Error> {
+    let path = match dataset {
+        Dataset::Depth => format!("history/depths/{}", pool_name),
+        Dataset::Earnings => "history/earnings".to_string(),
+        Dataset::Swap => "history/swaps".to_string(),
+        Dataset::Runepool => "history/runepool".to_string(),
+    };
+
+    let mut url = reqwest::Url::parse(&format!("{}/{}", config.url, path))?;
+    url.query_pairs_mut()
+        .append_pair("interval", "hour")
+        .append_pair("count", &config.page_size.to_string())
+        .append_pair("from", &from.timestamp().to_string());
+
+    let response_text = fetch_text(client, config, dataset, url).await?;
+
+    let page = parse_and_store(pool, dataset, pool_name, &response_text, to).await;
+    if let Ok(page) = &page {
+        telemetry::intervals_fetched(dataset.as_str(), page.fetched as usize);
+    }
+    page
+}
+
+async fn parse_and_store(
+    pool: &DbPool,
+    dataset: Dataset,
+    pool_name: &str,
+    response_text: &str,
+    to: DateTime<Utc>,
+) -> Result<Page, anyhow::Error> {
+    let parse_failure = |e: serde_json::Error| {
+        telemetry::parse_failure(dataset.as_str());
+        e
+    };
+
+    match dataset {
+        Dataset::Depth => {
+            let mut history: DepthHistoryResponse =
+                serde_json::from_str(response_text).map_err(parse_failure)?;
+            history
+                .intervals
+                .retain(|interval| interval.start_time < to);
+            depth::store_intervals(pool, pool_name, &history.intervals).await?;
+            Ok(page(&history.intervals, |interval| interval.end_time))
+        }
+        Dataset::Earnings => {
+            let mut history: EarningsHistoryResponse =
+                serde_json::from_str(response_text).map_err(parse_failure)?;
+            history
+                .intervals
+                .retain(|interval| interval.start_time < to);
+            earnings::store_intervals(pool, &history.intervals).await?;
+            Ok(page(&history.intervals, |interval| interval.end_time))
+        }
+        Dataset::Swap => {
+            let mut history: SwapHistoryResponse =
+                serde_json::from_str(response_text).map_err(parse_failure)?;
+            history
+                .intervals
+                .retain(|interval| interval.start_time < to);
+            swap::store_intervals(pool, &history.intervals).await?;
+            Ok(page(&history.intervals, |interval| interval.end_time))
+        }
+        Dataset::Runepool => {
+            let mut history: RunepoolUnitsHistoryResponse =
+                serde_json::from_str(response_text).map_err(parse_failure)?;
+            history
+                .intervals
+                .retain(|interval| interval.start_time < to);
+            runepool::store_intervals(pool, &history.intervals).await?;
+            Ok(page(&history.intervals, |interval| interval.end_time))
+        }
+    }
+}
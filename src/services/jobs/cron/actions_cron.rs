@@ -0,0 +1,175 @@
+use crate::services::storage::DbPool;
+use crate::{
+    config::app::AppConfig,
+    core::models::actions::{ActionsParams, ActionsResponse},
+    services::{repository::actions::store_actions, supervisor::Shutdown, telemetry},
+};
+use chrono::{DateTime, Duration, Utc};
+use std::sync::Arc;
+use tracing::{error, info};
+
+// Swaps, adds, withdraws and refunds are what support needs to look up
+const ACTION_TYPES: &str = "swap,addLiquidity,withdraw,refund";
+
+pub struct ActionsCron {
+    pool: DbPool,
+    config: Arc<AppConfig>,
+    limit: u32,
+    from: DateTime<Utc>,
+    next_page_token: Option<String>,
+    caught_up: bool,
+}
+
+impl ActionsCron {
+    pub fn new(pool: DbPool, config: Arc<AppConfig>) -> Self {
+        Self {
+            pool,
+            limit: 50, // Midgard does not return more than 50 actions per page
+            from: Utc::now() - Duration::days(config.ingestion.actions_lookback_days),
+            next_page_token: None,
+            caught_up: false,
+            config,
+        }
+    }
+
+    pub async fn start(&mut self, shutdown: Shutdown) -> Result<(), anyhow::Error> {
+        loop {
+            let pause = match self.fetch_and_store(&shutdown).await {
+                Ok(()) => Duration::minutes(1).to_std().unwrap(),
+                Err(e) => {
+                    error!("Failed to fetch and store actions: {}", e);
+                    self.config.midgard.request_delay()
+                }
+            };
+
+            if !shutdown.sleep(pause).await {
+                info!("Actions cron stopped");
+                return Ok(());
+            }
+        }
+    }
+
+    // Pages from the newest action backwards using `nextPageToken`.
+    // The first pass walks all the way down to `from` (so an interrupted backfill is resumed),
+    // after that we stop as soon as a page has nothing new in it.
+    // A shutdown stops it between two pages or requests, the page token is kept for the next pass.
+    async fn fetch_and_store(&mut self, shutdown: &Shutdown) -> Result<(), anyhow::Error> {
+        let client = reqwest::Client::new();
+
+        loop {
+            let params = ActionsParams {
+                limit: Some(self.limit),
+                action_type: Some(ACTION_TYPES.to_string()),
+                next_page_token: self.next_page_token.clone(),
+                from: Some(self.from),
+            };
+
+            let base_url = &self.config.midgard.url;
+            let mut url = reqwest::Url::parse(&format!("{}/actions", base_url))?;
+
+            if let Some(limit) = params.limit {
+                url.query_pairs_mut()
+                    .append_pair("limit", &limit.to_string());
+            }
+
+            if let Some(action_type) = &params.action_type {
+                url.query_pairs_mut().append_pair("type", action_type);
+            }
+
+            if let Some(token) = &params.next_page_token {
+                url.query_pairs_mut().append_pair("nextPageToken", token);
+            }
+
+            if let Some(from) = params.from {
+                url.query_pairs_mut()
+                    .append_pair("fromTimestamp", &from.timestamp().to_string());
+            }
+
+            match client.get(url.clone()).send().await {
+                Ok(response) => {
+                    let response_text = response.text().await?;
+
+                    if response_text.contains("slow down") {
+                        telemetry::rate_limited("actions");
+                        tracing::warn!(
+                            "Rate limited, waiting for {} seconds before retry...",
+                            self.config.midgard.rate_limit_backoff_secs
+                        );
+                        if !shutdown
+                            .sleep(self.config.midgard.rate_limit_backoff())
+                            .await
+                        {
+                            break Ok(());
+                        }
+                        continue;
+                    }
+
+                    match serde_json::from_str::<ActionsResponse>(&response_text) {
+                        Ok(actions) => {
+                            telemetry::intervals_fetched("actions", actions.actions.len());
+                            let inserted = store_actions(&self.pool, &actions.actions).await?;
+                            telemetry::intervals_stored(
+                                "actions",
+                                inserted as u64,
+                                (actions.actions.len() - inserted) as u64,
+                            );
+                            telemetry::fetch_succeeded("actions");
+
+                            info!(
+                                "Successfully stored {} new actions out of {}. URL: {}",
+                                inserted,
+                                actions.actions.len(),
+                                url
+                            );
+
+                            let next_page_token = actions
+                                .meta
+                                .next_page_token
+                                .filter(|token| !token.is_empty() && !actions.actions.is_empty());
+
+                            match next_page_token {
+                                Some(token) if !(self.caught_up && inserted == 0) => {
+                                    self.next_page_token = Some(token);
+                                    if !shutdown.sleep(self.config.midgard.request_delay()).await {
+                                        break Ok(());
+                                    }
+                                }
+                                _ => {
+                                    info!("Actions are up to date, starting from the newest page next time");
+                                    self.next_page_token = None;
+                                    self.caught_up = true;
+                                    break Ok(());
+                                }
+                            }
+                        }
+                        Err(e) => {
+                            telemetry::parse_failure("actions");
+                            error!(
+                                "Failed to parse response: {}, response text (first 500 chars): {}",
+                                e,
+                                response_text.chars().take(500).collect::<String>()
+                            );
+                            if !shutdown
+                                .sleep(self.config.midgard.rate_limit_backoff())
+                                .await
+                            {
+                                break Ok(());
+                            }
+                            continue;
+                        }
+                    }
+                }
+                Err(e) => {
+                    error!("Request failed: {}", e);
+                    if !shutdown
+                        .sleep(self.config.midgard.rate_limit_backoff())
+                        .await
+                    {
+                        break Ok(());
+                    }
+                    continue;
+                }
+            }
+        }
+    }
+}
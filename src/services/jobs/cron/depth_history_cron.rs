@@ -1,29 +1,33 @@
+use crate::services::storage::DbPool;
 use crate::{
+    config::app::AppConfig,
     core::models::{
         common::Interval,
-        depth_history::{DepthHistoryParams, DepthHistoryResponse},
+        depth_history::{DepthHistoryParams, DepthHistoryResponse, DEPTH_POOL},
     },
-    services::{client::get_midgard_api_url, repository::depth::store_intervals},
+    services::{repository::depth::store_intervals, telemetry},
 };
 use chrono::{DateTime, Duration, Utc};
-use sqlx::MySqlPool;
+use std::sync::Arc;
 use tokio::time;
 use tracing::{error, info};
 
 pub struct DepthHistoryCron {
-    pool: MySqlPool,
+    pool: DbPool,
+    config: Arc<AppConfig>,
     interval: Interval,
     count: u32,
     last_fetch_time: Option<DateTime<Utc>>,
 }
 
 impl DepthHistoryCron {
-    pub fn new(pool: MySqlPool) -> Self {
+    pub fn new(pool: DbPool, config: Arc<AppConfig>) -> Self {
         Self {
             pool,
             interval: Interval::Hour,
-            count: 400,
-            last_fetch_time: Some(DateTime::from_timestamp(1648771200, 0).unwrap()),
+            count: config.midgard.page_size,
+            last_fetch_time: DateTime::from_timestamp(config.ingestion.history_start, 0),
+            config,
         }
     }
 
@@ -31,15 +35,15 @@ impl DepthHistoryCron {
         loop {
             if let Err(e) = self.fetch_and_store().await {
                 error!("Failed to fetch and store depth history: {}", e);
-                time::sleep(Duration::seconds(3).to_std().unwrap()).await;
+                time::sleep(self.config.midgard.request_delay()).await;
                 continue;
             }
 
-            time::sleep(Duration::seconds(3).to_std().unwrap()).await;
+            time::sleep(self.config.midgard.request_delay()).await;
         }
     }
 
-    async fn fetch_and_store(&mut self) -> Result<(), anyhow::Error> {
+    pub(crate) async fn fetch_and_store(&mut self) -> Result<(), anyhow::Error> {
         let client = reqwest::Client::new();
 
         loop {
@@ -50,8 +54,9 @@ impl DepthHistoryCron {
                 to: None,
             };
 
-            let base_url = get_midgard_api_url();
-            let mut url = reqwest::Url::parse(&format!("{}/history/depths/ETH.ETH", base_url))?;
+            let base_url = &self.config.midgard.url;
+            let mut url =
+                reqwest::Url::parse(&format!("{}/history/depths/{}", base_url, DEPTH_POOL))?;
 
             if let Some(interval) = &params.interval {
                 url.query_pairs_mut()
@@ -73,14 +78,21 @@ impl DepthHistoryCron {
                     let response_text = response.text().await?;
 
                     if response_text.contains("slow down") {
-                        tracing::warn!("Rate limited, waiting for 5 seconds before retry...");
-                        time::sleep(Duration::seconds(5).to_std().unwrap()).await;
+                        telemetry::rate_limited("depth");
+                        tracing::warn!(
+                            "Rate limited, waiting for {} seconds before retry...",
+                            self.config.midgard.rate_limit_backoff_secs
+                        );
+                        time::sleep(self.config.midgard.rate_limit_backoff()).await;
                         continue;
                     }
 
                     match serde_json::from_str::<DepthHistoryResponse>(&response_text) {
                         Ok(depth_history) => {
-                            store_intervals(&self.pool, &depth_history.intervals).await?;
+                            telemetry::intervals_fetched("depth", depth_history.intervals.len());
+                            store_intervals(&self.pool, DEPTH_POOL, &depth_history.intervals)
+                                .await?;
+                            telemetry::fetch_succeeded("depth_history");
 
                             info!(
                                 "Successfully stored {} intervals",
@@ -97,19 +109,20 @@ impl DepthHistoryCron {
                             break Ok(());
                         }
                         Err(e) => {
+                            telemetry::parse_failure("depth");
                             error!(
                                 "Failed to parse response: {}, response text (first 500 chars): {}",
                                 e,
                                 response_text.chars().take(500).collect::<String>()
                             );
-                            time::sleep(Duration::seconds(5).to_std().unwrap()).await;
+                            time::sleep(self.config.midgard.rate_limit_backoff()).await;
                             continue;
                         }
                     }
                 }
                 Err(e) => {
                     error!("Request failed: {}", e);
-                    time::sleep(Duration::seconds(5).to_std().unwrap()).await;
+                    time::sleep(self.config.midgard.rate_limit_backoff()).await;
                     continue;
                 }
             }
@@ -128,8 +141,8 @@ impl DepthHistoryCron {
         // };
 
         let client = reqwest::Client::new();
-        let base_url = get_midgard_api_url();
-        let mut url = reqwest::Url::parse(&format!("{}/history/depths/ETH.ETH", base_url))?;
+        let base_url = &self.config.midgard.url;
+        let mut url = reqwest::Url::parse(&format!("{}/history/depths/{}", base_url, DEPTH_POOL))?;
 
         url.query_pairs_mut()
             .append_pair("interval", "hour")
@@ -142,18 +155,25 @@ impl DepthHistoryCron {
                 let response_text = response.text().await?;
 
                 if response_text.contains("slow down") {
-                    tracing::warn!("Rate limited, waiting for 5 seconds before retry...");
-                    time::sleep(Duration::seconds(5).to_std().unwrap()).await;
+                    telemetry::rate_limited("depth");
+                    tracing::warn!(
+                        "Rate limited, waiting for {} seconds before retry...",
+                        self.config.midgard.rate_limit_backoff_secs
+                    );
+                    time::sleep(self.config.midgard.rate_limit_backoff()).await;
                     return Box::pin(self.fetch_latest_hour()).await;
                 }
 
                 match serde_json::from_str::<DepthHistoryResponse>(&response_text) {
                     Ok(depth_history) => {
-                        store_intervals(&self.pool, &depth_history.intervals).await?;
+                        telemetry::intervals_fetched("depth", depth_history.intervals.len());
+                        store_intervals(&self.pool, DEPTH_POOL, &depth_history.intervals).await?;
+                        telemetry::fetch_succeeded("depth_history");
                         info!("Successfully stored latest hour depth data");
                         Ok(())
                     }
                     Err(e) => {
+                        telemetry::parse_failure("depth");
                         error!("Failed to parse response: {}", e);
                         Err(anyhow::anyhow!("Failed to parse response"))
                     }
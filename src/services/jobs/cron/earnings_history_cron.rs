@@ -1,26 +1,30 @@
+use crate::config::app::AppConfig;
 use crate::core::models::common::Interval;
 use crate::core::models::earnings_history::{EarningsHistoryParams, EarningsHistoryResponse};
-use crate::services::client::get_midgard_api_url;
 use crate::services::repository::earnings::store_intervals;
+use crate::services::storage::DbPool;
+use crate::services::telemetry;
 use chrono::{DateTime, Duration, Utc};
-use sqlx::MySqlPool;
+use std::sync::Arc;
 use tokio::time;
 use tracing::{error, info};
 
 pub struct EarningsHistoryCron {
-    pool: MySqlPool,
+    pool: DbPool,
+    config: Arc<AppConfig>,
     interval: Interval,
     count: u32,
     last_fetch_time: Option<DateTime<Utc>>,
 }
 
 impl EarningsHistoryCron {
-    pub fn new(pool: MySqlPool) -> Self {
+    pub fn new(pool: DbPool, config: Arc<AppConfig>) -> Self {
         Self {
             pool,
             interval: Interval::Hour,
-            count: 400,
-            last_fetch_time: Some(DateTime::from_timestamp(1648771200, 0).unwrap()),
+            count: config.midgard.page_size,
+            last_fetch_time: DateTime::from_timestamp(config.ingestion.history_start, 0),
+            config,
         }
     }
 
@@ -28,15 +32,15 @@ impl EarningsHistoryCron {
         loop {
             if let Err(e) = self.fetch_and_store().await {
                 error!("Failed to fetch and store earnings history: {}", e);
-                time::sleep(Duration::seconds(3).to_std().unwrap()).await;
+                time::sleep(self.config.midgard.request_delay()).await;
                 continue;
             }
 
-            time::sleep(Duration::seconds(3).to_std().unwrap()).await;
+            time::sleep(self.config.midgard.request_delay()).await;
         }
     }
 
-    async fn fetch_and_store(&mut self) -> Result<(), anyhow::Error> {
+    pub(crate) async fn fetch_and_store(&mut self) -> Result<(), anyhow::Error> {
         let client = reqwest::Client::new();
 
         loop {
@@ -47,7 +51,7 @@ impl EarningsHistoryCron {
                 to: None,
             };
 
-            let base_url = get_midgard_api_url();
+            let base_url = &self.config.midgard.url;
             let mut url = reqwest::Url::parse(&format!("{}/history/earnings", base_url))?;
 
             if let Some(interval) = &params.interval {
@@ -71,14 +75,23 @@ impl EarningsHistoryCron {
 
                     // Check if we got rate limited
                     if response_text.contains("slow down") {
-                        tracing::warn!("Rate limited, waiting for 5 seconds before retry...");
-                        time::sleep(Duration::seconds(5).to_std().unwrap()).await;
+                        telemetry::rate_limited("earnings");
+                        tracing::warn!(
+                            "Rate limited, waiting for {} seconds before retry...",
+                            self.config.midgard.rate_limit_backoff_secs
+                        );
+                        time::sleep(self.config.midgard.rate_limit_backoff()).await;
                         continue;
                     }
 
                     match serde_json::from_str::<EarningsHistoryResponse>(&response_text) {
                         Ok(earnings_history) => {
+                            telemetry::intervals_fetched(
+                                "earnings",
+                                earnings_history.intervals.len(),
+                            );
                             store_intervals(&self.pool, &earnings_history.intervals).await?;
+                            telemetry::fetch_succeeded("earnings_history");
 
                             info!(
                                 "Successfully stored {} intervals",
@@ -95,19 +108,20 @@ impl EarningsHistoryCron {
                             break Ok(());
                         }
                         Err(e) => {
+                            telemetry::parse_failure("earnings");
                             error!(
                                 "Failed to parse response: {}, response text (first 500 chars): {}",
                                 e,
                                 response_text.chars().take(500).collect::<String>()
                             );
-                            time::sleep(Duration::seconds(5).to_std().unwrap()).await;
+                            time::sleep(self.config.midgard.rate_limit_backoff()).await;
                             continue;
                         }
                     }
                 }
                 Err(e) => {
                     error!("Request failed: {}", e);
-                    time::sleep(Duration::seconds(5).to_std().unwrap()).await;
+                    time::sleep(self.config.midgard.rate_limit_backoff()).await;
                     continue;
                 }
             }
@@ -127,7 +141,7 @@ impl EarningsHistoryCron {
         // };
 
         let client = reqwest::Client::new();
-        let base_url = get_midgard_api_url();
+        let base_url = &self.config.midgard.url;
         let mut url = reqwest::Url::parse(&format!("{}/history/earnings", base_url))?;
 
         url.query_pairs_mut()
@@ -141,18 +155,25 @@ impl EarningsHistoryCron {
                 let response_text = response.text().await?;
 
                 if response_text.contains("slow down") {
-                    tracing::warn!("Rate limited, waiting for 5 seconds before retry...");
-                    time::sleep(Duration::seconds(5).to_std().unwrap()).await;
+                    telemetry::rate_limited("earnings");
+                    tracing::warn!(
+                        "Rate limited, waiting for {} seconds before retry...",
+                        self.config.midgard.rate_limit_backoff_secs
+                    );
+                    time::sleep(self.config.midgard.rate_limit_backoff()).await;
                     return Box::pin(self.fetch_latest_hour()).await;
                 }
 
                 match serde_json::from_str::<EarningsHistoryResponse>(&response_text) {
                     Ok(earnings_history) => {
+                        telemetry::intervals_fetched("earnings", earnings_history.intervals.len());
                         store_intervals(&self.pool, &earnings_history.intervals).await?;
+                        telemetry::fetch_succeeded("earnings_history");
                         info!("Successfully stored latest hour earnings data");
                         Ok(())
                     }
                     Err(e) => {
+                        telemetry::parse_failure("earnings");
                         error!("Failed to parse response: {}", e);
                         Err(anyhow::anyhow!("Failed to parse response"))
                     }
@@ -1,27 +1,33 @@
+use crate::services::storage::DbPool;
+use crate::services::supervisor::Shutdown;
 use chrono::{DateTime, Duration, Utc};
-use sqlx::MySqlPool;
+use std::sync::Arc;
 use tokio::time;
 use tracing::{error, info};
 
+use crate::config::app::AppConfig;
 use crate::services::jobs::cron::{
     depth_history_cron::DepthHistoryCron, earnings_history_cron::EarningsHistoryCron,
     runepool_units_history_cron::RunepoolUnitsHistoryCron, swap_history_cron::SwapHistoryCron,
 };
 
 pub struct HourlyFetcher {
-    pool: MySqlPool,
+    pool: DbPool,
+    config: Arc<AppConfig>,
     last_run: DateTime<Utc>,
 }
 
 impl HourlyFetcher {
-    pub fn new(pool: MySqlPool) -> Self {
+    pub fn new(pool: DbPool, config: Arc<AppConfig>) -> Self {
         Self {
             pool,
+            config,
             last_run: Utc::now(),
         }
     }
 
-    pub async fn start(&mut self) -> Result<(), anyhow::Error> {
+    // Returns once a shutdown is requested, never in the middle of a fetch cycle
+    pub async fn start(&mut self, shutdown: Shutdown) -> Result<(), anyhow::Error> {
         info!("Starting hourly fetcher...");
 
         loop {
@@ -31,43 +37,57 @@ impl HourlyFetcher {
             if duration_since_last >= Duration::hours(1) {
                 info!("Starting hourly data fetch cycle...");
                 self.last_run = now;
+                self.fetch_cycle().await;
+                info!("Completed hourly data fetch cycle");
+            }
 
-                // Fetch depth history
-                let depth_pool = self.pool.clone();
-                let mut depth_cron = DepthHistoryCron::new(depth_pool);
-                if let Err(e) = depth_cron.fetch_latest_hour().await {
-                    error!("Failed to fetch depth history: {}", e);
-                }
-                time::sleep(Duration::seconds(3).to_std().unwrap()).await;
+            // Sleep for a minute before checking again
+            if !shutdown.sleep(Duration::minutes(1).to_std().unwrap()).await {
+                info!("Hourly fetcher stopped");
+                return Ok(());
+            }
+        }
+    }
 
-                // Fetch earnings history
-                let earnings_pool = self.pool.clone();
-                let mut earnings_cron = EarningsHistoryCron::new(earnings_pool);
-                if let Err(e) = earnings_cron.fetch_latest_hour().await {
-                    error!("Failed to fetch earnings history: {}", e);
-                }
-                time::sleep(Duration::seconds(3).to_std().unwrap()).await;
+    // One pass over the four datasets, a failing dataset does not stop the others. Returns how
+    // many of them failed.
+    pub async fn fetch_cycle(&self) -> usize {
+        let mut failed = 0;
 
-                // Fetch swap history
-                let swap_pool = self.pool.clone();
-                let mut swap_cron = SwapHistoryCron::new(swap_pool);
-                if let Err(e) = swap_cron.fetch_latest_hour().await {
-                    error!("Failed to fetch swap history: {}", e);
-                }
-                time::sleep(Duration::seconds(3).to_std().unwrap()).await;
+        // Fetch depth history
+        let depth_pool = self.pool.clone();
+        let mut depth_cron = DepthHistoryCron::new(depth_pool, self.config.clone());
+        if let Err(e) = depth_cron.fetch_latest_hour().await {
+            error!("Failed to fetch depth history: {}", e);
+            failed += 1;
+        }
+        time::sleep(self.config.midgard.request_delay()).await;
 
-                // Fetch runepool units history
-                let runepool_pool = self.pool.clone();
-                let mut runepool_cron = RunepoolUnitsHistoryCron::new(runepool_pool);
-                if let Err(e) = runepool_cron.fetch_latest_hour().await {
-                    error!("Failed to fetch runepool units history: {}", e);
-                }
+        // Fetch earnings history
+        let earnings_pool = self.pool.clone();
+        let mut earnings_cron = EarningsHistoryCron::new(earnings_pool, self.config.clone());
+        if let Err(e) = earnings_cron.fetch_latest_hour().await {
+            error!("Failed to fetch earnings history: {}", e);
+            failed += 1;
+        }
+        time::sleep(self.config.midgard.request_delay()).await;
 
-                info!("Completed hourly data fetch cycle");
-            }
+        // Fetch swap history
+        let swap_pool = self.pool.clone();
+        let mut swap_cron = SwapHistoryCron::new(swap_pool, self.config.clone());
+        if let Err(e) = swap_cron.fetch_latest_hour().await {
+            error!("Failed to fetch swap history: {}", e);
+            failed += 1;
+        }
+        time::sleep(self.config.midgard.request_delay()).await;
 
-            // Sleep for a minute before checking again
-            time::sleep(Duration::minutes(1).to_std().unwrap()).await;
+        // Fetch runepool units history
+        let runepool_pool = self.pool.clone();
+        let mut runepool_cron = RunepoolUnitsHistoryCron::new(runepool_pool, self.config.clone());
+        if let Err(e) = runepool_cron.fetch_latest_hour().await {
+            error!("Failed to fetch runepool units history: {}", e);
+            failed += 1;
         }
+        failed
     }
 }
@@ -0,0 +1,151 @@
+use crate::services::storage::DbPool;
+use crate::{
+    config::app::AppConfig,
+    core::models::{
+        dataset::Dataset,
+        depth_history::DEPTH_POOL,
+        job::{Job, JobStatus},
+    },
+    services::{jobs::backfill::fetch_page, repository::jobs, supervisor::Shutdown},
+};
+use chrono::{Duration, Utc};
+use std::sync::Arc;
+use std::{env, fs, process};
+use tracing::{error, info};
+
+// A page is stored every few seconds, a running job without progress for this long lost its runner
+const STALE_AFTER_MINUTES: i64 = 10;
+
+// `host:pid`, unique among the instances sharing the jobs table
+fn runner_id() -> String {
+    let host = env::var("HOSTNAME")
+        .ok()
+        .or_else(|| fs::read_to_string("/etc/hostname").ok())
+        .map(|host| host.trim().to_string())
+        .filter(|host| !host.is_empty())
+        .unwrap_or_else(|| "localhost".to_string());
+    format!("{}:{}", host, process::id())
+}
+
+// Runs the backfills queued through the admin api, one at a time
+pub struct JobRunner {
+    pool: DbPool,
+    config: Arc<AppConfig>,
+    owner: String,
+}
+
+impl JobRunner {
+    pub fn new(pool: DbPool, config: Arc<AppConfig>) -> Self {
+        Self {
+            pool,
+            config,
+            owner: runner_id(),
+        }
+    }
+
+    // What the jobs this runner claims are marked with
+    pub fn owner(&self) -> &str {
+        &self.owner
+    }
+
+    pub async fn start(&mut self, shutdown: Shutdown) -> Result<(), anyhow::Error> {
+        while !shutdown.is_requested() {
+            // Checked on every pass, the job of a runner that just died only goes stale minutes
+            // later. None of this runner's own jobs is running at this point.
+            let stale_before = Utc::now() - Duration::minutes(STALE_AFTER_MINUTES);
+            match jobs::requeue_stale(&self.pool, &self.owner, stale_before).await {
+                Ok(0) => {}
+                Ok(requeued) => info!("Resuming {} interrupted jobs", requeued),
+                Err(e) => error!("Failed to requeue stale jobs: {}", e),
+            }
+
+            let idle = match jobs::claim_next(&self.pool, &self.owner).await {
+                Ok(Some(job)) => {
+                    self.run(job, &shutdown).await;
+                    false
+                }
+                Ok(None) => true,
+                Err(e) => {
+                    error!("Failed to poll the jobs table: {}", e);
+                    true
+                }
+            };
+            if idle {
+                shutdown
+                    .sleep(Duration::seconds(10).to_std().unwrap())
+                    .await;
+            }
+        }
+        info!("Job runner stopped");
+        Ok(())
+    }
+
+    // Runs a job claimed for `owner` to the end, the outcome is recorded on the job row. A
+    // shutdown stops it between two pages and puts it back in the queue, where it resumes from its
+    // cursor.
+    pub async fn run(&self, job: Job, shutdown: &Shutdown) {
+        info!(
+            "Starting {} backfill job {} from {} to {}",
+            job.dataset, job.id, job.from_time, job.to_time
+        );
+
+        let (status, error) = match self.backfill(&job, shutdown).await {
+            Ok(()) if shutdown.is_requested() => {
+                info!("Backfill job {} interrupted by the shutdown", job.id);
+                if let Err(e) = jobs::release(&self.pool, job.id, &self.owner).await {
+                    error!("Failed to requeue job {}: {}", job.id, e);
+                }
+                return;
+            }
+            Ok(()) => (JobStatus::Succeeded, None),
+            Err(e) => {
+                error!("Backfill job {} failed: {}", job.id, e);
+                (JobStatus::Failed, Some(e.to_string()))
+            }
+        };
+
+        if let Err(e) = jobs::finish(&self.pool, job.id, status, error.as_deref()).await {
+            error!("Failed to record the end of job {}: {}", job.id, e);
+        }
+    }
+
+    async fn backfill(&self, job: &Job, shutdown: &Shutdown) -> Result<(), anyhow::Error> {
+        let dataset = Dataset::try_from(job.dataset.clone()).map_err(anyhow::Error::msg)?;
+        let pool_name = job.pool.as_deref().unwrap_or(DEPTH_POOL);
+        let client = reqwest::Client::new();
+        let mut cursor = job.cursor_time.unwrap_or(job.from_time);
+
+        while cursor < job.to_time {
+            let status = jobs::status(&self.pool, job.id).await?;
+            if status.map(JobStatus::try_from) != Some(Ok(JobStatus::Running)) {
+                info!("Job {} was cancelled", job.id);
+                return Ok(());
+            }
+
+            let page = fetch_page(
+                &client,
+                &self.pool,
+                &self.config.midgard,
+                dataset,
+                pool_name,
+                cursor,
+                job.to_time,
+            )
+            .await?;
+
+            // Nothing newer on Midgard's side yet
+            let Some(last_end_time) = page.last_end_time.filter(|end| *end > cursor) else {
+                break;
+            };
+            cursor = last_end_time;
+            jobs::update_progress(&self.pool, job.id, cursor, page.fetched).await?;
+
+            if !shutdown.sleep(self.config.midgard.request_delay()).await {
+                return Ok(());
+            }
+        }
+
+        info!("Backfill job {} done", job.id);
+        Ok(())
+    }
+}
@@ -1,5 +1,7 @@
+pub mod actions_cron;
 pub mod depth_history_cron;
 pub mod earnings_history_cron;
 pub mod hourly_fetcher;
+pub mod job_runner;
 pub mod runepool_units_history_cron;
 pub mod swap_history_cron;
@@ -1,29 +1,33 @@
+use crate::services::storage::DbPool;
 use crate::{
+    config::app::AppConfig,
     core::models::{
         common::Interval,
         runepool_units_history::{RunepoolUnitsHistoryParams, RunepoolUnitsHistoryResponse},
     },
-    services::{client::get_midgard_api_url, repository::runepool::store_intervals},
+    services::{repository::runepool::store_intervals, telemetry},
 };
 use chrono::{DateTime, Duration, Utc};
-use sqlx::MySqlPool;
+use std::sync::Arc;
 use tokio::time;
 use tracing::{error, info};
 
 pub struct RunepoolUnitsHistoryCron {
-    pool: MySqlPool,
+    pool: DbPool,
+    config: Arc<AppConfig>,
     interval: Interval,
     count: u32,
     last_fetch_time: Option<DateTime<Utc>>,
 }
 
 impl RunepoolUnitsHistoryCron {
-    pub fn new(pool: MySqlPool) -> Self {
+    pub fn new(pool: DbPool, config: Arc<AppConfig>) -> Self {
         Self {
             pool,
             interval: Interval::Hour,
-            count: 400,
-            last_fetch_time: Some(DateTime::from_timestamp(1648771200, 0).unwrap()),
+            count: config.midgard.page_size,
+            last_fetch_time: DateTime::from_timestamp(config.ingestion.history_start, 0),
+            config,
         }
     }
 
@@ -31,14 +35,14 @@ impl RunepoolUnitsHistoryCron {
         loop {
             if let Err(e) = self.fetch_and_store().await {
                 error!("Failed to fetch and store runepool units history: {}", e);
-                time::sleep(Duration::seconds(3).to_std().unwrap()).await;
+                time::sleep(self.config.midgard.request_delay()).await;
                 continue;
             }
 
-            time::sleep(Duration::seconds(3).to_std().unwrap()).await;
+            time::sleep(self.config.midgard.request_delay()).await;
         }
     }
-    async fn fetch_and_store(&mut self) -> Result<(), anyhow::Error> {
+    pub(crate) async fn fetch_and_store(&mut self) -> Result<(), anyhow::Error> {
         let client = reqwest::Client::new();
 
         loop {
@@ -49,7 +53,7 @@ impl RunepoolUnitsHistoryCron {
                 to: None,
             };
 
-            let base_url = get_midgard_api_url();
+            let base_url = &self.config.midgard.url;
             let mut url = reqwest::Url::parse(&format!("{}/history/runepool", base_url))?;
 
             if let Some(interval) = &params.interval {
@@ -72,14 +76,23 @@ impl RunepoolUnitsHistoryCron {
                     let response_text = response.text().await?;
 
                     if response_text.contains("slow down") {
-                        tracing::warn!("Rate limited, waiting for 5 seconds before retry...");
-                        time::sleep(Duration::seconds(5).to_std().unwrap()).await;
+                        telemetry::rate_limited("runepool");
+                        tracing::warn!(
+                            "Rate limited, waiting for {} seconds before retry...",
+                            self.config.midgard.rate_limit_backoff_secs
+                        );
+                        time::sleep(self.config.midgard.rate_limit_backoff()).await;
                         continue;
                     }
 
                     match serde_json::from_str::<RunepoolUnitsHistoryResponse>(&response_text) {
                         Ok(runepool_history) => {
+                            telemetry::intervals_fetched(
+                                "runepool",
+                                runepool_history.intervals.len(),
+                            );
                             store_intervals(&self.pool, &runepool_history.intervals).await?;
+                            telemetry::fetch_succeeded("runepool_units_history");
 
                             info!(
                                 "Successfully stored {} intervals",
@@ -96,19 +109,20 @@ impl RunepoolUnitsHistoryCron {
                             break Ok(());
                         }
                         Err(e) => {
+                            telemetry::parse_failure("runepool");
                             error!(
                                 "Failed to parse response: {}, response text (first 500 chars): {}",
                                 e,
                                 response_text.chars().take(500).collect::<String>()
                             );
-                            time::sleep(Duration::seconds(5).to_std().unwrap()).await;
+                            time::sleep(self.config.midgard.rate_limit_backoff()).await;
                             continue;
                         }
                     }
                 }
                 Err(e) => {
                     error!("Request failed: {}", e);
-                    time::sleep(Duration::seconds(5).to_std().unwrap()).await;
+                    time::sleep(self.config.midgard.rate_limit_backoff()).await;
                     continue;
                 }
             }
@@ -128,7 +142,7 @@ impl RunepoolUnitsHistoryCron {
         // };
 
         let client = reqwest::Client::new();
-        let base_url = get_midgard_api_url();
+        let base_url = &self.config.midgard.url;
         let mut url = reqwest::Url::parse(&format!("{}/history/runepool", base_url))?;
 
         url.query_pairs_mut()
@@ -142,18 +156,25 @@ impl RunepoolUnitsHistoryCron {
                 let response_text = response.text().await?;
 
                 if response_text.contains("slow down") {
-                    tracing::warn!("Rate limited, waiting for 5 seconds before retry...");
-                    time::sleep(Duration::seconds(5).to_std().unwrap()).await;
+                    telemetry::rate_limited("runepool");
+                    tracing::warn!(
+                        "Rate limited, waiting for {} seconds before retry...",
+                        self.config.midgard.rate_limit_backoff_secs
+                    );
+                    time::sleep(self.config.midgard.rate_limit_backoff()).await;
                     return Box::pin(self.fetch_latest_hour()).await;
                 }
 
                 match serde_json::from_str::<RunepoolUnitsHistoryResponse>(&response_text) {
                     Ok(runepool_history) => {
+                        telemetry::intervals_fetched("runepool", runepool_history.intervals.len());
                         store_intervals(&self.pool, &runepool_history.intervals).await?;
+                        telemetry::fetch_succeeded("runepool_units_history");
                         info!("Successfully stored latest hour runepool units data");
                         Ok(())
                     }
                     Err(e) => {
+                        telemetry::parse_failure("runepool");
                         error!("Failed to parse response: {}", e);
                         Err(anyhow::anyhow!("Failed to parse response"))
                     }
@@ -1,30 +1,34 @@
+use crate::services::storage::DbPool;
 use chrono::{DateTime, Duration, Utc};
-use sqlx::MySqlPool;
+use std::sync::Arc;
 use tokio::time;
 use tracing::{error, info};
 
 use crate::{
+    config::app::AppConfig,
     core::models::{
         common::Interval,
         swap_history::{SwapHistoryParams, SwapHistoryResponse},
     },
-    services::{client::get_midgard_api_url, repository::swap::store_intervals},
+    services::{repository::swap::store_intervals, telemetry},
 };
 
 pub struct SwapHistoryCron {
-    pool: MySqlPool,
+    pool: DbPool,
+    config: Arc<AppConfig>,
     interval: Interval,
     count: u32,
     last_fetch_time: Option<DateTime<Utc>>,
 }
 
 impl SwapHistoryCron {
-    pub fn new(pool: MySqlPool) -> Self {
+    pub fn new(pool: DbPool, config: Arc<AppConfig>) -> Self {
         Self {
             pool,
             interval: Interval::Hour,
-            count: 400,
-            last_fetch_time: Some(DateTime::from_timestamp(1648771200, 0).unwrap()),
+            count: config.midgard.page_size,
+            last_fetch_time: DateTime::from_timestamp(config.ingestion.history_start, 0),
+            config,
         }
     }
 
@@ -32,15 +36,15 @@ impl SwapHistoryCron {
         loop {
             if let Err(e) = self.fetch_and_store().await {
                 error!("Failed to fetch and store swap history: {}", e);
-                time::sleep(Duration::seconds(3).to_std().unwrap()).await;
+                time::sleep(self.config.midgard.request_delay()).await;
                 continue;
             }
 
-            time::sleep(Duration::seconds(3).to_std().unwrap()).await;
+            time::sleep(self.config.midgard.request_delay()).await;
         }
     }
 
-    async fn fetch_and_store(&mut self) -> Result<(), anyhow::Error> {
+    pub(crate) async fn fetch_and_store(&mut self) -> Result<(), anyhow::Error> {
         let client = reqwest::Client::new();
 
         loop {
@@ -51,7 +55,7 @@ impl SwapHistoryCron {
                 to: None,
             };
 
-            let base_url = get_midgard_api_url();
+            let base_url = &self.config.midgard.url;
             let mut url = reqwest::Url::parse(&format!("{}/history/swaps", base_url))?;
 
             if let Some(interval) = &params.interval {
@@ -74,14 +78,20 @@ impl SwapHistoryCron {
                     let response_text = response.text().await?;
 
                     if response_text.contains("slow down") {
-                        tracing::warn!("Rate limited, waiting for 5 seconds before retry...");
-                        time::sleep(Duration::seconds(5).to_std().unwrap()).await;
+                        telemetry::rate_limited("swap");
+                        tracing::warn!(
+                            "Rate limited, waiting for {} seconds before retry...",
+                            self.config.midgard.rate_limit_backoff_secs
+                        );
+                        time::sleep(self.config.midgard.rate_limit_backoff()).await;
                         continue;
                     }
 
                     match serde_json::from_str::<SwapHistoryResponse>(&response_text) {
                         Ok(swap_history) => {
+                            telemetry::intervals_fetched("swap", swap_history.intervals.len());
                             store_intervals(&self.pool, &swap_history.intervals).await?;
+                            telemetry::fetch_succeeded("swap_history");
 
                             info!(
                                 "Successfully stored {} intervals",
@@ -98,19 +108,20 @@ impl SwapHistoryCron {
                             break Ok(());
                         }
                         Err(e) => {
+                            telemetry::parse_failure("swap");
                             error!(
                                 "Failed to parse response: {}, response text (first 500 chars): {}",
                                 e,
                                 response_text.chars().take(500).collect::<String>()
                             );
-                            time::sleep(Duration::seconds(5).to_std().unwrap()).await;
+                            time::sleep(self.config.midgard.rate_limit_backoff()).await;
                             continue;
                         }
                     }
                 }
                 Err(e) => {
                     error!("Request failed: {}", e);
-                    time::sleep(Duration::seconds(5).to_std().unwrap()).await;
+                    time::sleep(self.config.midgard.rate_limit_backoff()).await;
                     continue;
                 }
             }
@@ -130,7 +141,7 @@ impl SwapHistoryCron {
         // };
 
         let client = reqwest::Client::new();
-        let base_url = get_midgard_api_url();
+        let base_url = &self.config.midgard.url;
         let mut url = reqwest::Url::parse(&format!("{}/history/swaps", base_url))?;
 
         url.query_pairs_mut()
@@ -144,18 +155,25 @@ impl SwapHistoryCron {
                 let response_text = response.text().await?;
 
                 if response_text.contains("slow down") {
-                    tracing::warn!("Rate limited, waiting for 5 seconds before retry...");
-                    time::sleep(Duration::seconds(5).to_std().unwrap()).await;
+                    telemetry::rate_limited("swap");
+                    tracing::warn!(
+                        "Rate limited, waiting for {} seconds before retry...",
+                        self.config.midgard.rate_limit_backoff_secs
+                    );
+                    time::sleep(self.config.midgard.rate_limit_backoff()).await;
                     return Box::pin(self.fetch_latest_hour()).await;
                 }
 
                 match serde_json::from_str::<SwapHistoryResponse>(&response_text) {
                     Ok(swap_history) => {
+                        telemetry::intervals_fetched("swap", swap_history.intervals.len());
                         store_intervals(&self.pool, &swap_history.intervals).await?;
+                        telemetry::fetch_succeeded("swap_history");
                         info!("Successfully stored latest hour swap data");
                         Ok(())
                     }
                     Err(e) => {
+                        telemetry::parse_failure("swap");
                         error!("Failed to parse response: {}", e);
                         Err(anyhow::anyhow!("Failed to parse response"))
                     }
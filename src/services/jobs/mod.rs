@@ -1 +1,3 @@
+pub mod backfill;
 pub mod cron;
+pub mod verify;
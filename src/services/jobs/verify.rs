@@ -0,0 +1,147 @@
+// Consistency checks over what is stored for one dataset: every hour between the start of the
+// range and the newest data Midgard can have should be covered by exactly one interval
+use chrono::{DateTime, Duration, Utc};
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Problem {
+    // Nothing stored between the two timestamps
+    Gap {
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    },
+    // An interval starts before the previous one ended, usually a duplicate
+    Overlap {
+        start_time: DateTime<Utc>,
+        previous_end_time: DateTime<Utc>,
+    },
+    // Hourly intervals are an hour long, anything else was fetched with the wrong interval
+    Length {
+        start_time: DateTime<Utc>,
+        end_time: DateTime<Utc>,
+    },
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Problem::Gap { from, to } => write!(
+                f,
+                "gap of {} hours from {} to {}",
+                (*to - *from).num_hours(),
+                from,
+                to
+            ),
+            Problem::Overlap {
+                start_time,
+                previous_end_time,
+            } => write!(
+                f,
+                "interval starting at {} overlaps the previous one ending at {}",
+                start_time, previous_end_time
+            ),
+            Problem::Length {
+                start_time,
+                end_time,
+            } => write!(
+                f,
+                "interval from {} to {} is not one hour long",
+                start_time, end_time
+            ),
+        }
+    }
+}
+
+// `intervals` must be sorted by start time. Coverage is expected up to `until`, the end of the
+// range or now for a range that is not over yet; a missing last hour is not reported since the
+// hourly fetcher may simply not have run yet.
+pub fn check_intervals(
+    from: DateTime<Utc>,
+    until: DateTime<Utc>,
+    intervals: &[(DateTime<Utc>, DateTime<Utc>)],
+) -> Vec<Problem> {
+    let mut problems = Vec::new();
+    let mut covered_until = from;
+
+    for &(start_time, end_time) in intervals {
+        if end_time - start_time != Duration::hours(1) {
+            problems.push(Problem::Length {
+                start_time,
+                end_time,
+            });
+        }
+
+        if start_time > covered_until {
+            problems.push(Problem::Gap {
+                from: covered_until,
+                to: start_time,
+            });
+        } else if start_time < covered_until {
+            problems.push(Problem::Overlap {
+                start_time,
+                previous_end_time: covered_until,
+            });
+        }
+        covered_until = covered_until.max(end_time);
+    }
+
+    if until - covered_until > Duration::hours(1) {
+        problems.push(Problem::Gap {
+            from: covered_until,
+            to: until,
+        });
+    }
+
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hour(n: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp(1_700_000_000 / 3600 * 3600 + n * 3600, 0).unwrap()
+    }
+
+    fn intervals(starts: &[i64]) -> Vec<(DateTime<Utc>, DateTime<Utc>)> {
+        starts.iter().map(|&n| (hour(n), hour(n + 1))).collect()
+    }
+
+    #[test]
+    fn contiguous_intervals_are_fine() {
+        assert!(check_intervals(hour(0), hour(4), &intervals(&[0, 1, 2, 3])).is_empty());
+        // The newest hour may not be fetched yet
+        assert!(check_intervals(hour(0), hour(4), &intervals(&[0, 1, 2])).is_empty());
+    }
+
+    #[test]
+    fn gaps_and_overlaps_are_reported() {
+        let mut stored = intervals(&[0, 1, 4, 5]);
+        stored.insert(3, (hour(4), hour(5)));
+
+        assert_eq!(
+            check_intervals(hour(0), hour(6), &stored),
+            vec![
+                Problem::Gap {
+                    from: hour(2),
+                    to: hour(4)
+                },
+                Problem::Overlap {
+                    start_time: hour(4),
+                    previous_end_time: hour(5)
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn an_empty_range_is_one_gap() {
+        assert_eq!(
+            check_intervals(hour(0), hour(24), &[]),
+            vec![Problem::Gap {
+                from: hour(0),
+                to: hour(24)
+            }]
+        );
+    }
+}
@@ -1,4 +1,7 @@
-pub mod client;
+pub mod cache;
 pub mod jobs;
 pub mod repository;
 pub mod spawn;
+pub mod storage;
+pub mod supervisor;
+pub mod telemetry;
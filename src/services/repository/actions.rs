@@ -0,0 +1,192 @@
+use super::{Order, Page};
+use crate::core::models::actions::{Action, ActionTransaction, ActionsQueryParams};
+use crate::services::storage::{DbPool, Storage, Unsigned};
+use chrono::{DateTime, Utc};
+use serde_json::{self, json, Value as JsonValue};
+use sqlx::{prelude::FromRow, types::Json};
+
+// Same story as earnings, the JSON columns need their own row struct
+#[derive(Debug, FromRow)]
+struct ActionRow {
+    date: DateTime<Utc>,
+    #[sqlx(try_from = "Unsigned")]
+    height: u64,
+    in_txs: Json<Vec<ActionTransaction>>,
+    out_txs: Json<Vec<ActionTransaction>>,
+    pools: Json<Vec<String>>,
+    status: String,
+    action_type: String,
+    metadata: JsonValue,
+}
+
+impl From<ActionRow> for Action {
+    fn from(row: ActionRow) -> Self {
+        Action {
+            date: row.date,
+            height: row.height,
+            in_txs: row.in_txs.0,
+            out_txs: row.out_txs.0,
+            pools: row.pools.0,
+            status: row.status,
+            action_type: row.action_type,
+            metadata: row.metadata,
+        }
+    }
+}
+
+// Key an action is stored under, a tx can hold several actions of one type at different heights
+const ACTION_KEY: &[&str] = &["tx_id", "action_type", "height"];
+
+// Midgard keeps updating an action until it is final, a pending swap gets its outbound txs and
+// status later on
+const ACTION_UPDATES: &[&str] = &[
+    "status",
+    "tx_ids",
+    "out_txs",
+    "addresses",
+    "metadata",
+    "swap_slip",
+    "liquidity_fee",
+];
+
+// Inserts new actions and refreshes the ones already stored. Returns how many of them were new.
+pub async fn store_actions(pool: &DbPool, actions: &[Action]) -> Result<usize, sqlx::Error> {
+    let mut inserted = 0;
+
+    for action in actions {
+        let Some(tx_id) = action.tx_id() else {
+            tracing::warn!(
+                "Skipping {} action at height {} without a tx id",
+                action.action_type,
+                action.height
+            );
+            continue;
+        };
+
+        // Only counted, the upsert below is what keeps concurrent writers from duplicating rows
+        let mut count = pool.query("SELECT COUNT(*) FROM actions WHERE tx_id = ");
+        count
+            .push_bind(tx_id)
+            .push(" AND action_type = ")
+            .push_bind(&action.action_type)
+            .push(" AND height = ")
+            .push_bind(action.height);
+        let (count,): (i64,) = pool.fetch_one(&count).await?;
+
+        let tx_ids_json = serde_json::to_value(action.tx_ids())
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        let pools_json = serde_json::to_value(&action.pools)
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        let in_txs_json = serde_json::to_value(&action.in_txs)
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        let out_txs_json = serde_json::to_value(&action.out_txs)
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        let addresses_json = serde_json::to_value(action.addresses())
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+
+        let mut upsert = pool.query(
+            "INSERT INTO actions (
+                tx_id, tx_ids, action_type, status, date, height, pools, in_txs,
+                out_txs, addresses, metadata, swap_slip, liquidity_fee
+            ) VALUES ",
+        );
+        upsert
+            .push_tuple([
+                tx_id.into(),
+                tx_ids_json.into(),
+                (&action.action_type).into(),
+                (&action.status).into(),
+                action.date.into(),
+                action.height.into(),
+                pools_json.into(),
+                in_txs_json.into(),
+                out_txs_json.into(),
+                addresses_json.into(),
+                action.metadata.clone().into(),
+                action.swap_slip().into(),
+                action.liquidity_fee().into(),
+            ])
+            .push(pool.backend().upsert_clause(ACTION_KEY, ACTION_UPDATES));
+        pool.execute(&upsert).await?;
+
+        if count == 0 {
+            inserted += 1;
+        }
+    }
+
+    Ok(inserted)
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ActionFilter {
+    pub tx_id: Option<String>,
+    // Actions where the address sent or received funds
+    pub address: Option<String>,
+    pub pool: Option<String>,
+    pub action_type: Option<String>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+}
+
+impl From<&ActionsQueryParams> for ActionFilter {
+    fn from(params: &ActionsQueryParams) -> Self {
+        let (from, to) = params.parse_date_range().unzip();
+        Self {
+            tx_id: params.txid.clone(),
+            address: params.address.clone(),
+            pool: params.pool.clone(),
+            action_type: params.action_type.clone(),
+            from,
+            to,
+        }
+    }
+}
+
+// Matching actions by date, and by height within the same date
+pub async fn query_actions(
+    pool: &DbPool,
+    filter: &ActionFilter,
+    order: Order,
+    page: Page,
+) -> Result<Vec<Action>, sqlx::Error> {
+    let mut query = pool.query(
+        "SELECT date, height, in_txs, out_txs, pools, status, action_type, metadata \
+         FROM actions WHERE 1=1",
+    );
+    if let Some(tx_id) = &filter.tx_id {
+        query
+            .push(" AND ")
+            .push_json_contains("tx_ids", json!(tx_id));
+    }
+    if let Some(address) = &filter.address {
+        query
+            .push(" AND ")
+            .push_json_contains("addresses", json!(address));
+    }
+    if let Some(pool_name) = &filter.pool {
+        query
+            .push(" AND ")
+            .push_json_contains("pools", json!(pool_name));
+    }
+    if let Some(action_type) = &filter.action_type {
+        query.push(" AND action_type = ").push_bind(action_type);
+    }
+    if let Some(from) = filter.from {
+        query.push(" AND date >= ").push_bind(from);
+    }
+    if let Some(to) = filter.to {
+        query.push(" AND date <= ").push_bind(to);
+    }
+
+    let order = order.as_sql();
+    query
+        .push(" ORDER BY date ")
+        .push(order)
+        .push(", height ")
+        .push(order);
+    page.push(&mut query);
+    tracing::debug!("Executing SQL query: {}", query.sql());
+
+    let rows: Vec<ActionRow> = pool.fetch_all(&query).await?;
+    Ok(rows.into_iter().map(Action::from).collect())
+}
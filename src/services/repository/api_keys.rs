@@ -0,0 +1,11 @@
+use crate::core::models::api_key::ApiKey;
+use crate::services::storage::{DbPool, Storage};
+
+pub async fn find_by_hash(pool: &DbPool, key_hash: &str) -> Result<Option<ApiKey>, sqlx::Error> {
+    let mut query = pool.query(
+        "SELECT id, name, requests_per_minute, enabled, is_admin FROM api_keys WHERE key_hash = ",
+    );
+    query.push_bind(key_hash);
+
+    pool.fetch_optional(&query).await
+}
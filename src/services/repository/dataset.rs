@@ -0,0 +1,100 @@
+use crate::core::models::dataset::Dataset;
+use crate::services::storage::{DbPool, Storage};
+use chrono::{DateTime, Utc};
+use sqlx::prelude::FromRow;
+use tracing::debug;
+
+#[derive(Debug, FromRow)]
+pub struct ColumnValue {
+    pub start_time: DateTime<Utc>,
+    pub end_time: DateTime<Utc>,
+    pub value: f64,
+}
+
+// The first `limit` stored intervals of one numeric column, oldest first. `column` must come from
+// `Dataset::numeric_column` since it is pushed into the query as is.
+pub async fn fetch_column_values(
+    pool: &DbPool,
+    dataset: Dataset,
+    column: &'static str,
+    pool_name: Option<&str>,
+    date_range: Option<(DateTime<Utc>, DateTime<Utc>)>,
+    limit: usize,
+) -> Result<Vec<ColumnValue>, sqlx::Error> {
+    let mut query = pool.query(format!(
+        "SELECT start_time, end_time, CAST({} AS {}) AS value FROM {} WHERE 1=1",
+        column,
+        pool.backend().double_type(),
+        dataset.table()
+    ));
+    if let Some(pool_name) = pool_name {
+        query.push(" AND pool = ");
+        query.push_bind(pool_name);
+    }
+    if let Some((start, end)) = date_range {
+        query.push(" AND start_time >= ");
+        query.push_bind(start);
+        query.push(" AND end_time <= ");
+        query.push_bind(end);
+    }
+    query
+        .push(" ORDER BY start_time ASC LIMIT ")
+        .push_bind(limit as i64);
+    debug!("Executing SQL query: {}", query.sql());
+
+    pool.fetch_all(&query).await
+}
+
+// Start of the oldest and of the newest stored interval (of one pool for depth), `None` when
+// nothing is stored
+pub async fn start_time_bounds(
+    pool: &DbPool,
+    dataset: Dataset,
+    pool_name: Option<&str>,
+) -> Result<Option<(DateTime<Utc>, DateTime<Utc>)>, sqlx::Error> {
+    let mut query = pool.query(format!(
+        "SELECT MIN(start_time), MAX(start_time) FROM {} WHERE 1=1",
+        dataset.table()
+    ));
+    if let Some(pool_name) = pool_name {
+        query.push(" AND pool = ").push_bind(pool_name);
+    }
+    let (first, last) = pool
+        .fetch_one::<(Option<DateTime<Utc>>, Option<DateTime<Utc>>)>(&query)
+        .await?;
+    Ok(first.zip(last))
+}
+
+// End of the newest stored interval, `None` when the table is empty
+pub async fn latest_end_time(
+    pool: &DbPool,
+    dataset: Dataset,
+) -> Result<Option<DateTime<Utc>>, sqlx::Error> {
+    let query = pool.query(format!("SELECT MAX(end_time) FROM {}", dataset.table()));
+    let (latest,) = pool.fetch_one::<(Option<DateTime<Utc>>,)>(&query).await?;
+    Ok(latest)
+}
+
+// Start and end of every interval that starts in `[from, to)`, oldest first
+pub async fn interval_bounds(
+    pool: &DbPool,
+    dataset: Dataset,
+    pool_name: Option<&str>,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<Vec<(DateTime<Utc>, DateTime<Utc>)>, sqlx::Error> {
+    let mut query = pool.query(format!(
+        "SELECT start_time, end_time FROM {} WHERE start_time >= ",
+        dataset.table()
+    ));
+    query.push_bind(from);
+    query.push(" AND start_time < ");
+    query.push_bind(to);
+    if let Some(pool_name) = pool_name {
+        query.push(" AND pool = ");
+        query.push_bind(pool_name);
+    }
+    query.push(" ORDER BY start_time ASC");
+
+    pool.fetch_all(&query).await
+}
@@ -1,52 +1,232 @@
-use crate::core::models::depth_history::DepthInterval;
-use sqlx::MySqlPool;
+use super::{
+    aggregate_column, paged_intervals_query, push_time_bounds, ColumnAggregate, IntervalFilter,
+    Page, Sort,
+};
+use crate::core::models::dataset::Dataset;
+use crate::core::models::depth_history::{
+    DepthHistoryQueryParams, DepthInterval, MetaStats, DEPTH_POOL,
+};
+use crate::services::storage::{DbPool, Query, Storage};
+use crate::services::{cache, telemetry};
+use chrono::{DateTime, Utc};
 
 pub async fn store_intervals(
-    pool: &MySqlPool,
+    pool: &DbPool,
+    pool_name: &str,
     intervals: &[DepthInterval],
 ) -> Result<(), sqlx::Error> {
+    let mut inserted = 0;
+    let mut skipped = 0;
     for interval in intervals {
         // Check if record exists
-        let exists = sqlx::query!(
-            r#"
-            SELECT COUNT(*) as count 
-            FROM `depth_intervals` 
-            WHERE start_time = ? AND end_time = ?
-            "#,
-            interval.start_time.naive_utc(),
-            interval.end_time.naive_utc()
-        )
-        .fetch_one(pool)
-        .await?
-        .count
-            > 0;
+        let mut count = pool.query("SELECT COUNT(*) FROM depth_intervals WHERE pool = ");
+        count
+            .push_bind(pool_name)
+            .push(" AND start_time = ")
+            .push_bind(interval.start_time)
+            .push(" AND end_time = ")
+            .push_bind(interval.end_time);
+        let (count,): (i64,) = pool.fetch_one(&count).await?;
+        let exists = count > 0;
 
         if !exists {
-            sqlx::query!(
-                r#"
-                INSERT INTO `depth_intervals` (
-                    start_time, end_time, asset_depth, asset_price,
+            let mut insert = pool.query(
+                "INSERT INTO depth_intervals (
+                    pool, start_time, end_time, asset_depth, asset_price,
                     asset_price_usd, liquidity_units, luvi, members_count,
                     rune_depth, synth_supply, synth_units, units
-                ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
-                "#,
-                interval.start_time.naive_utc(),
-                interval.end_time.naive_utc(),
-                interval.asset_depth as i64,
-                interval.asset_price,
-                interval.asset_price_usd,
-                interval.liquidity_units as i64,
-                interval.luvi,
-                interval.members_count as i32,
-                interval.rune_depth as i64,
-                interval.synth_supply as i64,
-                interval.synth_units as i64,
-                interval.units as i64
-            )
-            .execute(pool)
-            .await?;
+                ) VALUES ",
+            );
+            insert.push_tuple([
+                pool_name.into(),
+                interval.start_time.into(),
+                interval.end_time.into(),
+                interval.asset_depth.into(),
+                interval.asset_price.into(),
+                interval.asset_price_usd.into(),
+                interval.liquidity_units.into(),
+                interval.luvi.into(),
+                interval.members_count.into(),
+                interval.rune_depth.into(),
+                interval.synth_supply.into(),
+                interval.synth_units.into(),
+                interval.units.into(),
+            ]);
+            pool.execute(&insert).await?;
+            inserted += 1;
+        } else {
+            skipped += 1;
         }
     }
 
+    telemetry::intervals_stored(Dataset::Depth.as_str(), inserted, skipped);
+
+    if inserted > 0 {
+        cache::invalidate(Dataset::Depth);
+    }
+
     Ok(())
 }
+
+#[derive(Debug, Clone, Default)]
+pub struct DepthFilter {
+    pub pool: Option<String>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    pub liquidity_gt: Option<u64>,
+}
+
+impl From<&DepthHistoryQueryParams> for DepthFilter {
+    fn from(params: &DepthHistoryQueryParams) -> Self {
+        let (from, to) = params.parse_date_range().unzip();
+        Self {
+            pool: Some(params.pool.clone().unwrap_or(DEPTH_POOL.to_string())),
+            from,
+            to,
+            liquidity_gt: params.liquidity_gt,
+        }
+    }
+}
+
+impl IntervalFilter for DepthFilter {
+    fn push_conditions(&self, query: &mut Query) {
+        if let Some(pool_name) = &self.pool {
+            query.push(" AND pool = ").push_bind(pool_name);
+        }
+        push_time_bounds(query, self.from, self.to);
+        if let Some(min_liquidity) = self.liquidity_gt {
+            query
+                .push(" AND liquidity_units > ")
+                .push_bind(min_liquidity);
+        }
+    }
+}
+
+pub async fn query_intervals(
+    pool: &DbPool,
+    filter: &DepthFilter,
+    sort: Sort,
+    page: Option<Page>,
+) -> Result<Vec<DepthInterval>, sqlx::Error> {
+    let query = paged_intervals_query(pool, Dataset::Depth, filter, sort, page);
+    pool.fetch_all(&query).await
+}
+
+pub async fn aggregate(
+    pool: &DbPool,
+    filter: &DepthFilter,
+    column: &'static str,
+) -> Result<ColumnAggregate, sqlx::Error> {
+    aggregate_column(pool, Dataset::Depth, filter, column).await
+}
+
+// Start and end state of the given intervals, oldest first
+pub fn meta(intervals: &[DepthInterval]) -> Option<MetaStats> {
+    let (first, last) = (intervals.first()?, intervals.last()?);
+    Some(MetaStats {
+        start_time: first.start_time,
+        end_time: last.end_time,
+        start_asset_depth: first.asset_depth,
+        end_asset_depth: last.asset_depth,
+        start_rune_depth: first.rune_depth,
+        end_rune_depth: last.rune_depth,
+        start_lp_units: first.liquidity_units,
+        end_lp_units: last.liquidity_units,
+        start_member_count: first.members_count,
+        end_member_count: last.members_count,
+        start_synth_units: first.synth_units,
+        end_synth_units: last.synth_units,
+        // TODO Calculate this
+        luvi_increase: 0.0,
+        price_shift_loss: 0.0,
+    })
+}
+
+// End of the newest stored interval of the pool, `None` when there is none
+pub async fn latest_end_time(
+    pool: &DbPool,
+    pool_name: &str,
+) -> Result<Option<DateTime<Utc>>, sqlx::Error> {
+    let mut query = pool.query("SELECT MAX(end_time) FROM depth_intervals WHERE pool = ");
+    query.push_bind(pool_name);
+    let (latest,) = pool.fetch_one::<(Option<DateTime<Utc>>,)>(&query).await?;
+    Ok(latest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{test_pool, Order};
+    use super::*;
+
+    fn interval(hour: i64, asset_depth: u128) -> DepthInterval {
+        DepthInterval {
+            asset_depth,
+            asset_price: 1.0,
+            asset_price_usd: 1.0,
+            end_time: DateTime::from_timestamp((hour + 1) * 3600, 0).unwrap(),
+            liquidity_units: asset_depth * 2,
+            luvi: 0.0,
+            members_count: 10,
+            rune_depth: asset_depth,
+            start_time: DateTime::from_timestamp(hour * 3600, 0).unwrap(),
+            synth_supply: 0,
+            synth_units: 0,
+            units: asset_depth * 2,
+        }
+    }
+
+    #[tokio::test]
+    async fn reads_filtered_sorted_and_paged_intervals() {
+        let pool = test_pool().await;
+        let stored: Vec<DepthInterval> = (0..4)
+            .map(|hour| interval(hour, 100 + hour as u128))
+            .collect();
+        store_intervals(&pool, "BTC.BTC", &stored).await.unwrap();
+        store_intervals(&pool, "ETH.ETH", &[interval(0, 999)])
+            .await
+            .unwrap();
+
+        let filter = DepthFilter {
+            pool: Some("BTC.BTC".to_string()),
+            from: DateTime::from_timestamp(3600, 0),
+            ..Default::default()
+        };
+        let newest_first = query_intervals(
+            &pool,
+            &filter,
+            Sort::by(Dataset::Depth, "asset_depth", Order::Desc),
+            Some(Page::first(2)),
+        )
+        .await
+        .unwrap();
+        let depths: Vec<u128> = newest_first.iter().map(|i| i.asset_depth).collect();
+        assert_eq!(depths, [103, 102]);
+
+        let all = query_intervals(&pool, &filter, Sort::start_time(Order::Asc), None)
+            .await
+            .unwrap();
+        let meta = meta(&all).unwrap();
+        assert_eq!((meta.start_asset_depth, meta.end_asset_depth), (101, 103));
+        assert!(super::meta(&[]).is_none());
+
+        let summary = aggregate(&pool, &filter, "asset_depth").await.unwrap();
+        assert_eq!(summary.intervals, 3);
+        assert_eq!(summary.sum, Some(306.0));
+        assert_eq!(summary.max, Some(103.0));
+        assert_eq!(summary.start_time, DateTime::from_timestamp(3600, 0));
+
+        // Backfilled pools stay out of the history unless asked for
+        let params: DepthHistoryQueryParams =
+            serde_json::from_value(serde_json::json!({})).unwrap();
+        let default_pool = query_intervals(
+            &pool,
+            &DepthFilter::from(&params),
+            Sort::start_time(Order::Asc),
+            None,
+        )
+        .await
+        .unwrap();
+        let depths: Vec<u128> = default_pool.iter().map(|i| i.asset_depth).collect();
+        assert_eq!(depths, [999]);
+    }
+}
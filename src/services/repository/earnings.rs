@@ -1,54 +1,217 @@
-use crate::core::models::earnings_history::IntervalData;
-use serde_json;
-use sqlx::MySqlPool;
-
-pub async fn store_intervals(
-    pool: &MySqlPool,
-    intervals: &[IntervalData],
-) -> Result<(), sqlx::Error> {
+use super::{
+    aggregate_column, paged_intervals_query, push_time_bounds, ColumnAggregate, IntervalFilter,
+    Page, Sort,
+};
+use crate::core::models::dataset::Dataset;
+use crate::core::models::earnings_history::{
+    EarningsHistoryQueryParams, IntervalData, MetaStats, Pool,
+};
+use crate::services::storage::{Amount, DbPool, Query, Storage};
+use crate::services::{cache, telemetry};
+use chrono::{DateTime, Utc};
+use serde_json::{self, json};
+use sqlx::{prelude::FromRow, types::Json};
+
+// `IntervalData` can't be read directly, its pools are a JSON column
+#[derive(Debug, FromRow)]
+struct IntervalRow {
+    avg_node_count: f64,
+    #[sqlx(try_from = "Amount")]
+    block_rewards: u128,
+    #[sqlx(try_from = "Amount")]
+    bonding_earnings: u128,
+    #[sqlx(try_from = "Amount")]
+    earnings: u128,
+    end_time: DateTime<Utc>,
+    #[sqlx(try_from = "Amount")]
+    liquidity_earnings: u128,
+    #[sqlx(try_from = "Amount")]
+    liquidity_fees: u128,
+    rune_price_usd: f64,
+    start_time: DateTime<Utc>,
+    pools: Json<Vec<Pool>>,
+}
+
+impl From<IntervalRow> for IntervalData {
+    fn from(row: IntervalRow) -> Self {
+        IntervalData {
+            avg_node_count: row.avg_node_count,
+            block_rewards: row.block_rewards,
+            bonding_earnings: row.bonding_earnings,
+            earnings: row.earnings,
+            end_time: row.end_time,
+            liquidity_earnings: row.liquidity_earnings,
+            liquidity_fees: row.liquidity_fees,
+            pools: row.pools.0,
+            rune_price_usd: row.rune_price_usd,
+            start_time: row.start_time,
+        }
+    }
+}
+
+pub async fn store_intervals(pool: &DbPool, intervals: &[IntervalData]) -> Result<(), sqlx::Error> {
+    let mut inserted = 0;
+    let mut skipped = 0;
     for interval in intervals {
         // Check if record exists
-        let exists = sqlx::query!(
-            r#"
-            SELECT COUNT(*) as count
-            FROM `earning_intervals`
-            WHERE start_time = ? AND end_time = ?
-            "#,
-            interval.start_time.naive_utc(),
-            interval.end_time.naive_utc()
-        )
-        .fetch_one(pool)
-        .await?
-        .count
-            > 0;
+        let mut count = pool.query("SELECT COUNT(*) FROM earning_intervals WHERE start_time = ");
+        count
+            .push_bind(interval.start_time)
+            .push(" AND end_time = ")
+            .push_bind(interval.end_time);
+        let (count,): (i64,) = pool.fetch_one(&count).await?;
+        let exists = count > 0;
 
         if !exists {
-            let pools_json = serde_json::to_string(&interval.pools)
+            let pools_json = serde_json::to_value(&interval.pools)
                 .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
-            sqlx::query!(
-                r#"
-                INSERT INTO `earning_intervals` (
+            let mut insert = pool.query(
+                "INSERT INTO earning_intervals (
                     start_time, end_time, avg_node_count, block_rewards,
                     bonding_earnings, earnings, liquidity_earnings,
                     liquidity_fees, rune_price_usd, pools
-                ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
-                "#,
-                interval.start_time.naive_utc(),
-                interval.end_time.naive_utc(),
-                interval.avg_node_count,
-                interval.block_rewards as i64,
-                interval.bonding_earnings as i64,
-                interval.earnings as i64,
-                interval.liquidity_earnings as i64,
-                interval.liquidity_fees as i64,
-                interval.rune_price_usd,
-                pools_json,
-            )
-            .execute(pool)
-            .await?;
+                ) VALUES ",
+            );
+            insert.push_tuple([
+                interval.start_time.into(),
+                interval.end_time.into(),
+                interval.avg_node_count.into(),
+                interval.block_rewards.into(),
+                interval.bonding_earnings.into(),
+                interval.earnings.into(),
+                interval.liquidity_earnings.into(),
+                interval.liquidity_fees.into(),
+                interval.rune_price_usd.into(),
+                pools_json.into(),
+            ]);
+            pool.execute(&insert).await?;
+            inserted += 1;
+        } else {
+            skipped += 1;
         }
     }
 
+    telemetry::intervals_stored(Dataset::Earnings.as_str(), inserted, skipped);
+
+    if inserted > 0 {
+        cache::invalidate(Dataset::Earnings);
+    }
+
     Ok(())
 }
+
+#[derive(Debug, Clone, Default)]
+pub struct EarningsFilter {
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    pub earnings_gt: Option<u64>,
+    pub block_rewards_gt: Option<u64>,
+    pub node_count_gt: Option<f64>,
+    // Only intervals with earnings for this pool
+    pub pool: Option<String>,
+}
+
+impl From<&EarningsHistoryQueryParams> for EarningsFilter {
+    fn from(params: &EarningsHistoryQueryParams) -> Self {
+        let (from, to) = params.parse_date_range().unzip();
+        Self {
+            from,
+            to,
+            earnings_gt: params.earnings_gt,
+            block_rewards_gt: params.block_rewards_gt,
+            node_count_gt: params.node_count_gt,
+            pool: params.pool.clone(),
+        }
+    }
+}
+
+impl IntervalFilter for EarningsFilter {
+    fn push_conditions(&self, query: &mut Query) {
+        push_time_bounds(query, self.from, self.to);
+        if let Some(min_earnings) = self.earnings_gt {
+            query.push(" AND earnings > ").push_bind(min_earnings);
+        }
+        if let Some(min_rewards) = self.block_rewards_gt {
+            query.push(" AND block_rewards > ").push_bind(min_rewards);
+        }
+        if let Some(min_nodes) = self.node_count_gt {
+            query.push(" AND avg_node_count > ").push_bind(min_nodes);
+        }
+        if let Some(pool_name) = &self.pool {
+            query
+                .push(" AND ")
+                .push_json_contains("pools", json!([{ "pool": pool_name }]));
+        }
+    }
+}
+
+pub async fn query_intervals(
+    pool: &DbPool,
+    filter: &EarningsFilter,
+    sort: Sort,
+    page: Option<Page>,
+) -> Result<Vec<IntervalData>, sqlx::Error> {
+    let query = paged_intervals_query(pool, Dataset::Earnings, filter, sort, page);
+    let rows: Vec<IntervalRow> = pool.fetch_all(&query).await?;
+    Ok(rows.into_iter().map(IntervalData::from).collect())
+}
+
+pub async fn aggregate(
+    pool: &DbPool,
+    filter: &EarningsFilter,
+    column: &'static str,
+) -> Result<ColumnAggregate, sqlx::Error> {
+    aggregate_column(pool, Dataset::Earnings, filter, column).await
+}
+
+// The figures of the last of the given intervals
+pub fn meta(intervals: &[IntervalData]) -> Option<MetaStats> {
+    let last = intervals.last()?;
+    Some(MetaStats {
+        avg_node_count: last.avg_node_count,
+        block_rewards: last.block_rewards,
+        bonding_earnings: last.bonding_earnings,
+        earnings: last.earnings,
+        end_time: last.end_time,
+        liquidity_earnings: last.liquidity_earnings,
+        liquidity_fees: last.liquidity_fees,
+        pools: last.pools.clone(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::test_pool;
+    use super::*;
+    use crate::core::models::earnings_history::EarningsHistoryResponse;
+
+    #[tokio::test]
+    async fn aggregates_the_filtered_intervals() {
+        let pool = test_pool().await;
+        let response: EarningsHistoryResponse = serde_json::from_str(include_str!(
+            "../../../tests/fixtures/midgard/earnings.json"
+        ))
+        .unwrap();
+        store_intervals(&pool, &response.intervals).await.unwrap();
+        let [first, second] = [&response.intervals[0], &response.intervals[1]];
+
+        let all = aggregate(&pool, &EarningsFilter::default(), "earnings")
+            .await
+            .unwrap();
+        assert_eq!(all.intervals, 2);
+        assert_eq!(all.sum, Some((first.earnings + second.earnings) as f64));
+        assert_eq!(all.max, Some(first.earnings.max(second.earnings) as f64));
+        assert_eq!(all.start_time, Some(first.start_time));
+        assert_eq!(all.end_time, Some(second.end_time));
+
+        let filter = EarningsFilter {
+            from: Some(second.start_time),
+            ..Default::default()
+        };
+        let last = aggregate(&pool, &filter, "earnings").await.unwrap();
+        assert_eq!(last.intervals, 1);
+        assert_eq!(last.average, Some(second.earnings as f64));
+        assert_eq!(last.start_time, Some(second.start_time));
+    }
+}
@@ -0,0 +1,202 @@
+use crate::core::models::dataset::Dataset;
+use crate::core::models::job::{Job, JobStatus};
+use crate::services::storage::{DbPool, Storage};
+use chrono::{DateTime, Utc};
+
+const JOB_COLUMNS: &str = "id, dataset, pool, from_time, to_time, status, cursor_time, \
+    intervals_fetched, error, requested_by, created_at, started_at, finished_at, owner, \
+    heartbeat_at";
+
+pub async fn create_backfill(
+    pool: &DbPool,
+    dataset: Dataset,
+    pool_name: Option<&str>,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+    requested_by: Option<&str>,
+) -> Result<i64, sqlx::Error> {
+    let mut query =
+        pool.query("INSERT INTO jobs (dataset, pool, from_time, to_time, requested_by) VALUES ");
+    query.push_tuple([
+        dataset.to_string().into(),
+        pool_name.into(),
+        from.into(),
+        to.into(),
+        requested_by.into(),
+    ]);
+
+    pool.insert(&query).await
+}
+
+pub async fn find(pool: &DbPool, id: i64) -> Result<Option<Job>, sqlx::Error> {
+    let mut query = pool.query(format!("SELECT {} FROM jobs WHERE id = ", JOB_COLUMNS));
+    query.push_bind(id);
+
+    pool.fetch_optional(&query).await
+}
+
+// Newest first
+pub async fn list(
+    pool: &DbPool,
+    status: Option<JobStatus>,
+    limit: u32,
+) -> Result<Vec<Job>, sqlx::Error> {
+    let mut query = pool.query(format!("SELECT {} FROM jobs WHERE 1=1", JOB_COLUMNS));
+    if let Some(status) = status {
+        query.push(" AND status = ").push_bind(status.as_str());
+    }
+    query.push(" ORDER BY id DESC LIMIT ").push_bind(limit);
+
+    pool.fetch_all(&query).await
+}
+
+// Returns false when the job does not exist or already finished
+pub async fn cancel(pool: &DbPool, id: i64) -> Result<bool, sqlx::Error> {
+    let mut query = pool.query("UPDATE jobs SET status = ");
+    query
+        .push_bind(JobStatus::Cancelled.as_str())
+        .push(", finished_at = ")
+        .push_bind(Utc::now())
+        .push(" WHERE id = ")
+        .push_bind(id)
+        .push(" AND status IN ")
+        .push_tuple([
+            JobStatus::Queued.as_str().into(),
+            JobStatus::Running.as_str().into(),
+        ]);
+
+    Ok(pool.execute(&query).await? > 0)
+}
+
+// Marks the oldest queued job as running for `owner`. The conditional update makes sure only one
+// runner gets a job even when several instances poll the table.
+pub async fn claim_next(pool: &DbPool, owner: &str) -> Result<Option<Job>, sqlx::Error> {
+    loop {
+        let mut query = pool.query("SELECT id FROM jobs WHERE status = ");
+        query
+            .push_bind(JobStatus::Queued.as_str())
+            .push(" ORDER BY id ASC LIMIT 1");
+        let Some((id,)) = pool.fetch_optional::<(i64,)>(&query).await? else {
+            return Ok(None);
+        };
+
+        if let Some(job) = claim(pool, id, owner).await? {
+            return Ok(Some(job));
+        }
+    }
+}
+
+// Marks one queued job as running for `owner`, `None` when it is not queued (anymore)
+pub async fn claim(pool: &DbPool, id: i64, owner: &str) -> Result<Option<Job>, sqlx::Error> {
+    let now = Utc::now();
+    let mut query = pool.query("UPDATE jobs SET status = ");
+    query
+        .push_bind(JobStatus::Running.as_str())
+        .push(", owner = ")
+        .push_bind(owner)
+        .push(", heartbeat_at = ")
+        .push_bind(now)
+        .push(", started_at = COALESCE(started_at, ")
+        .push_bind(now)
+        .push(") WHERE id = ")
+        .push_bind(id)
+        .push(" AND status = ")
+        .push_bind(JobStatus::Queued.as_str());
+    let claimed = pool.execute(&query).await? > 0;
+
+    if claimed {
+        find(pool, id).await
+    } else {
+        Ok(None)
+    }
+}
+
+// Running jobs whose runner stopped reporting progress before `stale_before`, or that belonged to
+// `owner` (a runner restarting in the same process). They resume from their cursor.
+pub async fn requeue_stale(
+    pool: &DbPool,
+    owner: &str,
+    stale_before: DateTime<Utc>,
+) -> Result<u64, sqlx::Error> {
+    let mut query = pool.query("UPDATE jobs SET status = ");
+    query
+        .push_bind(JobStatus::Queued.as_str())
+        .push(", owner = NULL WHERE status = ")
+        .push_bind(JobStatus::Running.as_str())
+        .push(" AND (owner = ")
+        .push_bind(owner)
+        .push(" OR heartbeat_at IS NULL OR heartbeat_at < ")
+        .push_bind(stale_before)
+        .push(")");
+
+    pool.execute(&query).await
+}
+
+// Hands a job `owner` is running back to the queue, for a runner that stops before the job is done
+pub async fn release(pool: &DbPool, id: i64, owner: &str) -> Result<bool, sqlx::Error> {
+    let mut query = pool.query("UPDATE jobs SET status = ");
+    query
+        .push_bind(JobStatus::Queued.as_str())
+        .push(", owner = NULL WHERE id = ")
+        .push_bind(id)
+        .push(" AND status = ")
+        .push_bind(JobStatus::Running.as_str())
+        .push(" AND owner = ")
+        .push_bind(owner);
+
+    Ok(pool.execute(&query).await? > 0)
+}
+
+pub async fn status(pool: &DbPool, id: i64) -> Result<Option<String>, sqlx::Error> {
+    let mut query = pool.query("SELECT status FROM jobs WHERE id = ");
+    query.push_bind(id);
+
+    Ok(pool
+        .fetch_optional::<(String,)>(&query)
+        .await?
+        .map(|(status,)| status))
+}
+
+// Also the runner's heartbeat, it is called after every page
+pub async fn update_progress(
+    pool: &DbPool,
+    id: i64,
+    cursor: DateTime<Utc>,
+    fetched: u32,
+) -> Result<(), sqlx::Error> {
+    let mut query = pool.query("UPDATE jobs SET cursor_time = ");
+    query
+        .push_bind(cursor)
+        .push(", intervals_fetched = intervals_fetched + ")
+        .push_bind(fetched)
+        .push(", heartbeat_at = ")
+        .push_bind(Utc::now())
+        .push(" WHERE id = ")
+        .push_bind(id);
+    pool.execute(&query).await?;
+
+    Ok(())
+}
+
+// Only running jobs are finished, a job cancelled in the meantime stays cancelled
+pub async fn finish(
+    pool: &DbPool,
+    id: i64,
+    status: JobStatus,
+    error: Option<&str>,
+) -> Result<(), sqlx::Error> {
+    let mut query = pool.query("UPDATE jobs SET status = ");
+    query
+        .push_bind(status.as_str())
+        .push(", error = ")
+        .push_bind(error)
+        .push(", finished_at = ")
+        .push_bind(Utc::now())
+        .push(" WHERE id = ")
+        .push_bind(id)
+        .push(" AND status = ")
+        .push_bind(JobStatus::Running.as_str());
+    pool.execute(&query).await?;
+
+    Ok(())
+}
@@ -1,4 +1,232 @@
+pub mod actions;
+pub mod api_keys;
+pub mod dataset;
 pub mod depth;
 pub mod earnings;
+pub mod jobs;
 pub mod runepool;
 pub mod swap;
+
+use crate::core::models::common::{DEFAULT_PAGE_SIZE, MAX_PAGE_SIZE};
+use crate::core::models::dataset::Dataset;
+use crate::services::storage::{DbPool, Query, Storage, Unsigned};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::prelude::FromRow;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Order {
+    #[default]
+    Asc,
+    Desc,
+}
+
+impl Order {
+    // The `order` query param, anything but `desc` sorts ascending
+    pub fn from_param(order: Option<&str>) -> Self {
+        match order {
+            Some("desc") => Order::Desc,
+            _ => Order::Asc,
+        }
+    }
+
+    fn as_sql(&self) -> &'static str {
+        match self {
+            Order::Asc => "ASC",
+            Order::Desc => "DESC",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sort {
+    pub column: &'static str,
+    pub order: Order,
+}
+
+impl Sort {
+    pub fn start_time(order: Order) -> Self {
+        Self {
+            column: "start_time",
+            order,
+        }
+    }
+
+    // Sorts by a time or numeric column of the dataset, anything else falls back to the start
+    // time so a user supplied name never reaches the SQL
+    pub fn by(dataset: Dataset, column: &str, order: Order) -> Self {
+        let column = match column {
+            "start_time" => "start_time",
+            "end_time" => "end_time",
+            column => dataset.numeric_column(column).unwrap_or("start_time"),
+        };
+        Self { column, order }
+    }
+
+    fn push(&self, query: &mut Query) {
+        query
+            .push(" ORDER BY ")
+            .push(self.column)
+            .push(" ")
+            .push(self.order.as_sql());
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Page {
+    pub limit: u32,
+    pub offset: u32,
+}
+
+impl Page {
+    // The `page` and `limit` query params, the limit is capped at `MAX_PAGE_SIZE`
+    pub fn new(page: Option<u32>, limit: Option<u32>) -> Self {
+        let limit = limit.unwrap_or(DEFAULT_PAGE_SIZE).min(MAX_PAGE_SIZE);
+        Self {
+            limit,
+            offset: page.unwrap_or(0).saturating_mul(limit),
+        }
+    }
+
+    pub fn first(limit: u32) -> Self {
+        Self { limit, offset: 0 }
+    }
+
+    fn push(&self, query: &mut Query) {
+        query
+            .push(" LIMIT ")
+            .push_bind(self.limit)
+            .push(" OFFSET ")
+            .push_bind(self.offset);
+    }
+}
+
+// Conditions of an interval read, pushed after a `WHERE 1=1`
+pub trait IntervalFilter {
+    fn push_conditions(&self, query: &mut Query);
+}
+
+// `start_time >= from` and `end_time <= to`, the way every history route reads a date range
+fn push_time_bounds(query: &mut Query, from: Option<DateTime<Utc>>, to: Option<DateTime<Utc>>) {
+    if let Some(from) = from {
+        query.push(" AND start_time >= ").push_bind(from);
+    }
+    if let Some(to) = to {
+        query.push(" AND end_time <= ").push_bind(to);
+    }
+}
+
+// Every row of `dataset` matching `filter`, sorted but not paged. Exports stream this as is.
+pub fn intervals_query(
+    pool: &DbPool,
+    dataset: Dataset,
+    filter: &impl IntervalFilter,
+    sort: Sort,
+) -> Query {
+    let mut query = pool.query(format!("SELECT * FROM {} WHERE 1=1", dataset.table()));
+    filter.push_conditions(&mut query);
+    sort.push(&mut query);
+    query
+}
+
+fn paged_intervals_query(
+    pool: &DbPool,
+    dataset: Dataset,
+    filter: &impl IntervalFilter,
+    sort: Sort,
+    page: Option<Page>,
+) -> Query {
+    let mut query = intervals_query(pool, dataset, filter, sort);
+    if let Some(page) = page {
+        page.push(&mut query);
+    }
+    tracing::debug!("Executing SQL query: {}", query.sql());
+    query
+}
+
+// Summary of one numeric column over the intervals matching a filter. The figures are doubles on
+// every backend, the sum of a day of volumes does not fit the column type in general.
+#[derive(Debug, Clone, PartialEq, Serialize, FromRow)]
+pub struct ColumnAggregate {
+    #[sqlx(try_from = "Unsigned")]
+    pub intervals: u64,
+    pub start_time: Option<DateTime<Utc>>,
+    pub end_time: Option<DateTime<Utc>>,
+    pub sum: Option<f64>,
+    pub average: Option<f64>,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+}
+
+// `column` must come from `Dataset::numeric_column` since it is pushed into the query as is
+async fn aggregate_column(
+    pool: &DbPool,
+    dataset: Dataset,
+    filter: &impl IntervalFilter,
+    column: &'static str,
+) -> Result<ColumnAggregate, sqlx::Error> {
+    let double = pool.backend().double_type();
+    let mut query = pool.query(format!(
+        "SELECT COUNT(*) AS intervals, MIN(start_time) AS start_time, MAX(end_time) AS end_time, \
+         CAST(SUM({column}) AS {double}) AS sum, CAST(AVG({column}) AS {double}) AS average, \
+         CAST(MIN({column}) AS {double}) AS min, CAST(MAX({column}) AS {double}) AS max \
+         FROM {} WHERE 1=1",
+        dataset.table()
+    ));
+    filter.push_conditions(&mut query);
+
+    pool.fetch_one(&query).await
+}
+
+// An empty, migrated in-memory database
+#[cfg(test)]
+pub async fn test_pool() -> DbPool {
+    use crate::config::app::DatabaseConfig;
+
+    let config = DatabaseConfig {
+        url: "sqlite::memory:".to_string(),
+        ..DatabaseConfig::default()
+    };
+    let pool = crate::services::storage::connect(&config).await.unwrap();
+    pool.run_migrations().await.unwrap();
+    pool
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sort_only_takes_known_columns() {
+        assert_eq!(
+            Sort::by(Dataset::Swap, "total_volume", Order::Desc).column,
+            "total_volume"
+        );
+        assert_eq!(
+            Sort::by(Dataset::Depth, "end_time", Order::Asc).column,
+            "end_time"
+        );
+        assert_eq!(
+            Sort::by(Dataset::Depth, "1; DROP TABLE depth_intervals", Order::Asc).column,
+            "start_time"
+        );
+    }
+
+    #[test]
+    fn pages_are_capped() {
+        assert_eq!(
+            Page::new(None, None),
+            Page {
+                limit: DEFAULT_PAGE_SIZE,
+                offset: 0
+            }
+        );
+        assert_eq!(
+            Page::new(Some(2), Some(10_000)),
+            Page {
+                limit: MAX_PAGE_SIZE,
+                offset: 2 * MAX_PAGE_SIZE
+            }
+        );
+    }
+}
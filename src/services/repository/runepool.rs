@@ -1,42 +1,148 @@
-use crate::core::models::runepool_units_history::RunepoolUnitsInterval;
-use sqlx::MySqlPool;
+use super::{
+    aggregate_column, paged_intervals_query, push_time_bounds, ColumnAggregate, IntervalFilter,
+    Page, Sort,
+};
+use crate::core::models::dataset::Dataset;
+use crate::core::models::runepool_units_history::{
+    MetaStats, RunepoolUnitsHistoryQueryParams, RunepoolUnitsInterval,
+};
+use crate::services::storage::{DbPool, Query, Storage};
+use crate::services::{cache, telemetry};
+use chrono::{DateTime, Utc};
 
 pub async fn store_intervals(
-    pool: &MySqlPool,
+    pool: &DbPool,
     intervals: &[RunepoolUnitsInterval],
 ) -> Result<(), sqlx::Error> {
+    let mut inserted = 0;
+    let mut skipped = 0;
     for interval in intervals {
         // Check if record exists
-        let exists = sqlx::query!(
-            r#"
-            SELECT COUNT(*) as count 
-            FROM `runepool_unit_intervals` 
-            WHERE start_time = ? AND end_time = ?
-            "#,
-            interval.start_time.naive_utc(),
-            interval.end_time.naive_utc()
-        )
-        .fetch_one(pool)
-        .await?
-        .count
-            > 0;
+        let mut count =
+            pool.query("SELECT COUNT(*) FROM runepool_unit_intervals WHERE start_time = ");
+        count
+            .push_bind(interval.start_time)
+            .push(" AND end_time = ")
+            .push_bind(interval.end_time);
+        let (count,): (i64,) = pool.fetch_one(&count).await?;
+        let exists = count > 0;
 
         if !exists {
-            sqlx::query!(
-                r#"
-                INSERT INTO `runepool_unit_intervals` (
-                    start_time, end_time, count, units
-                ) VALUES (?, ?, ?, ?)
-                "#,
-                interval.start_time.naive_utc(),
-                interval.end_time.naive_utc(),
-                interval.count as i64,
-                interval.units as i64,
-            )
-            .execute(pool)
-            .await?;
+            let mut insert = pool.query(
+                "INSERT INTO runepool_unit_intervals (start_time, end_time, count, units) VALUES ",
+            );
+            insert.push_tuple([
+                interval.start_time.into(),
+                interval.end_time.into(),
+                interval.count.into(),
+                interval.units.into(),
+            ]);
+            pool.execute(&insert).await?;
+            inserted += 1;
+        } else {
+            skipped += 1;
         }
     }
 
+    telemetry::intervals_stored(Dataset::Runepool.as_str(), inserted, skipped);
+
+    if inserted > 0 {
+        cache::invalidate(Dataset::Runepool);
+    }
+
     Ok(())
 }
+
+#[derive(Debug, Clone, Default)]
+pub struct RunepoolFilter {
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    pub units_gt: Option<u64>,
+}
+
+impl From<&RunepoolUnitsHistoryQueryParams> for RunepoolFilter {
+    fn from(params: &RunepoolUnitsHistoryQueryParams) -> Self {
+        let (from, to) = params.parse_date_range().unzip();
+        Self {
+            from,
+            to,
+            units_gt: params.units_gt,
+        }
+    }
+}
+
+impl IntervalFilter for RunepoolFilter {
+    fn push_conditions(&self, query: &mut Query) {
+        push_time_bounds(query, self.from, self.to);
+        if let Some(min_units) = self.units_gt {
+            query.push(" AND units > ").push_bind(min_units);
+        }
+    }
+}
+
+pub async fn query_intervals(
+    pool: &DbPool,
+    filter: &RunepoolFilter,
+    sort: Sort,
+    page: Option<Page>,
+) -> Result<Vec<RunepoolUnitsInterval>, sqlx::Error> {
+    let query = paged_intervals_query(pool, Dataset::Runepool, filter, sort, page);
+    pool.fetch_all(&query).await
+}
+
+pub async fn aggregate(
+    pool: &DbPool,
+    filter: &RunepoolFilter,
+    column: &'static str,
+) -> Result<ColumnAggregate, sqlx::Error> {
+    aggregate_column(pool, Dataset::Runepool, filter, column).await
+}
+
+// Start and end state of the given intervals, oldest first
+pub fn meta(intervals: &[RunepoolUnitsInterval]) -> Option<MetaStats> {
+    let (first, last) = (intervals.first()?, intervals.last()?);
+    Some(MetaStats {
+        start_time: first.start_time,
+        end_time: last.end_time,
+        start_count: first.count,
+        end_count: last.count,
+        start_units: first.units,
+        end_units: last.units,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::test_pool;
+    use super::*;
+    use crate::core::models::runepool_units_history::RunepoolUnitsHistoryResponse;
+
+    #[tokio::test]
+    async fn aggregates_the_filtered_intervals() {
+        let pool = test_pool().await;
+        let response: RunepoolUnitsHistoryResponse = serde_json::from_str(include_str!(
+            "../../../tests/fixtures/midgard/runepool.json"
+        ))
+        .unwrap();
+        store_intervals(&pool, &response.intervals).await.unwrap();
+        let [first, second] = [&response.intervals[0], &response.intervals[1]];
+
+        let all = aggregate(&pool, &RunepoolFilter::default(), "units")
+            .await
+            .unwrap();
+        assert_eq!(all.intervals, 2);
+        assert_eq!(all.sum, Some((first.units + second.units) as f64));
+        assert_eq!(all.max, Some(first.units.max(second.units) as f64));
+        assert_eq!(all.start_time, Some(first.start_time));
+        assert_eq!(all.end_time, Some(second.end_time));
+
+        let filter = RunepoolFilter {
+            from: Some(second.start_time),
+            ..Default::default()
+        };
+        let last = aggregate(&pool, &filter, "units").await.unwrap();
+        assert_eq!(last.intervals, 1);
+        assert_eq!(last.average, Some(second.units as f64));
+        assert_eq!(last.start_time, Some(second.start_time));
+    }
+}
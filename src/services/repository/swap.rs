@@ -1,30 +1,29 @@
-use crate::core::models::swap_history::SwapInterval;
-use sqlx::MySqlPool;
+use super::{
+    aggregate_column, paged_intervals_query, push_time_bounds, ColumnAggregate, IntervalFilter,
+    Page, Sort,
+};
+use crate::core::models::dataset::Dataset;
+use crate::core::models::swap_history::{SwapHistoryQueryParams, SwapInterval, SwapMeta};
+use crate::services::storage::{DbPool, Query, Storage};
+use crate::services::{cache, telemetry};
+use chrono::{DateTime, Utc};
 
-pub async fn store_intervals(
-    pool: &MySqlPool,
-    intervals: &[SwapInterval],
-) -> Result<(), sqlx::Error> {
+pub async fn store_intervals(pool: &DbPool, intervals: &[SwapInterval]) -> Result<(), sqlx::Error> {
+    let mut inserted = 0;
+    let mut skipped = 0;
     for interval in intervals {
         // Check if record exists
-        let exists = sqlx::query!(
-            r#"
-            SELECT COUNT(*) as count 
-            FROM `swap_intervals` 
-            WHERE start_time = ? AND end_time = ?
-            "#,
-            interval.start_time.naive_utc(),
-            interval.end_time.naive_utc()
-        )
-        .fetch_one(pool)
-        .await?
-        .count
-            > 0;
+        let mut count = pool.query("SELECT COUNT(*) FROM swap_intervals WHERE start_time = ");
+        count
+            .push_bind(interval.start_time)
+            .push(" AND end_time = ")
+            .push_bind(interval.end_time);
+        let (count,): (i64,) = pool.fetch_one(&count).await?;
+        let exists = count > 0;
 
         if !exists {
-            sqlx::query!(
-                r#"
-                INSERT INTO `swap_intervals` (
+            let mut insert = pool.query(
+                "INSERT INTO swap_intervals (
                     start_time, end_time, average_slip, from_trade_average_slip,
                     from_trade_count, from_trade_fees, from_trade_volume,
                     from_trade_volume_usd, rune_price_usd, synth_mint_average_slip,
@@ -37,51 +36,196 @@ pub async fn store_intervals(
                     to_trade_average_slip, to_trade_count, to_trade_fees,
                     to_trade_volume, to_trade_volume_usd, total_count, total_fees,
                     total_volume, total_volume_usd
-                ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
-                "#,
-                interval.start_time.naive_utc(),
-                interval.end_time.naive_utc(),
-                interval.average_slip,
-                interval.from_trade_average_slip,
-                interval.from_trade_count as i64,
-                interval.from_trade_fees as i64,
-                interval.from_trade_volume as i64,
-                interval.from_trade_volume_usd as i64,
-                interval.rune_price_usd,
-                interval.synth_mint_average_slip,
-                interval.synth_mint_count as i64,
-                interval.synth_mint_fees as i64,
-                interval.synth_mint_volume as i64,
-                interval.synth_mint_volume_usd as i64,
-                interval.synth_redeem_average_slip,
-                interval.synth_redeem_count as i64,
-                interval.synth_redeem_fees as i64,
-                interval.synth_redeem_volume as i64,
-                interval.synth_redeem_volume_usd as i64,
-                interval.to_asset_average_slip,
-                interval.to_asset_count as i64,
-                interval.to_asset_fees as i64,
-                interval.to_asset_volume as i64,
-                interval.to_asset_volume_usd as i64,
-                interval.to_rune_average_slip,
-                interval.to_rune_count as i64,
-                interval.to_rune_fees as i64,
-                interval.to_rune_volume as i64,
-                interval.to_rune_volume_usd as i64,
-                interval.to_trade_average_slip,
-                interval.to_trade_count as i64,
-                interval.to_trade_fees as i64,
-                interval.to_trade_volume as i64,
-                interval.to_trade_volume_usd as i64,
-                interval.total_count as i64,
-                interval.total_fees as i64,
-                interval.total_volume as i64,
-                interval.total_volume_usd as i64,
-            )
-            .execute(pool)
-            .await?;
+                ) VALUES ",
+            );
+            insert.push_tuple([
+                interval.start_time.into(),
+                interval.end_time.into(),
+                interval.average_slip.into(),
+                interval.from_trade_average_slip.into(),
+                interval.from_trade_count.into(),
+                interval.from_trade_fees.into(),
+                interval.from_trade_volume.into(),
+                interval.from_trade_volume_usd.into(),
+                interval.rune_price_usd.into(),
+                interval.synth_mint_average_slip.into(),
+                interval.synth_mint_count.into(),
+                interval.synth_mint_fees.into(),
+                interval.synth_mint_volume.into(),
+                interval.synth_mint_volume_usd.into(),
+                interval.synth_redeem_average_slip.into(),
+                interval.synth_redeem_count.into(),
+                interval.synth_redeem_fees.into(),
+                interval.synth_redeem_volume.into(),
+                interval.synth_redeem_volume_usd.into(),
+                interval.to_asset_average_slip.into(),
+                interval.to_asset_count.into(),
+                interval.to_asset_fees.into(),
+                interval.to_asset_volume.into(),
+                interval.to_asset_volume_usd.into(),
+                interval.to_rune_average_slip.into(),
+                interval.to_rune_count.into(),
+                interval.to_rune_fees.into(),
+                interval.to_rune_volume.into(),
+                interval.to_rune_volume_usd.into(),
+                interval.to_trade_average_slip.into(),
+                interval.to_trade_count.into(),
+                interval.to_trade_fees.into(),
+                interval.to_trade_volume.into(),
+                interval.to_trade_volume_usd.into(),
+                interval.total_count.into(),
+                interval.total_fees.into(),
+                interval.total_volume.into(),
+                interval.total_volume_usd.into(),
+            ]);
+            pool.execute(&insert).await?;
+            inserted += 1;
+        } else {
+            skipped += 1;
         }
     }
 
+    telemetry::intervals_stored(Dataset::Swap.as_str(), inserted, skipped);
+
+    if inserted > 0 {
+        cache::invalidate(Dataset::Swap);
+    }
+
     Ok(())
 }
+
+#[derive(Debug, Clone, Default)]
+pub struct SwapFilter {
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    pub volume_gt: Option<u64>,
+    pub fees_gt: Option<u64>,
+}
+
+impl From<&SwapHistoryQueryParams> for SwapFilter {
+    fn from(params: &SwapHistoryQueryParams) -> Self {
+        let (from, to) = params.parse_date_range().unzip();
+        Self {
+            from,
+            to,
+            volume_gt: params.volume_gt,
+            fees_gt: params.fees_gt,
+        }
+    }
+}
+
+impl IntervalFilter for SwapFilter {
+    fn push_conditions(&self, query: &mut Query) {
+        push_time_bounds(query, self.from, self.to);
+        if let Some(min_volume) = self.volume_gt {
+            query.push(" AND total_volume > ").push_bind(min_volume);
+        }
+        if let Some(min_fees) = self.fees_gt {
+            query.push(" AND total_fees > ").push_bind(min_fees);
+        }
+    }
+}
+
+pub async fn query_intervals(
+    pool: &DbPool,
+    filter: &SwapFilter,
+    sort: Sort,
+    page: Option<Page>,
+) -> Result<Vec<SwapInterval>, sqlx::Error> {
+    let query = paged_intervals_query(pool, Dataset::Swap, filter, sort, page);
+    pool.fetch_all(&query).await
+}
+
+pub async fn aggregate(
+    pool: &DbPool,
+    filter: &SwapFilter,
+    column: &'static str,
+) -> Result<ColumnAggregate, sqlx::Error> {
+    aggregate_column(pool, Dataset::Swap, filter, column).await
+}
+
+// The figures of the last of the given intervals, over the time they span
+pub fn meta(intervals: &[SwapInterval]) -> Option<SwapMeta> {
+    let (first, last) = (intervals.first()?, intervals.last()?);
+    Some(SwapMeta {
+        average_slip: last.average_slip,
+        end_time: last.end_time,
+        from_trade_average_slip: last.from_trade_average_slip,
+        from_trade_count: last.from_trade_count,
+        from_trade_fees: last.from_trade_fees,
+        from_trade_volume: last.from_trade_volume,
+        from_trade_volume_usd: last.from_trade_volume_usd,
+        rune_price_usd: last.rune_price_usd,
+        start_time: first.start_time,
+        synth_mint_average_slip: last.synth_mint_average_slip,
+        synth_mint_count: last.synth_mint_count,
+        synth_mint_fees: last.synth_mint_fees,
+        synth_mint_volume: last.synth_mint_volume,
+        synth_mint_volume_usd: last.synth_mint_volume_usd,
+        synth_redeem_average_slip: last.synth_redeem_average_slip,
+        synth_redeem_count: last.synth_redeem_count,
+        synth_redeem_fees: last.synth_redeem_fees,
+        synth_redeem_volume: last.synth_redeem_volume,
+        synth_redeem_volume_usd: last.synth_redeem_volume_usd,
+        to_asset_average_slip: last.to_asset_average_slip,
+        to_asset_count: last.to_asset_count,
+        to_asset_fees: last.to_asset_fees,
+        to_asset_volume: last.to_asset_volume,
+        to_asset_volume_usd: last.to_asset_volume_usd,
+        to_rune_average_slip: last.to_rune_average_slip,
+        to_rune_count: last.to_rune_count,
+        to_rune_fees: last.to_rune_fees,
+        to_rune_volume: last.to_rune_volume,
+        to_rune_volume_usd: last.to_rune_volume_usd,
+        to_trade_average_slip: last.to_trade_average_slip,
+        to_trade_count: last.to_trade_count,
+        to_trade_fees: last.to_trade_fees,
+        to_trade_volume: last.to_trade_volume,
+        to_trade_volume_usd: last.to_trade_volume_usd,
+        total_count: last.total_count,
+        total_fees: last.total_fees,
+        total_volume: last.total_volume,
+        total_volume_usd: last.total_volume_usd,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::test_pool;
+    use super::*;
+    use crate::core::models::swap_history::SwapHistoryResponse;
+
+    #[tokio::test]
+    async fn aggregates_the_filtered_intervals() {
+        let pool = test_pool().await;
+        let response: SwapHistoryResponse =
+            serde_json::from_str(include_str!("../../../tests/fixtures/midgard/swaps.json"))
+                .unwrap();
+        store_intervals(&pool, &response.intervals).await.unwrap();
+        let [first, second] = [&response.intervals[0], &response.intervals[1]];
+
+        let all = aggregate(&pool, &SwapFilter::default(), "total_volume")
+            .await
+            .unwrap();
+        assert_eq!(all.intervals, 2);
+        assert_eq!(
+            all.sum,
+            Some((first.total_volume + second.total_volume) as f64)
+        );
+        assert_eq!(
+            all.max,
+            Some(first.total_volume.max(second.total_volume) as f64)
+        );
+        assert_eq!(all.start_time, Some(first.start_time));
+        assert_eq!(all.end_time, Some(second.end_time));
+
+        let filter = SwapFilter {
+            from: Some(second.start_time),
+            ..Default::default()
+        };
+        let last = aggregate(&pool, &filter, "total_volume").await.unwrap();
+        assert_eq!(last.intervals, 1);
+        assert_eq!(last.average, Some(second.total_volume as f64));
+        assert_eq!(last.start_time, Some(second.start_time));
+    }
+}
@@ -2,35 +2,42 @@ use super::jobs::cron::{
     depth_history_cron::DepthHistoryCron, earnings_history_cron::EarningsHistoryCron,
     runepool_units_history_cron::RunepoolUnitsHistoryCron, swap_history_cron::SwapHistoryCron,
 };
+use crate::config::app::AppConfig;
+use crate::services::storage::DbPool;
+use std::sync::Arc;
 
-pub fn spawn_cron_jobs(pool: sqlx::MySqlPool) {
+pub fn spawn_cron_jobs(pool: DbPool, config: Arc<AppConfig>) {
     let depth_pool = pool.clone();
+    let depth_config = config.clone();
     tokio::spawn(async move {
-        let mut depth_cron = DepthHistoryCron::new(depth_pool);
+        let mut depth_cron = DepthHistoryCron::new(depth_pool, depth_config);
         if let Err(e) = depth_cron.start().await {
             tracing::error!("Depth history cron failed: {}", e);
         }
     });
 
     let earnings_pool = pool.clone();
+    let earnings_config = config.clone();
     tokio::spawn(async move {
-        let mut earnings_cron = EarningsHistoryCron::new(earnings_pool);
+        let mut earnings_cron = EarningsHistoryCron::new(earnings_pool, earnings_config);
         if let Err(e) = earnings_cron.start().await {
             tracing::error!("Earnings history cron failed: {}", e);
         }
     });
 
     let swap_pool = pool.clone();
+    let swap_config = config.clone();
     tokio::spawn(async move {
-        let mut swap_cron = SwapHistoryCron::new(swap_pool);
+        let mut swap_cron = SwapHistoryCron::new(swap_pool, swap_config);
         if let Err(e) = swap_cron.start().await {
             tracing::error!("Swap history cron failed: {}", e);
         }
     });
 
     let runepool_pool = pool.clone();
+    let runepool_config = config.clone();
     tokio::spawn(async move {
-        let mut runepool_cron = RunepoolUnitsHistoryCron::new(runepool_pool);
+        let mut runepool_cron = RunepoolUnitsHistoryCron::new(runepool_pool, runepool_config);
         if let Err(e) = runepool_cron.start().await {
             tracing::error!("Runepool units history cron failed: {}", e);
         }
@@ -0,0 +1,119 @@
+use sqlx::{
+    decode::Decode,
+    error::BoxDynError,
+    mysql::{MySql, MySqlTypeInfo, MySqlValueRef},
+    postgres::{PgTypeInfo, PgValueRef, Postgres},
+    sqlite::{Sqlite, SqliteTypeInfo, SqliteValueRef},
+    types::BigDecimal,
+    Type, ValueRef,
+};
+
+// Amounts in base units are `DECIMAL(38,0)` in MySQL and `NUMERIC(38,0)` in Postgres, cumulative
+// volumes do not fit 64 bits. SQLite has no such type, its amount columns are 64 bit INTEGERs and
+// storing anything larger is an error. Read them with `#[sqlx(try_from = "Amount")]`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Amount(pub u128);
+
+impl From<Amount> for u128 {
+    fn from(value: Amount) -> Self {
+        value.0
+    }
+}
+
+impl TryFrom<&BigDecimal> for Amount {
+    type Error = BoxDynError;
+
+    fn try_from(value: &BigDecimal) -> Result<Self, Self::Error> {
+        if !value.is_integer() {
+            return Err(format!("{} is not a whole amount", value).into());
+        }
+        Ok(Amount(value.with_scale(0).to_plain_string().parse()?))
+    }
+}
+
+// What the amount columns are bound as in MySQL and Postgres
+pub fn to_decimal(value: u128) -> BigDecimal {
+    BigDecimal::from(value)
+}
+
+impl Type<MySql> for Amount {
+    fn type_info() -> MySqlTypeInfo {
+        <BigDecimal as Type<MySql>>::type_info()
+    }
+
+    fn compatible(ty: &MySqlTypeInfo) -> bool {
+        <BigDecimal as Type<MySql>>::compatible(ty)
+            || <u64 as Type<MySql>>::compatible(ty)
+            || <i64 as Type<MySql>>::compatible(ty)
+    }
+}
+
+impl<'r> Decode<'r, MySql> for Amount {
+    fn decode(value: MySqlValueRef<'r>) -> Result<Self, BoxDynError> {
+        let type_info = value.type_info();
+        if <BigDecimal as Type<MySql>>::compatible(&type_info) {
+            return Amount::try_from(&<BigDecimal as Decode<MySql>>::decode(value)?);
+        }
+        if <u64 as Type<MySql>>::compatible(&type_info) {
+            return Ok(Amount(<u64 as Decode<MySql>>::decode(value)?.into()));
+        }
+        let value = <i64 as Decode<MySql>>::decode(value)?;
+        Ok(Amount(u128::try_from(value)?))
+    }
+}
+
+impl Type<Postgres> for Amount {
+    fn type_info() -> PgTypeInfo {
+        <BigDecimal as Type<Postgres>>::type_info()
+    }
+
+    fn compatible(ty: &PgTypeInfo) -> bool {
+        <BigDecimal as Type<Postgres>>::compatible(ty) || <i64 as Type<Postgres>>::compatible(ty)
+    }
+}
+
+impl<'r> Decode<'r, Postgres> for Amount {
+    fn decode(value: PgValueRef<'r>) -> Result<Self, BoxDynError> {
+        if <i64 as Type<Postgres>>::compatible(&value.type_info()) {
+            let value = <i64 as Decode<Postgres>>::decode(value)?;
+            return Ok(Amount(u128::try_from(value)?));
+        }
+        Amount::try_from(&<BigDecimal as Decode<Postgres>>::decode(value)?)
+    }
+}
+
+impl Type<Sqlite> for Amount {
+    fn type_info() -> SqliteTypeInfo {
+        <i64 as Type<Sqlite>>::type_info()
+    }
+
+    fn compatible(ty: &SqliteTypeInfo) -> bool {
+        <i64 as Type<Sqlite>>::compatible(ty)
+    }
+}
+
+impl<'r> Decode<'r, Sqlite> for Amount {
+    fn decode(value: SqliteValueRef<'r>) -> Result<Self, BoxDynError> {
+        let value = <i64 as Decode<Sqlite>>::decode(value)?;
+        Ok(Amount(u128::try_from(value)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn only_whole_amounts_in_range_convert() {
+        for value in [0, u128::from(u64::MAX) + 1, u128::MAX] {
+            assert_eq!(Amount::try_from(&to_decimal(value)).unwrap(), Amount(value));
+        }
+        let with_scale = BigDecimal::from_str("42.000").unwrap();
+        assert_eq!(Amount::try_from(&with_scale).unwrap(), Amount(42));
+
+        for invalid in ["42.5", "-1", "340282366920938463463374607431768211456"] {
+            assert!(Amount::try_from(&BigDecimal::from_str(invalid).unwrap()).is_err());
+        }
+    }
+}
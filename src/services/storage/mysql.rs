@@ -0,0 +1,109 @@
+use super::amount::to_decimal;
+use super::{decode_row, Backend, Column, PoolStatus, Query, Record, Storage, Value};
+use crate::config::app::DatabaseConfig;
+use futures::{stream::BoxStream, StreamExt};
+use sqlx::{
+    migrate::{MigrateError, Migrator},
+    mysql::{MySqlArguments, MySqlPoolOptions},
+    types::Json,
+    Arguments, MySqlPool,
+};
+
+static MIGRATOR: Migrator = sqlx::migrate!("migrations/mysql");
+
+#[derive(Clone)]
+pub struct MySqlStorage {
+    pool: MySqlPool,
+}
+
+impl MySqlStorage {
+    pub async fn connect(config: &DatabaseConfig) -> Result<Self, sqlx::Error> {
+        let pool = MySqlPoolOptions::new()
+            .max_connections(config.max_connections)
+            .acquire_timeout(config.acquire_timeout())
+            .connect(&config.url)
+            .await?;
+        Ok(Self { pool })
+    }
+}
+
+fn arguments(query: &Query) -> Result<MySqlArguments, sqlx::Error> {
+    let mut arguments = MySqlArguments::default();
+    for value in query.params() {
+        match value {
+            Value::Int(value) => arguments.add(value),
+            Value::UInt(value) => arguments.add(value),
+            Value::Amount(value) => arguments.add(to_decimal(*value)),
+            Value::Float(value) => arguments.add(value),
+            Value::Bool(value) => arguments.add(value),
+            Value::Text(value) => arguments.add(value.as_str()),
+            Value::Time(value) => arguments.add(value),
+            Value::Json(value) => arguments.add(Json(value)),
+            Value::Null => arguments.add(Option::<&str>::None),
+        }
+        .map_err(sqlx::Error::Encode)?;
+    }
+    Ok(arguments)
+}
+
+impl Storage for MySqlStorage {
+    fn backend(&self) -> Backend {
+        Backend::MySql
+    }
+
+    async fn fetch_all<T: Record>(&self, query: &Query) -> Result<Vec<T>, sqlx::Error> {
+        sqlx::query_as_with::<_, T, _>(query.sql(), arguments(query)?)
+            .fetch_all(&self.pool)
+            .await
+    }
+
+    async fn fetch_optional<T: Record>(&self, query: &Query) -> Result<Option<T>, sqlx::Error> {
+        sqlx::query_as_with::<_, T, _>(query.sql(), arguments(query)?)
+            .fetch_optional(&self.pool)
+            .await
+    }
+
+    fn fetch_rows<'a>(
+        &'a self,
+        query: &'a Query,
+        columns: &'a [Column],
+    ) -> BoxStream<'a, Result<Vec<Value>, sqlx::Error>> {
+        match arguments(query) {
+            Ok(arguments) => sqlx::query_with(query.sql(), arguments)
+                .fetch(&self.pool)
+                .map(move |row| decode_row(&row?, columns))
+                .boxed(),
+            Err(e) => futures::stream::once(async { Err(e) }).boxed(),
+        }
+    }
+
+    async fn execute(&self, query: &Query) -> Result<u64, sqlx::Error> {
+        Ok(sqlx::query_with(query.sql(), arguments(query)?)
+            .execute(&self.pool)
+            .await?
+            .rows_affected())
+    }
+
+    async fn insert(&self, query: &Query) -> Result<i64, sqlx::Error> {
+        let result = sqlx::query_with(query.sql(), arguments(query)?)
+            .execute(&self.pool)
+            .await?;
+        Ok(result.last_insert_id() as i64)
+    }
+
+    fn migrator(&self) -> &'static Migrator {
+        &MIGRATOR
+    }
+
+    async fn run_migrations(&self) -> Result<(), MigrateError> {
+        MIGRATOR.run(&self.pool).await
+    }
+
+    fn pool_status(&self) -> PoolStatus {
+        PoolStatus {
+            size: self.pool.size(),
+            idle: self.pool.num_idle(),
+            max: self.pool.options().get_max_connections(),
+        }
+    }
+}
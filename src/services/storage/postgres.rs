@@ -0,0 +1,134 @@
+use super::amount::to_decimal;
+use super::{decode_row, Backend, Column, PoolStatus, Query, Record, Storage, Value};
+use crate::config::app::DatabaseConfig;
+use futures::{stream::BoxStream, StreamExt};
+use sqlx::{
+    encode::{Encode, IsNull},
+    error::BoxDynError,
+    migrate::{MigrateError, Migrator},
+    postgres::{types::Oid, PgArgumentBuffer, PgArguments, PgPoolOptions, PgTypeInfo, Postgres},
+    types::Json,
+    Arguments, PgPool, Type,
+};
+
+static MIGRATOR: Migrator = sqlx::migrate!("migrations/postgres");
+
+#[derive(Clone)]
+pub struct PgStorage {
+    pool: PgPool,
+}
+
+impl PgStorage {
+    pub async fn connect(config: &DatabaseConfig) -> Result<Self, sqlx::Error> {
+        let pool = PgPoolOptions::new()
+            .max_connections(config.max_connections)
+            .acquire_timeout(config.acquire_timeout())
+            .connect(&config.url)
+            .await?;
+        Ok(Self { pool })
+    }
+}
+
+// A NULL parameter of unspecified type, Postgres infers it from where it is used like it does
+// for a literal NULL
+struct Null;
+
+impl Type<Postgres> for Null {
+    fn type_info() -> PgTypeInfo {
+        PgTypeInfo::with_oid(Oid(0))
+    }
+}
+
+impl Encode<'_, Postgres> for Null {
+    fn encode_by_ref(&self, _: &mut PgArgumentBuffer) -> Result<IsNull, BoxDynError> {
+        Ok(IsNull::Yes)
+    }
+}
+
+fn arguments(query: &Query) -> Result<PgArguments, sqlx::Error> {
+    let mut arguments = PgArguments::default();
+    for value in query.params() {
+        match value {
+            Value::Int(value) => arguments.add(value),
+            // Every unsigned column is a BIGINT, a value past its range could not be stored or
+            // compared with anything stored anyway
+            Value::UInt(value) => match i64::try_from(*value) {
+                Ok(value) => arguments.add(value),
+                Err(e) => Err(e.into()),
+            },
+            Value::Amount(value) => arguments.add(to_decimal(*value)),
+            Value::Float(value) => arguments.add(value),
+            Value::Bool(value) => arguments.add(value),
+            Value::Text(value) => arguments.add(value.as_str()),
+            Value::Time(value) => arguments.add(value),
+            Value::Json(value) => arguments.add(Json(value)),
+            Value::Null => arguments.add(Null),
+        }
+        .map_err(sqlx::Error::Encode)?;
+    }
+    Ok(arguments)
+}
+
+impl Storage for PgStorage {
+    fn backend(&self) -> Backend {
+        Backend::Postgres
+    }
+
+    async fn fetch_all<T: Record>(&self, query: &Query) -> Result<Vec<T>, sqlx::Error> {
+        sqlx::query_as_with::<_, T, _>(query.sql(), arguments(query)?)
+            .fetch_all(&self.pool)
+            .await
+    }
+
+    async fn fetch_optional<T: Record>(&self, query: &Query) -> Result<Option<T>, sqlx::Error> {
+        sqlx::query_as_with::<_, T, _>(query.sql(), arguments(query)?)
+            .fetch_optional(&self.pool)
+            .await
+    }
+
+    fn fetch_rows<'a>(
+        &'a self,
+        query: &'a Query,
+        columns: &'a [Column],
+    ) -> BoxStream<'a, Result<Vec<Value>, sqlx::Error>> {
+        match arguments(query) {
+            Ok(arguments) => sqlx::query_with(query.sql(), arguments)
+                .fetch(&self.pool)
+                .map(move |row| decode_row(&row?, columns))
+                .boxed(),
+            Err(e) => futures::stream::once(async { Err(e) }).boxed(),
+        }
+    }
+
+    async fn execute(&self, query: &Query) -> Result<u64, sqlx::Error> {
+        Ok(sqlx::query_with(query.sql(), arguments(query)?)
+            .execute(&self.pool)
+            .await?
+            .rows_affected())
+    }
+
+    // Postgres has no `LAST_INSERT_ID()`, the id comes back with `RETURNING`
+    async fn insert(&self, query: &Query) -> Result<i64, sqlx::Error> {
+        let sql = format!("{} RETURNING id", query.sql());
+        let (id,): (i64,) = sqlx::query_as_with(&sql, arguments(query)?)
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(id)
+    }
+
+    fn migrator(&self) -> &'static Migrator {
+        &MIGRATOR
+    }
+
+    async fn run_migrations(&self) -> Result<(), MigrateError> {
+        MIGRATOR.run(&self.pool).await
+    }
+
+    fn pool_status(&self) -> PoolStatus {
+        PoolStatus {
+            size: self.pool.size(),
+            idle: self.pool.num_idle(),
+            max: self.pool.options().get_max_connections(),
+        }
+    }
+}
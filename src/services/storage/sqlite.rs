@@ -0,0 +1,125 @@
+use super::{decode_row, Backend, Column, PoolStatus, Query, Record, Storage, Value};
+use crate::config::app::DatabaseConfig;
+use futures::{stream::BoxStream, StreamExt};
+use sqlx::{
+    migrate::{MigrateError, Migrator},
+    sqlite::{SqliteArguments, SqliteConnectOptions, SqliteJournalMode, SqlitePoolOptions},
+    Arguments, SqlitePool,
+};
+use std::str::FromStr;
+
+static MIGRATOR: Migrator = sqlx::migrate!("migrations/sqlite");
+
+#[derive(Clone)]
+pub struct SqliteStorage {
+    pool: SqlitePool,
+}
+
+impl SqliteStorage {
+    pub async fn connect(config: &DatabaseConfig) -> Result<Self, sqlx::Error> {
+        let options = SqliteConnectOptions::from_str(&config.url)?
+            .create_if_missing(true)
+            .journal_mode(SqliteJournalMode::Wal);
+
+        // An in-memory database is gone once its last connection closes, so they are kept open
+        // for the life of the pool
+        let pool = SqlitePoolOptions::new()
+            .max_connections(config.max_connections)
+            .acquire_timeout(config.acquire_timeout())
+            .idle_timeout(None)
+            .max_lifetime(None)
+            .connect_with(options)
+            .await?;
+        Ok(Self { pool })
+    }
+}
+
+fn arguments(query: &Query) -> Result<SqliteArguments<'_>, sqlx::Error> {
+    let mut arguments = SqliteArguments::default();
+    for value in query.params() {
+        match value {
+            Value::Int(value) => arguments.add(*value),
+            // SQLite integers are 64 bit signed, like the Postgres BIGINT columns
+            Value::UInt(value) => match i64::try_from(*value) {
+                Ok(value) => arguments.add(value),
+                Err(e) => Err(e.into()),
+            },
+            // Amount columns too, see `Amount`
+            Value::Amount(value) => match i64::try_from(*value) {
+                Ok(value) => arguments.add(value),
+                Err(e) => Err(e.into()),
+            },
+            Value::Float(value) => arguments.add(*value),
+            Value::Bool(value) => arguments.add(*value),
+            Value::Text(value) => arguments.add(value.as_str()),
+            Value::Time(value) => arguments.add(*value),
+            // JSON columns are TEXT
+            Value::Json(value) => arguments.add(value.to_string()),
+            Value::Null => arguments.add(Option::<&str>::None),
+        }
+        .map_err(sqlx::Error::Encode)?;
+    }
+    Ok(arguments)
+}
+
+impl Storage for SqliteStorage {
+    fn backend(&self) -> Backend {
+        Backend::Sqlite
+    }
+
+    async fn fetch_all<T: Record>(&self, query: &Query) -> Result<Vec<T>, sqlx::Error> {
+        sqlx::query_as_with::<_, T, _>(query.sql(), arguments(query)?)
+            .fetch_all(&self.pool)
+            .await
+    }
+
+    async fn fetch_optional<T: Record>(&self, query: &Query) -> Result<Option<T>, sqlx::Error> {
+        sqlx::query_as_with::<_, T, _>(query.sql(), arguments(query)?)
+            .fetch_optional(&self.pool)
+            .await
+    }
+
+    fn fetch_rows<'a>(
+        &'a self,
+        query: &'a Query,
+        columns: &'a [Column],
+    ) -> BoxStream<'a, Result<Vec<Value>, sqlx::Error>> {
+        match arguments(query) {
+            Ok(arguments) => sqlx::query_with(query.sql(), arguments)
+                .fetch(&self.pool)
+                .map(move |row| decode_row(&row?, columns))
+                .boxed(),
+            Err(e) => futures::stream::once(async { Err(e) }).boxed(),
+        }
+    }
+
+    async fn execute(&self, query: &Query) -> Result<u64, sqlx::Error> {
+        Ok(sqlx::query_with(query.sql(), arguments(query)?)
+            .execute(&self.pool)
+            .await?
+            .rows_affected())
+    }
+
+    async fn insert(&self, query: &Query) -> Result<i64, sqlx::Error> {
+        let result = sqlx::query_with(query.sql(), arguments(query)?)
+            .execute(&self.pool)
+            .await?;
+        Ok(result.last_insert_rowid())
+    }
+
+    fn migrator(&self) -> &'static Migrator {
+        &MIGRATOR
+    }
+
+    async fn run_migrations(&self) -> Result<(), MigrateError> {
+        MIGRATOR.run(&self.pool).await
+    }
+
+    fn pool_status(&self) -> PoolStatus {
+        PoolStatus {
+            size: self.pool.size(),
+            idle: self.pool.num_idle(),
+            max: self.pool.options().get_max_connections(),
+        }
+    }
+}
@@ -0,0 +1,88 @@
+use sqlx::{
+    decode::Decode,
+    error::BoxDynError,
+    mysql::{MySql, MySqlTypeInfo, MySqlValueRef},
+    postgres::{PgTypeInfo, PgValueRef, Postgres},
+    sqlite::{Sqlite, SqliteTypeInfo, SqliteValueRef},
+    Type, TypeInfo, ValueRef,
+};
+
+// Amounts and counters are `BIGINT UNSIGNED` in MySQL but plain signed integers in Postgres and
+// SQLite, which have no unsigned ones. Read them through this with
+// `#[sqlx(try_from = "Unsigned")]`, a negative value is a decoding error instead of wrapping around.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Unsigned(pub u64);
+
+impl From<Unsigned> for u64 {
+    fn from(value: Unsigned) -> Self {
+        value.0
+    }
+}
+
+impl TryFrom<Unsigned> for u32 {
+    type Error = std::num::TryFromIntError;
+
+    fn try_from(value: Unsigned) -> Result<Self, Self::Error> {
+        u32::try_from(value.0)
+    }
+}
+
+impl Type<MySql> for Unsigned {
+    fn type_info() -> MySqlTypeInfo {
+        <u64 as Type<MySql>>::type_info()
+    }
+
+    fn compatible(ty: &MySqlTypeInfo) -> bool {
+        <u64 as Type<MySql>>::compatible(ty) || <i64 as Type<MySql>>::compatible(ty)
+    }
+}
+
+impl<'r> Decode<'r, MySql> for Unsigned {
+    fn decode(value: MySqlValueRef<'r>) -> Result<Self, BoxDynError> {
+        if <u64 as Type<MySql>>::compatible(&value.type_info()) {
+            return Ok(Unsigned(<u64 as Decode<MySql>>::decode(value)?));
+        }
+        let value = <i64 as Decode<MySql>>::decode(value)?;
+        Ok(Unsigned(u64::try_from(value)?))
+    }
+}
+
+impl Type<Postgres> for Unsigned {
+    fn type_info() -> PgTypeInfo {
+        <i64 as Type<Postgres>>::type_info()
+    }
+
+    fn compatible(ty: &PgTypeInfo) -> bool {
+        <i64 as Type<Postgres>>::compatible(ty)
+            || <i32 as Type<Postgres>>::compatible(ty)
+            || <i16 as Type<Postgres>>::compatible(ty)
+    }
+}
+
+impl<'r> Decode<'r, Postgres> for Unsigned {
+    fn decode(value: PgValueRef<'r>) -> Result<Self, BoxDynError> {
+        let value = match value.type_info().name() {
+            "INT2" => <i16 as Decode<Postgres>>::decode(value)?.into(),
+            "INT4" => <i32 as Decode<Postgres>>::decode(value)?.into(),
+            _ => <i64 as Decode<Postgres>>::decode(value)?,
+        };
+        Ok(Unsigned(u64::try_from(value)?))
+    }
+}
+
+impl Type<Sqlite> for Unsigned {
+    fn type_info() -> SqliteTypeInfo {
+        <i64 as Type<Sqlite>>::type_info()
+    }
+
+    fn compatible(ty: &SqliteTypeInfo) -> bool {
+        <i64 as Type<Sqlite>>::compatible(ty)
+    }
+}
+
+impl<'r> Decode<'r, Sqlite> for Unsigned {
+    fn decode(value: SqliteValueRef<'r>) -> Result<Self, BoxDynError> {
+        let value = <i64 as Decode<Sqlite>>::decode(value)?;
+        Ok(Unsigned(u64::try_from(value)?))
+    }
+}
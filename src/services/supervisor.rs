@@ -0,0 +1,306 @@
+// Runs the background crons, restarts the ones that fail and stops them all on shutdown
+use crate::config::app::SupervisorConfig;
+use crate::core::models::health::{TaskState, TaskStatus};
+use crate::services::telemetry;
+use chrono::Utc;
+use std::any::Any;
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use tokio::time;
+use tracing::{error, info, warn};
+
+static TASKS: Mutex<BTreeMap<&'static str, TaskState>> = Mutex::new(BTreeMap::new());
+
+// State of every supervised task, by name
+pub fn task_states() -> Vec<TaskState> {
+    TASKS.lock().unwrap().values().cloned().collect()
+}
+
+fn record(name: &'static str, status: TaskStatus, error: Option<String>) {
+    let mut tasks = TASKS.lock().unwrap();
+    let state = tasks.entry(name).or_insert_with(|| TaskState {
+        name: name.to_string(),
+        status,
+        restarts: 0,
+        last_error: None,
+        since: 0,
+    });
+    if status == TaskStatus::Restarting {
+        state.restarts += 1;
+    }
+    if error.is_some() {
+        state.last_error = error;
+    }
+    state.status = status;
+    state.since = Utc::now().timestamp();
+    telemetry::task_up(name, status == TaskStatus::Running);
+}
+
+// Handed to every task. Tasks only look at it between two units of work, so whatever they were
+// storing when the shutdown started is committed before they return.
+#[derive(Debug, Clone)]
+pub struct Shutdown {
+    stop: watch::Receiver<bool>,
+}
+
+impl Shutdown {
+    // For callers outside the supervisor, like the CLI, that run a task to the end
+    pub fn never() -> Self {
+        let (_, stop) = watch::channel(false);
+        Self { stop }
+    }
+
+    pub fn is_requested(&self) -> bool {
+        *self.stop.borrow()
+    }
+
+    pub async fn requested(&self) {
+        let mut stop = self.stop.clone();
+        if stop.wait_for(|stop| *stop).await.is_err() {
+            // Nobody is left to ask for a shutdown
+            std::future::pending::<()>().await;
+        }
+    }
+
+    // Sleeps for `duration` or until a shutdown is requested. Returns whether the task should
+    // keep going.
+    pub async fn sleep(&self, duration: Duration) -> bool {
+        tokio::select! {
+            _ = time::sleep(duration) => !self.is_requested(),
+            _ = self.requested() => false,
+        }
+    }
+}
+
+fn panic_message(panic: Box<dyn Any + Send>) -> String {
+    match panic.downcast::<String>() {
+        Ok(message) => *message,
+        Err(panic) => panic
+            .downcast_ref::<&str>()
+            .map(|message| message.to_string())
+            .unwrap_or_else(|| "unknown panic".to_string()),
+    }
+}
+
+pub struct Supervisor {
+    config: SupervisorConfig,
+    stop: watch::Sender<bool>,
+    tasks: Vec<(&'static str, JoinHandle<()>)>,
+}
+
+impl Supervisor {
+    pub fn new(config: &SupervisorConfig) -> Self {
+        Self {
+            config: config.clone(),
+            stop: watch::channel(false).0,
+            tasks: Vec::new(),
+        }
+    }
+
+    // Runs `task` until a shutdown. An error, a panic or an early return restarts it after a
+    // backoff that doubles on every failure in a row, and starts over once a run lasted longer
+    // than the longest backoff.
+    pub fn spawn<F, Fut>(&mut self, name: &'static str, mut task: F)
+    where
+        F: FnMut(Shutdown) -> Fut + Send + 'static,
+        Fut: Future<Output = Result<(), anyhow::Error>> + Send + 'static,
+    {
+        let shutdown = self.handle();
+        let initial_backoff = self.config.restart_backoff();
+        let max_backoff = self.config.max_restart_backoff();
+
+        let handle = tokio::spawn(async move {
+            let mut backoff = initial_backoff;
+            loop {
+                record(name, TaskStatus::Running, None);
+                let started = time::Instant::now();
+                // Spawned on its own so a panic is caught here instead of taking the loop down
+                let outcome = tokio::spawn(task(shutdown.clone())).await;
+
+                if shutdown.is_requested() {
+                    if let Ok(Err(e)) = &outcome {
+                        warn!("{} failed while shutting down: {}", name, e);
+                    }
+                    break;
+                }
+
+                let reason = match outcome {
+                    Ok(Ok(())) => "stopped on its own".to_string(),
+                    Ok(Err(e)) => e.to_string(),
+                    Err(e) if e.is_panic() => {
+                        format!("panicked: {}", panic_message(e.into_panic()))
+                    }
+                    Err(e) => e.to_string(),
+                };
+                if started.elapsed() > max_backoff {
+                    backoff = initial_backoff;
+                }
+
+                error!("{} failed: {}, restarting in {:?}", name, reason, backoff);
+                record(name, TaskStatus::Restarting, Some(reason));
+                telemetry::task_restarted(name);
+
+                if !shutdown.sleep(backoff).await {
+                    break;
+                }
+                backoff = (backoff * 2).min(max_backoff);
+            }
+            record(name, TaskStatus::Stopped, None);
+        });
+        self.tasks.push((name, handle));
+    }
+
+    // Requested along with the tasks' own, for whatever else has to stop with them
+    pub fn handle(&self) -> Shutdown {
+        Shutdown {
+            stop: self.stop.subscribe(),
+        }
+    }
+
+    // Asks every task to stop, they finish what they are doing first
+    pub fn stop(&self) {
+        self.stop.send_replace(true);
+    }
+
+    // Stops the tasks and waits for them for up to `shutdown_grace_secs` in total. Returns whether
+    // every one of them stopped in time.
+    pub async fn shutdown(self) -> bool {
+        self.stop();
+        let deadline = time::Instant::now() + self.config.shutdown_grace();
+
+        let mut stopped = true;
+        for (name, handle) in self.tasks {
+            match time::timeout_at(deadline, handle).await {
+                Ok(Ok(())) => info!("{} stopped", name),
+                Ok(Err(e)) => {
+                    error!("{} did not stop cleanly: {}", name, e);
+                    stopped = false;
+                }
+                Err(_) => {
+                    error!("{} did not stop within the grace period", name);
+                    stopped = false;
+                }
+            }
+        }
+        stopped
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Arc;
+
+    fn config() -> SupervisorConfig {
+        SupervisorConfig {
+            restart_backoff_secs: 1,
+            max_restart_backoff_secs: 2,
+            shutdown_grace_secs: 5,
+        }
+    }
+
+    fn state(name: &str) -> TaskState {
+        task_states()
+            .into_iter()
+            .find(|state| state.name == name)
+            .unwrap()
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn failed_tasks_are_restarted_with_backoff() {
+        let runs = Arc::new(AtomicU32::new(0));
+        let mut supervisor = Supervisor::new(&config());
+
+        let counter = runs.clone();
+        supervisor.spawn("test_failing", move |shutdown| {
+            let run = counter.fetch_add(1, Ordering::SeqCst);
+            async move {
+                match run {
+                    0 => anyhow::bail!("midgard is down"),
+                    1 => panic!("bad response"),
+                    _ => {
+                        shutdown.requested().await;
+                        Ok(())
+                    }
+                }
+            }
+        });
+
+        // First failure, restarted after 1s
+        time::sleep(Duration::from_millis(500)).await;
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+        assert_eq!(state("test_failing").status, TaskStatus::Restarting);
+        assert_eq!(
+            state("test_failing").last_error.as_deref(),
+            Some("midgard is down")
+        );
+
+        // The panic doubles the backoff to 2s
+        time::sleep(Duration::from_millis(1000)).await;
+        assert_eq!(runs.load(Ordering::SeqCst), 2);
+        assert_eq!(
+            state("test_failing").last_error.as_deref(),
+            Some("panicked: bad response")
+        );
+        time::sleep(Duration::from_millis(2000)).await;
+        assert_eq!(runs.load(Ordering::SeqCst), 3);
+
+        let running = state("test_failing");
+        assert_eq!(running.status, TaskStatus::Running);
+        assert_eq!(running.restarts, 2);
+
+        assert!(supervisor.shutdown().await);
+        assert_eq!(state("test_failing").status, TaskStatus::Stopped);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn shutdown_lets_the_current_unit_of_work_finish() {
+        let stored = Arc::new(AtomicU32::new(0));
+        let mut supervisor = Supervisor::new(&config());
+
+        let counter = stored.clone();
+        supervisor.spawn("test_storing", move |shutdown| {
+            let counter = counter.clone();
+            async move {
+                loop {
+                    // Stands in for a fetch and its transaction, which the shutdown must not cut
+                    time::sleep(Duration::from_secs(3)).await;
+                    counter.fetch_add(1, Ordering::SeqCst);
+                    if !shutdown.sleep(Duration::from_secs(60)).await {
+                        return Ok(());
+                    }
+                }
+            }
+        });
+
+        time::sleep(Duration::from_secs(1)).await;
+        assert!(supervisor.shutdown().await);
+        assert_eq!(stored.load(Ordering::SeqCst), 1);
+        assert_eq!(state("test_storing").status, TaskStatus::Stopped);
+        assert_eq!(state("test_storing").restarts, 0);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn tasks_that_outlast_the_grace_period_are_reported() {
+        let mut supervisor = Supervisor::new(&config());
+        supervisor.spawn("test_stuck", |_| async {
+            time::sleep(Duration::from_secs(3600)).await;
+            Ok(())
+        });
+
+        time::sleep(Duration::from_secs(1)).await;
+        assert!(!supervisor.shutdown().await);
+    }
+
+    #[tokio::test]
+    async fn never_is_never_requested() {
+        let shutdown = Shutdown::never();
+        assert!(!shutdown.is_requested());
+        assert!(shutdown.sleep(Duration::from_millis(1)).await);
+    }
+}
@@ -0,0 +1,72 @@
+// Prometheus recorder plus the ingestion metrics reported by the crons and repositories
+use crate::services::storage::{DbPool, Storage};
+use chrono::Utc;
+use metrics_exporter_prometheus::{BuildError, Matcher, PrometheusBuilder, PrometheusHandle};
+use std::sync::OnceLock;
+
+const HTTP_DURATION_BUCKETS: &[f64] = &[
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0,
+];
+
+static PROMETHEUS: OnceLock<PrometheusHandle> = OnceLock::new();
+
+pub fn install_recorder() -> Result<(), BuildError> {
+    let handle = PrometheusBuilder::new()
+        .set_buckets_for_metric(
+            Matcher::Full("http_request_duration_seconds".to_string()),
+            HTTP_DURATION_BUCKETS,
+        )?
+        .install_recorder()?;
+    // `install_recorder` already fails when a recorder is set, so this cannot be set either
+    let _ = PROMETHEUS.set(handle);
+    Ok(())
+}
+
+// Text exposition of every metric, `None` when no recorder was installed
+pub fn render() -> Option<String> {
+    PROMETHEUS.get().map(|handle| {
+        handle.run_upkeep();
+        handle.render()
+    })
+}
+
+// Read when scraping, sqlx has no hook to report pool usage as it changes
+pub fn record_pool(pool: &DbPool) {
+    let status = pool.pool_status();
+    metrics::gauge!("db_pool_connections").set(status.size as f64);
+    metrics::gauge!("db_pool_idle_connections").set(status.idle as f64);
+    metrics::gauge!("db_pool_max_connections").set(status.max as f64);
+}
+
+pub fn intervals_fetched(dataset: &'static str, count: usize) {
+    metrics::counter!("ingestion_intervals_fetched_total", "dataset" => dataset)
+        .increment(count as u64);
+}
+
+pub fn intervals_stored(dataset: &'static str, inserted: u64, skipped: u64) {
+    metrics::counter!("ingestion_intervals_inserted_total", "dataset" => dataset)
+        .increment(inserted);
+    metrics::counter!("ingestion_intervals_skipped_total", "dataset" => dataset).increment(skipped);
+}
+
+pub fn parse_failure(dataset: &'static str) {
+    metrics::counter!("ingestion_parse_failures_total", "dataset" => dataset).increment(1);
+}
+
+// Midgard answered "slow down"
+pub fn rate_limited(dataset: &'static str) {
+    metrics::counter!("ingestion_rate_limited_total", "dataset" => dataset).increment(1);
+}
+
+pub fn fetch_succeeded(cron: &'static str) {
+    metrics::gauge!("ingestion_last_success_timestamp_seconds", "cron" => cron)
+        .set(Utc::now().timestamp() as f64);
+}
+
+pub fn task_up(task: &'static str, up: bool) {
+    metrics::gauge!("background_task_up", "task" => task).set(if up { 1.0 } else { 0.0 });
+}
+
+pub fn task_restarted(task: &'static str) {
+    metrics::counter!("background_task_restarts_total", "task" => task).increment(1);
+}
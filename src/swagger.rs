@@ -1,11 +1,30 @@
 // !I don't know why but the this is working but i need to import the __path_ to make it work wise words from the compiler
+use crate::api::routes::actions::__path_get_actions;
+use crate::api::routes::admin::{__path_cancel_job, __path_get_jobs, __path_post_backfill};
+use crate::api::routes::analytics::{
+    __path_get_lp_performance, __path_get_pool_yield, __path_get_rolling_stats,
+    __path_get_timeseries,
+};
 use crate::api::routes::depth::__path_get_depth_history;
 use crate::api::routes::earnings::__path_get_earnings_history;
+use crate::api::routes::export::__path_get_export;
+use crate::api::routes::health::{
+    __path_get_freshness, __path_get_healthz, __path_get_readyz, __path_get_tasks,
+};
+use crate::api::routes::metrics::__path_get_metrics;
 use crate::api::routes::runepool::__path_get_runepool_units_history;
 use crate::api::routes::swap::__path_get_swap_history;
 use crate::core::models::{
-    depth_history::DepthHistoryResponse, earnings_history::EarningsHistoryResponse,
-    runepool_units_history::RunepoolUnitsHistoryResponse, swap_history::SwapHistoryResponse,
+    actions::ActionsSearchResponse,
+    analytics::{LpPerformanceResponse, PoolYieldResponse, RollingResponse, TimeseriesResponse},
+    depth_history::DepthHistoryResponse,
+    earnings_history::EarningsHistoryResponse,
+    health::{
+        FreshnessResponse, HealthResponse, ReadinessResponse, TaskState, TaskStatus, TasksResponse,
+    },
+    job::{Job, JobsResponse},
+    runepool_units_history::RunepoolUnitsHistoryResponse,
+    swap_history::SwapHistoryResponse,
 };
 
 // ! Don't format the description it will break the swagger ui description it looks better this way
@@ -21,8 +40,11 @@ use crate::core::models::{
         - Swap transaction analytics and metrics
         - Network earnings data across different pools
         - Runepool units historical data
+        - Per-transaction action search (swaps, adds, withdraws, refunds)
+        - Bulk export of whole history ranges as Parquet or NDJSON
+        - Pool analytics derived from the stored history (APR/APY, LP performance and impermanent loss, rolling statistics, aligned cross-dataset time series)
 
-'The API supports pagination, filtering, sorting, and date range queries. Data is continuously synchronized through background cron jobs with rate limiting protection. Built with Rust using Axum framework and MySQL for persistence.'
+'The API supports pagination, filtering, sorting, and date range queries. Numbers are returned as strings like Midgard does, pass `numbers=native` (or the `x-numbers: native` header) to get plain JSON numbers, integers past 2^53 - 1 stay strings so JavaScript clients do not round them. Amounts are THORChain 1e8 base units, history and analytics routes take `units=decimal` for whole RUNE/asset or `units=usd` for USD at each interval's own price. Data is continuously synchronized through background cron jobs with rate limiting protection. Every request needs an `x-api-key` header, each key has its own per minute quota reported through the `X-RateLimit-Limit`, `X-RateLimit-Remaining` and `X-RateLimit-Reset` headers and requests over it get a 429. Built with Rust using Axum framework and MySQL for persistence.'
 ",
         contact(
             name = "API Support",
@@ -41,39 +63,72 @@ use crate::core::models::{
         (name = "depth", description = "Depth history operations"),
         (name = "swap", description = "Swap history operations"),
         (name = "earnings", description = "Earnings history operations"),
-        (name = "runepool", description = "Runepool units history operations")
+        (name = "runepool", description = "Runepool units history operations"),
+        (name = "actions", description = "Individual action search operations"),
+        (name = "analytics", description = "Analytics derived from the stored history"),
+        (name = "export", description = "Bulk export operations"),
+        (name = "admin", description = "Backfills and job management, admin api keys only"),
+        (name = "health", description = "Liveness, readiness, data freshness and background task probes, Prometheus metrics")
     ),
     paths(
         get_depth_history,
         get_swap_history,
         get_runepool_units_history,
-        get_earnings_history
+        get_earnings_history,
+        get_actions,
+        get_pool_yield,
+        get_lp_performance,
+        get_rolling_stats,
+        get_timeseries,
+        get_export,
+        post_backfill,
+        get_jobs,
+        cancel_job,
+        get_healthz,
+        get_readyz,
+        get_freshness,
+        get_tasks,
+        get_metrics
     ),
     components(
         schemas(
             DepthHistoryResponse,
             SwapHistoryResponse,
             RunepoolUnitsHistoryResponse,
-            EarningsHistoryResponse
+            EarningsHistoryResponse,
+            ActionsSearchResponse,
+            PoolYieldResponse,
+            LpPerformanceResponse,
+            RollingResponse,
+            TimeseriesResponse,
+            Job,
+            JobsResponse,
+            HealthResponse,
+            ReadinessResponse,
+            FreshnessResponse,
+            TasksResponse,
+            TaskState,
+            TaskStatus
         )
     ),
-    // modifiers(&SecurityAddon)
+    modifiers(&SecurityAddon),
+    security(("api_key" = []))
 )]
 pub struct SwaggerApiDoc;
 
-// struct SecurityAddon;
+struct SecurityAddon;
 
-// impl utoipa::Modify for SecurityAddon {
-//     fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
-//         if let Some(components) = openapi.components.as_mut() {
-//             components.add_security_scheme(
-//                 "api_key",
-//                 utoipa::openapi::security::SecurityScheme::ApiKey(
-//                     utoipa::openapi::security::ApiKey::Header(
-//                         utoipa::openapi::security::ApiKeyValue::new("x-api-key"),
-//                     ),
-//                 ),
-//             );
-//         }
-//     }
-// }
+impl utoipa::Modify for SecurityAddon {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        if let Some(components) = openapi.components.as_mut() {
+            components.add_security_scheme(
+                "api_key",
+                utoipa::openapi::security::SecurityScheme::ApiKey(
+                    utoipa::openapi::security::ApiKey::Header(
+                        utoipa::openapi::security::ApiKeyValue::new("x-api-key"),
+                    ),
+                ),
+            );
+        }
+    }
+}
@@ -0,0 +1,252 @@
+use super::mock_midgard::{MockMidgard, Reply};
+use crate::api::state::AppState;
+use crate::config::app::AppConfig;
+use crate::core::models::{dataset::Dataset, depth_history::DEPTH_POOL};
+use crate::services::jobs::cron::{
+    depth_history_cron::DepthHistoryCron, earnings_history_cron::EarningsHistoryCron,
+    hourly_fetcher::HourlyFetcher, runepool_units_history_cron::RunepoolUnitsHistoryCron,
+    swap_history_cron::SwapHistoryCron,
+};
+use crate::services::repository::{depth, earnings, runepool, swap, test_pool, Order, Sort};
+use crate::services::storage::DbPool;
+use serde_json::Value;
+use std::sync::Arc;
+use tokio::net::TcpListener;
+
+// Start of the first recorded interval
+const HISTORY_START: i64 = 1704067200;
+
+const MALFORMED: &[&str] = &[
+    "",
+    "<html>502 Bad Gateway</html>",
+    r#"{"intervals": [{"startTime": "1704067200"}], "meta": {}}"#,
+    r#"{"intervals": [{"assetDepth": "-1""#,
+];
+
+struct Setup {
+    midgard: MockMidgard,
+    pool: DbPool,
+    config: Arc<AppConfig>,
+}
+
+async fn setup() -> Setup {
+    let midgard = MockMidgard::start().await;
+    let mut config = AppConfig::default();
+    config.midgard.url = midgard.url.clone();
+    config.midgard.request_delay_secs = 0;
+    config.midgard.rate_limit_backoff_secs = 0;
+    config.ingestion.history_start = HISTORY_START;
+    config.server.require_api_key = false;
+
+    Setup {
+        midgard,
+        pool: test_pool().await,
+        config: Arc::new(config),
+    }
+}
+
+async fn stored_depths(pool: &DbPool) -> Vec<u128> {
+    depth::query_intervals(
+        pool,
+        &Default::default(),
+        Sort::start_time(Order::Asc),
+        None,
+    )
+    .await
+    .unwrap()
+    .iter()
+    .map(|interval| interval.asset_depth)
+    .collect()
+}
+
+async fn stored_rows(pool: &DbPool) -> [usize; 4] {
+    let sort = Sort::start_time(Order::Asc);
+    [
+        stored_depths(pool).await.len(),
+        earnings::query_intervals(pool, &Default::default(), sort, None)
+            .await
+            .unwrap()
+            .len(),
+        swap::query_intervals(pool, &Default::default(), sort, None)
+            .await
+            .unwrap()
+            .len(),
+        runepool::query_intervals(pool, &Default::default(), sort, None)
+            .await
+            .unwrap()
+            .len(),
+    ]
+}
+
+#[tokio::test]
+async fn crons_store_the_recorded_intervals() {
+    let Setup {
+        midgard,
+        pool,
+        config,
+    } = setup().await;
+
+    let mut depth_cron = DepthHistoryCron::new(pool.clone(), config.clone());
+    depth_cron.fetch_and_store().await.unwrap();
+    let mut earnings_cron = EarningsHistoryCron::new(pool.clone(), config.clone());
+    earnings_cron.fetch_and_store().await.unwrap();
+    let mut swap_cron = SwapHistoryCron::new(pool.clone(), config.clone());
+    swap_cron.fetch_and_store().await.unwrap();
+    let mut runepool_cron = RunepoolUnitsHistoryCron::new(pool.clone(), config.clone());
+    runepool_cron.fetch_and_store().await.unwrap();
+
+    assert_eq!(stored_rows(&pool).await, [2; 4]);
+    assert_eq!(stored_depths(&pool).await, [107402637405, 107446183502]);
+
+    let sort = Sort::start_time(Order::Asc);
+    let stored = earnings::query_intervals(&pool, &Default::default(), sort, None)
+        .await
+        .unwrap();
+    let pools: Vec<&str> = stored[1].pools.iter().map(|p| p.pool.as_str()).collect();
+    assert_eq!(pools, ["BTC.BTC", "ETH.ETH"]);
+    assert_eq!(stored[1].earnings, 113405283155);
+
+    let stored = runepool::query_intervals(&pool, &Default::default(), sort, None)
+        .await
+        .unwrap();
+    assert_eq!(stored[1].units, 4893719228400);
+
+    // Every cron starts at the configured history start
+    let requests = midgard.requests();
+    assert_eq!(requests.len(), 4);
+    assert!(requests[0].starts_with(&format!("/history/depths/{}?interval=hour", DEPTH_POOL)));
+    assert!(requests
+        .iter()
+        .all(|request| request.contains(&format!("from={}", HISTORY_START))));
+}
+
+#[tokio::test]
+async fn crons_wait_out_rate_limits() {
+    let Setup {
+        midgard,
+        pool,
+        config,
+    } = setup().await;
+    midgard.queue(Dataset::Swap, [Reply::SlowDown, Reply::SlowDown]);
+    midgard.queue(Dataset::Runepool, [Reply::SlowDown]);
+
+    let mut swap_cron = SwapHistoryCron::new(pool.clone(), config.clone());
+    swap_cron.fetch_and_store().await.unwrap();
+    let mut runepool_cron = RunepoolUnitsHistoryCron::new(pool.clone(), config.clone());
+    runepool_cron.fetch_latest_hour().await.unwrap();
+
+    assert_eq!(stored_rows(&pool).await, [0, 0, 2, 2]);
+    assert_eq!(midgard.requests().len(), 5);
+}
+
+#[tokio::test]
+async fn malformed_payloads_store_nothing() {
+    let Setup {
+        midgard,
+        pool,
+        config,
+    } = setup().await;
+    let malformed = || MALFORMED.iter().map(|body| Reply::Body(body));
+    for dataset in Dataset::ALL {
+        midgard.queue(dataset, malformed());
+    }
+
+    // The hourly fetch gives up on a broken answer and leaves the next cycle to try again
+    for _ in MALFORMED {
+        let mut cron = DepthHistoryCron::new(pool.clone(), config.clone());
+        assert!(cron.fetch_latest_hour().await.is_err());
+        let mut cron = EarningsHistoryCron::new(pool.clone(), config.clone());
+        assert!(cron.fetch_latest_hour().await.is_err());
+        let mut cron = SwapHistoryCron::new(pool.clone(), config.clone());
+        assert!(cron.fetch_latest_hour().await.is_err());
+        let mut cron = RunepoolUnitsHistoryCron::new(pool.clone(), config.clone());
+        assert!(cron.fetch_latest_hour().await.is_err());
+    }
+    assert_eq!(stored_rows(&pool).await, [0; 4]);
+
+    // The history cron asks again until it gets something it can store
+    midgard.queue(Dataset::Depth, malformed());
+    let mut cron = DepthHistoryCron::new(pool.clone(), config.clone());
+    cron.fetch_and_store().await.unwrap();
+    assert_eq!(stored_rows(&pool).await, [2, 0, 0, 0]);
+    assert_eq!(
+        midgard.requests().len(),
+        4 * MALFORMED.len() + MALFORMED.len() + 1
+    );
+}
+
+#[tokio::test]
+async fn refetching_an_hour_keeps_one_row_per_interval() {
+    let Setup { pool, config, .. } = setup().await;
+
+    let fetcher = HourlyFetcher::new(pool.clone(), config.clone());
+    assert_eq!(fetcher.fetch_cycle().await, 0);
+    assert_eq!(fetcher.fetch_cycle().await, 0);
+
+    assert_eq!(stored_rows(&pool).await, [2; 4]);
+}
+
+async fn get(base: &str, path: &str) -> Value {
+    let response = reqwest::get(format!("{}{}", base, path)).await.unwrap();
+    assert!(
+        response.status().is_success(),
+        "{} answered {}",
+        path,
+        response.status()
+    );
+    response.json().await.unwrap()
+}
+
+#[tokio::test]
+async fn api_serves_what_the_crons_stored() {
+    let Setup { pool, config, .. } = setup().await;
+    assert_eq!(
+        HourlyFetcher::new(pool.clone(), config.clone())
+            .fetch_cycle()
+            .await,
+        0
+    );
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base = format!("http://{}", listener.local_addr().unwrap());
+    let app = crate::api::router::app(AppState { pool, config });
+    tokio::spawn(async move { axum::serve(listener, app).await });
+
+    let depths = get(&base, "/depth_history").await;
+    assert_eq!(depths["intervals"].as_array().unwrap().len(), 2);
+    assert_eq!(depths["meta"]["startAssetDepth"], "107402637405");
+    assert_eq!(depths["meta"]["endAssetDepth"], "107446183502");
+
+    let earnings = get(&base, "/earning_history?pool=ETH.ETH&order=desc&limit=1").await;
+    assert_eq!(earnings["intervals"][0]["earnings"], "113405283155");
+    assert_eq!(earnings["intervals"][0]["startTime"], "1704070800");
+
+    let swaps = get(
+        &base,
+        "/swap_history?sort_by=total_volume&order=desc&limit=1",
+    )
+    .await;
+    assert_eq!(swaps["intervals"][0]["startTime"], "1704070800");
+
+    let decimal = get(&base, "/depth_history?units=decimal").await;
+    assert_eq!(decimal["meta"]["startAssetDepth"], "1074.02637405");
+    assert_eq!(decimal["intervals"][0]["units"], "1033651480437633");
+    let usd = get(&base, "/depth_history?units=usd&numbers=native").await;
+    let asset_depth_usd = usd["meta"]["startAssetDepth"].as_f64().unwrap();
+    assert!((asset_depth_usd - 1074.02637405 * 43289.5630195876).abs() < 1e-3);
+
+    let invalid = reqwest::get(format!("{}/swap_history?units=sats", base))
+        .await
+        .unwrap();
+    assert_eq!(invalid.status(), reqwest::StatusCode::BAD_REQUEST);
+
+    let runepool = get(&base, "/runepool_units_history").await;
+    assert_eq!(runepool["meta"]["startUnits"], "4893622139017");
+    assert_eq!(runepool["meta"]["endCount"], "1463");
+
+    let empty = get(&base, "/runepool_units_history?units_gt=9999999999999").await;
+    assert_eq!(
+        empty["data"],
+        "no data found in the database for the given params"
+    );
+}
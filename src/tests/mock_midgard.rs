@@ -0,0 +1,101 @@
+use crate::core::models::dataset::Dataset;
+use axum::{extract::State, http::StatusCode, http::Uri, routing::get, Router};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use tokio::net::TcpListener;
+
+pub const DEPTHS: &str = include_str!("../../tests/fixtures/midgard/depths.json");
+pub const EARNINGS: &str = include_str!("../../tests/fixtures/midgard/earnings.json");
+pub const SWAPS: &str = include_str!("../../tests/fixtures/midgard/swaps.json");
+pub const RUNEPOOL: &str = include_str!("../../tests/fixtures/midgard/runepool.json");
+
+// What the mock answers a request with, the recorded fixture once nothing is queued
+pub enum Reply {
+    // Midgard's rate limit answer, the crons look for the words in the body
+    SlowDown,
+    Body(&'static str),
+}
+
+#[derive(Clone, Default)]
+struct MockState {
+    queued: Arc<Mutex<HashMap<Dataset, VecDeque<Reply>>>>,
+    requests: Arc<Mutex<Vec<String>>>,
+}
+
+// Serves the four history endpoints on a random local port until the test ends
+pub struct MockMidgard {
+    pub url: String,
+    state: MockState,
+}
+
+impl MockMidgard {
+    pub async fn start() -> Self {
+        let state = MockState::default();
+        let app = Router::new()
+            .route("/history/depths/:pool", get(reply))
+            .route("/history/earnings", get(reply))
+            .route("/history/swaps", get(reply))
+            .route("/history/runepool", get(reply))
+            .with_state(state.clone());
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        Self { url, state }
+    }
+
+    // The next requests for `dataset` get these replies, in order
+    pub fn queue(&self, dataset: Dataset, replies: impl IntoIterator<Item = Reply>) {
+        self.state
+            .queued
+            .lock()
+            .unwrap()
+            .entry(dataset)
+            .or_default()
+            .extend(replies);
+    }
+
+    // Path and query of every request so far
+    pub fn requests(&self) -> Vec<String> {
+        self.state.requests.lock().unwrap().clone()
+    }
+}
+
+fn dataset(path: &str) -> Dataset {
+    match path {
+        path if path.starts_with("/history/depths/") => Dataset::Depth,
+        "/history/earnings" => Dataset::Earnings,
+        "/history/swaps" => Dataset::Swap,
+        _ => Dataset::Runepool,
+    }
+}
+
+fn fixture(dataset: Dataset) -> &'static str {
+    match dataset {
+        Dataset::Depth => DEPTHS,
+        Dataset::Earnings => EARNINGS,
+        Dataset::Swap => SWAPS,
+        Dataset::Runepool => RUNEPOOL,
+    }
+}
+
+async fn reply(State(state): State<MockState>, uri: Uri) -> (StatusCode, &'static str) {
+    state.requests.lock().unwrap().push(uri.to_string());
+
+    let dataset = dataset(uri.path());
+    let queued = state
+        .queued
+        .lock()
+        .unwrap()
+        .get_mut(&dataset)
+        .and_then(VecDeque::pop_front);
+    match queued {
+        Some(Reply::SlowDown) => (
+            StatusCode::TOO_MANY_REQUESTS,
+            "Too many requests, slow down",
+        ),
+        Some(Reply::Body(body)) => (StatusCode::OK, body),
+        None => (StatusCode::OK, fixture(dataset)),
+    }
+}
@@ -0,0 +1,6 @@
+// End to end tests of the service: the crons against an in-process Midgard, the api on top of what
+// they stored, and the parsing of what Midgard answers. Everything runs on an in-memory SQLite
+// database.
+mod ingestion;
+mod mock_midgard;
+mod responses;
@@ -0,0 +1,76 @@
+use super::mock_midgard::{DEPTHS, EARNINGS, RUNEPOOL, SWAPS};
+use crate::core::models::{
+    depth_history::DepthHistoryResponse, earnings_history::EarningsHistoryResponse,
+    runepool_units_history::RunepoolUnitsHistoryResponse, swap_history::SwapHistoryResponse,
+};
+use proptest::prelude::*;
+use serde_json::Value;
+
+// Parses `body` as every history response, each one must answer with a value or an error
+fn parse_all(body: &[u8]) -> [bool; 4] {
+    [
+        serde_json::from_slice::<DepthHistoryResponse>(body).is_ok(),
+        serde_json::from_slice::<EarningsHistoryResponse>(body).is_ok(),
+        serde_json::from_slice::<SwapHistoryResponse>(body).is_ok(),
+        serde_json::from_slice::<RunepoolUnitsHistoryResponse>(body).is_ok(),
+    ]
+}
+
+// The fixture with one field of its first interval replaced
+fn with_field(fixture: &str, field: usize, value: Value) -> Vec<u8> {
+    let mut response: Value = serde_json::from_str(fixture).unwrap();
+    let interval = response["intervals"][0].as_object_mut().unwrap();
+    let key = interval.keys().nth(field % interval.len()).unwrap().clone();
+    interval.insert(key, value);
+    serde_json::to_vec(&response).unwrap()
+}
+
+fn json_value() -> impl Strategy<Value = Value> {
+    prop_oneof![
+        Just(Value::Null),
+        any::<bool>().prop_map(Value::from),
+        any::<i64>().prop_map(Value::from),
+        any::<u64>().prop_map(Value::from),
+        any::<f64>().prop_map(Value::from),
+        "\\PC*".prop_map(Value::from),
+        "-?[0-9,]{0,25}(\\.[0-9]{0,4})?(e[0-9]{1,3})?".prop_map(Value::from),
+        Just(Value::from("NaN")),
+        Just(serde_json::json!([{ "pool": "BTC.BTC" }])),
+    ]
+}
+
+#[test]
+fn recorded_responses_parse() {
+    assert!(parse_all(DEPTHS.as_bytes())[0]);
+    assert!(parse_all(EARNINGS.as_bytes())[1]);
+    assert!(parse_all(SWAPS.as_bytes())[2]);
+    assert!(parse_all(RUNEPOOL.as_bytes())[3]);
+}
+
+#[test]
+fn fractional_earnings_are_rejected() {
+    let mut response: Value = serde_json::from_str(EARNINGS).unwrap();
+    response["intervals"][0]["earnings"] = Value::from("119377046022.7");
+    assert!(serde_json::from_value::<EarningsHistoryResponse>(response).is_err());
+}
+
+proptest! {
+    #[test]
+    fn arbitrary_bytes_never_panic(body in proptest::collection::vec(any::<u8>(), 0..512)) {
+        parse_all(&body);
+    }
+
+    #[test]
+    fn arbitrary_fields_never_panic(field in 0..64usize, value in json_value()) {
+        for fixture in [DEPTHS, EARNINGS, SWAPS, RUNEPOOL] {
+            parse_all(&with_field(fixture, field, value.clone()));
+        }
+    }
+
+    #[test]
+    fn amounts_survive_a_round_trip(value: u128) {
+        let body = with_field(RUNEPOOL, 3, Value::from(value.to_string()));
+        let response: RunepoolUnitsHistoryResponse = serde_json::from_slice(&body).unwrap();
+        prop_assert_eq!(response.intervals[0].units, value);
+    }
+}